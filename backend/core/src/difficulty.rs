@@ -0,0 +1,72 @@
+use crate::survey::SurveyTally;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+pub struct DifficultyInputs {
+    pub length: usize,
+    pub digits: usize,
+    pub candidates: usize,
+    pub opener_overlap: f64,
+}
+
+pub fn estimated_solve_rate(inputs: &DifficultyInputs) -> f64 {
+    let length_penalty = 0.04 * inputs.length.saturating_sub(4) as f64;
+    let digit_penalty = 0.03 * inputs.digits as f64;
+    let candidate_penalty = 0.05 * (inputs.candidates.max(1) as f64).log10();
+    let opener_bonus = 0.1 * inputs.opener_overlap;
+
+    (0.95 - length_penalty - digit_penalty - candidate_penalty + opener_bonus).clamp(0.05, 0.99)
+}
+
+// blend the model estimate with player ratings, trusting the ratings more as they accumulate
+pub fn adjusted_solve_rate(estimate: f64, survey: &SurveyTally) -> f64 {
+    const SURVEY_SHIFT: f64 = 0.3;
+    const HALF_WEIGHT_RESPONSES: f64 = 20.0;
+
+    let total = survey.total() as f64;
+    if total == 0.0 {
+        return estimate;
+    }
+
+    let sentiment = (survey.too_easy as f64 - survey.too_hard as f64) / total;
+    let weight = total / (total + HALF_WEIGHT_RESPONSES);
+
+    (estimate + weight * SURVEY_SHIFT * sentiment).clamp(0.05, 0.99)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::difficulty::{DifficultyInputs, adjusted_solve_rate, estimated_solve_rate};
+    use crate::survey::SurveyTally;
+
+    #[test]
+    fn test_estimated_solve_rate() {
+        let easy = DifficultyInputs {
+            length: 4,
+            digits: 0,
+            candidates: 100,
+            opener_overlap: 1.0,
+        };
+        let hard = DifficultyInputs {
+            length: 8,
+            digits: 2,
+            candidates: 5000,
+            opener_overlap: 0.0,
+        };
+
+        assert!(estimated_solve_rate(&easy) > estimated_solve_rate(&hard));
+        assert!((0.05..=0.99).contains(&estimated_solve_rate(&hard)));
+    }
+
+    #[test]
+    fn test_adjusted_solve_rate() {
+        assert_eq!(adjusted_solve_rate(0.5, &SurveyTally::default()), 0.5);
+
+        let too_hard = SurveyTally {
+            too_easy: 1,
+            just_right: 4,
+            too_hard: 15,
+        };
+        assert!(adjusted_solve_rate(0.5, &too_hard) < 0.5);
+    }
+}
@@ -0,0 +1,363 @@
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+    Normal,
+    Hard,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum GuessResult {
+    Invalid(InvalidGuess),
+    Valid(ValidGuess),
+}
+
+impl Serialize for GuessResult {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("GuessResult", 2)?;
+
+        match self {
+            GuessResult::Invalid(invalid) => {
+                state.serialize_field("type", "invalid")?;
+                state.serialize_field("data", invalid)
+            }
+            GuessResult::Valid(valid) => {
+                state.serialize_field("type", "valid")?;
+                state.serialize_field("data", valid)
+            }
+        }?;
+
+        state.end()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidGuess {
+    InternalError(String),
+    NotEnoughLetters,
+    TooManyLetters,
+    InvalidLetter,
+    NotInCorpus,
+    GameOver,
+    // a race whose second player hasn't joined yet
+    NotStarted,
+    HintNotUsed { letter: char, position: usize },
+    MissingLetter { letter: char },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ValidGuess {
+    pub is_correct: bool,
+    pub result: Vec<LetterFeedback>,
+    #[serde(default)]
+    pub reveal: RevealTiming,
+}
+
+impl ValidGuess {
+    pub fn new(is_correct: bool, result: Vec<LetterFeedback>) -> Self {
+        let reveal = RevealTiming::for_feedback(&result, is_correct);
+        ValidGuess {
+            is_correct,
+            result,
+            reveal,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct RevealTiming {
+    pub order: Vec<usize>,
+    pub delays_ms: Vec<u32>,
+    pub celebrate_after_ms: Option<u32>,
+}
+
+impl RevealTiming {
+    const FLIP_MS: u32 = 500;
+    const ABSENT_STEP_MS: u32 = 250;
+    const HIT_STEP_MS: u32 = 350;
+
+    // letters flip left to right, lingering slightly longer on hits so they register
+    pub fn for_feedback(result: &[LetterFeedback], is_correct: bool) -> Self {
+        let mut delays_ms = Vec::with_capacity(result.len());
+        let mut elapsed = 0;
+        for feedback in result {
+            delays_ms.push(elapsed);
+            elapsed += match feedback {
+                LetterFeedback::Absent => Self::ABSENT_STEP_MS,
+                LetterFeedback::Correct | LetterFeedback::Present => Self::HIT_STEP_MS,
+            };
+        }
+
+        RevealTiming {
+            order: (0..result.len()).collect(),
+            celebrate_after_ms: is_correct
+                .then(|| delays_ms.last().copied().unwrap_or_default() + Self::FLIP_MS),
+            delays_ms,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum LetterFeedback {
+    Correct,
+    Present,
+    Absent,
+}
+
+impl LetterFeedback {
+    pub fn emoji(&self) -> char {
+        match self {
+            LetterFeedback::Correct => '🟩',
+            LetterFeedback::Present => '🟨',
+            LetterFeedback::Absent => '⬛',
+        }
+    }
+}
+
+// how many symbols could still be the answer, in bands coarse enough not to give it away
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Temperature {
+    // more than 1000
+    Cold,
+    // 100 to 1000
+    Cool,
+    // 10 to 99
+    Warm,
+    // fewer than 10
+    Hot,
+}
+
+impl Temperature {
+    pub fn for_candidates(count: usize) -> Self {
+        match count {
+            0..10 => Temperature::Hot,
+            10..100 => Temperature::Warm,
+            100..=1000 => Temperature::Cool,
+            _ => Temperature::Cold,
+        }
+    }
+}
+
+// mirrors the on-screen keyboard
+pub fn is_playable(letter: char) -> bool {
+    letter.is_ascii_uppercase() || letter.is_ascii_digit() || letter == '-'
+}
+
+pub fn check_length(word: &[char], len: usize) -> Option<InvalidGuess> {
+    if word.len() < len {
+        Some(InvalidGuess::NotEnoughLetters)
+    } else if word.len() > len {
+        Some(InvalidGuess::TooManyLetters)
+    } else {
+        None
+    }
+}
+
+// hard mode keeps every Correct letter in place and reuses every Present one, as often as earlier
+// feedback proved it appears
+pub fn check_hints<'a>(
+    guess: &[char],
+    previous: impl IntoIterator<Item = (&'a [char], &'a [LetterFeedback])>,
+) -> Option<InvalidGuess> {
+    for (word, feedback) in previous {
+        for (position, (&letter, result)) in word.iter().zip(feedback).enumerate() {
+            if *result == LetterFeedback::Correct && guess.get(position) != Some(&letter) {
+                return Some(InvalidGuess::HintNotUsed { letter, position });
+            }
+        }
+
+        for (&letter, result) in word.iter().zip(feedback) {
+            if *result != LetterFeedback::Present {
+                continue;
+            }
+
+            let hits = |word: &[char], feedback: Option<&[LetterFeedback]>| {
+                word.iter()
+                    .enumerate()
+                    .filter(|&(i, &c)| {
+                        c == letter
+                            && feedback.is_none_or(|feedback| feedback[i] != LetterFeedback::Absent)
+                    })
+                    .count()
+            };
+            if hits(guess, None) < hits(word, Some(feedback)) {
+                return Some(InvalidGuess::MissingLetter { letter });
+            }
+        }
+    }
+
+    None
+}
+
+// a candidate could still be the answer if it would have scored every earlier guess the same way
+pub fn is_consistent<'a>(
+    candidate: &[char],
+    previous: impl IntoIterator<Item = (&'a [char], &'a [LetterFeedback])>,
+) -> bool {
+    previous.into_iter().all(|(word, feedback)| {
+        word.len() == candidate.len() && score_guess(word, candidate).result == feedback
+    })
+}
+
+pub fn score_guess(guess: &[char], word: &[char]) -> ValidGuess {
+    let mut char_counts: HashMap<char, usize> = HashMap::new();
+    for letter in word {
+        *char_counts.entry(*letter).or_default() += 1;
+    }
+
+    let mut result = vec![LetterFeedback::Absent; word.len()];
+
+    for (i, (guessed, actual)) in guess.iter().zip(word).enumerate() {
+        if guessed == actual {
+            result[i] = LetterFeedback::Correct;
+            *char_counts.get_mut(guessed).unwrap() -= 1;
+        }
+    }
+
+    for (i, guessed) in guess.iter().enumerate() {
+        if result[i] == LetterFeedback::Absent
+            && let Some(count) = char_counts.get_mut(guessed)
+            && *count > 0
+        {
+            result[i] = LetterFeedback::Present;
+            *count -= 1;
+        }
+    }
+
+    let is_correct = result
+        .iter()
+        .all(|&feedback| feedback == LetterFeedback::Correct);
+
+    ValidGuess::new(is_correct, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feedback::{
+        InvalidGuess, LetterFeedback, RevealTiming, Temperature, check_hints, check_length,
+        is_consistent, score_guess,
+    };
+
+    fn chars(word: &str) -> Vec<char> {
+        word.chars().collect()
+    }
+
+    #[test]
+    fn test_check_length() {
+        assert_eq!(
+            check_length(&chars("MIB"), 4),
+            Some(InvalidGuess::NotEnoughLetters)
+        );
+        assert_eq!(
+            check_length(&chars("MIB22"), 4),
+            Some(InvalidGuess::TooManyLetters)
+        );
+        assert_eq!(check_length(&chars("MIB2"), 4), None);
+    }
+
+    #[test]
+    fn test_score_guess() {
+        let scored = score_guess(&chars("MIB2"), &chars("MIB2"));
+        assert!(scored.is_correct);
+        assert_eq!(scored.result, vec![LetterFeedback::Correct; 4]);
+
+        let scored = score_guess(&chars("2IBM"), &chars("MIB2"));
+        assert!(!scored.is_correct);
+        assert_eq!(
+            scored.result,
+            vec![
+                LetterFeedback::Present,
+                LetterFeedback::Correct,
+                LetterFeedback::Correct,
+                LetterFeedback::Present
+            ]
+        );
+
+        // duplicate letters are only marked once per occurrence in the answer
+        let scored = score_guess(&chars("2222"), &chars("MIB2"));
+        assert_eq!(
+            scored.result,
+            vec![
+                LetterFeedback::Absent,
+                LetterFeedback::Absent,
+                LetterFeedback::Absent,
+                LetterFeedback::Correct
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_hints() {
+        let opener = chars("2IBM");
+        let feedback = score_guess(&opener, &chars("MIB2")).result;
+        let previous = [(opener.as_slice(), feedback.as_slice())];
+
+        assert_eq!(check_hints(&chars("MIB2"), previous), None);
+        assert_eq!(
+            check_hints(&chars("MAB2"), previous),
+            Some(InvalidGuess::HintNotUsed {
+                letter: 'I',
+                position: 1
+            })
+        );
+        assert_eq!(
+            check_hints(&chars("XIBM"), previous),
+            Some(InvalidGuess::MissingLetter { letter: '2' })
+        );
+
+        // one Present 2 next to an Absent one only proves a single 2
+        let opener = chars("22AA");
+        let feedback = score_guess(&opener, &chars("MIB2")).result;
+        let previous = [(opener.as_slice(), feedback.as_slice())];
+        assert_eq!(check_hints(&chars("XYZ2"), previous), None);
+        assert_eq!(
+            check_hints(&chars("XYZW"), previous),
+            Some(InvalidGuess::MissingLetter { letter: '2' })
+        );
+    }
+
+    #[test]
+    fn test_is_consistent() {
+        let opener = chars("2IBM");
+        let feedback = score_guess(&opener, &chars("MIB2")).result;
+        let previous = [(opener.as_slice(), feedback.as_slice())];
+
+        assert!(is_consistent(&chars("MIB2"), previous));
+        assert!(!is_consistent(&chars("MIB1"), previous));
+        assert!(!is_consistent(&chars("MIB22"), previous));
+        assert!(is_consistent(&chars("ANYTHING"), []));
+
+        assert_eq!(Temperature::for_candidates(0), Temperature::Hot);
+        assert_eq!(Temperature::for_candidates(10), Temperature::Warm);
+        assert_eq!(Temperature::for_candidates(1000), Temperature::Cool);
+        assert_eq!(Temperature::for_candidates(1001), Temperature::Cold);
+    }
+
+    #[test]
+    fn test_reveal_timing() {
+        let reveal = RevealTiming::for_feedback(
+            &[
+                LetterFeedback::Correct,
+                LetterFeedback::Absent,
+                LetterFeedback::Present,
+            ],
+            false,
+        );
+        assert_eq!(reveal.order, vec![0, 1, 2]);
+        assert_eq!(reveal.delays_ms, vec![0, 350, 600]);
+        assert_eq!(reveal.celebrate_after_ms, None);
+
+        let reveal = RevealTiming::for_feedback(&[LetterFeedback::Correct; 2], true);
+        assert_eq!(reveal.celebrate_after_ms, Some(850));
+    }
+}
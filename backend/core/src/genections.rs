@@ -0,0 +1,112 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+pub const GROUP_SIZE: usize = 4;
+pub const NUM_GROUPS: usize = 4;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Category {
+    pub name: String,
+    pub symbols: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Board {
+    pub categories: Vec<Category>,
+    pub layout: Vec<String>,
+}
+
+impl Board {
+    pub fn category_of(&self, symbol: &str) -> Option<usize> {
+        self.categories
+            .iter()
+            .position(|category| category.symbols.iter().any(|s| s == symbol))
+    }
+}
+
+// families whose prefixes share a stem look alike on the board, e.g. RPL and RPS
+fn stem(prefix: &str) -> &str {
+    &prefix[..prefix.len().min(2)]
+}
+
+// orders families so that up to `red_herrings` look-alike pairs come first, followed by
+// families that don't collide with any stem already on the board
+pub fn pick_families<'a, R: Rng>(
+    families: &[(&'a str, &'a str)],
+    red_herrings: usize,
+    rng: &mut R,
+) -> Vec<(&'a str, &'a str)> {
+    let mut families = families.to_vec();
+    families.shuffle(rng);
+
+    let mut picked: Vec<(&str, &str)> = Vec::with_capacity(families.len());
+    for _ in 0..red_herrings.min(NUM_GROUPS / 2) {
+        let pair = families.iter().enumerate().find_map(|(i, a)| {
+            families[i + 1..]
+                .iter()
+                .position(|b| stem(a.0) == stem(b.0))
+                .map(|j| (i, i + 1 + j))
+        });
+        let Some((i, j)) = pair else {
+            break;
+        };
+
+        picked.push(families[i]);
+        picked.push(families[j]);
+        families.retain(|family| stem(family.0) != stem(picked[picked.len() - 1].0));
+    }
+
+    let mut stems: Vec<&str> = picked.iter().map(|family| stem(family.0)).collect();
+    for family in families {
+        if !stems.contains(&stem(family.0)) {
+            stems.push(stem(family.0));
+            picked.push(family);
+        }
+    }
+
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::genections::{NUM_GROUPS, pick_families, stem};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    const FAMILIES: [(&str, &str); 8] = [
+        ("RPL", "Large ribosomal subunit"),
+        ("RPS", "Small ribosomal subunit"),
+        ("CDK", "Cyclin-dependent kinases"),
+        ("CDH", "Cadherins"),
+        ("COL", "Collagens"),
+        ("COX", "Cytochrome c oxidase"),
+        ("HOX", "Homeobox genes"),
+        ("KRT", "Keratins"),
+    ];
+
+    fn herrings(families: &[(&str, &str)]) -> usize {
+        let board = &families[..NUM_GROUPS];
+        board
+            .iter()
+            .enumerate()
+            .filter(|(i, a)| board[i + 1..].iter().any(|b| stem(a.0) == stem(b.0)))
+            .count()
+    }
+
+    #[test]
+    fn test_pick_families() {
+        for seed in 0..20 {
+            for red_herrings in 0..=2 {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let families = pick_families(&FAMILIES, red_herrings, &mut rng);
+
+                assert!(families.len() >= NUM_GROUPS);
+                assert_eq!(herrings(&families), red_herrings);
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(herrings(&pick_families(&FAMILIES, 5, &mut rng)), 2);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod difficulty;
+pub mod feedback;
+pub mod genections;
+pub mod spelling_gene;
+pub mod streak;
+pub mod survey;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,412 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+pub const VALID_LETTERS: [&str; 27] = [
+    "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R", "S",
+    "T", "U", "V", "W", "X", "Y", "Z", "-",
+];
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct SpellingGeneGame {
+    #[serde(flatten)]
+    pub metadata: SpellingGeneMetadata,
+    pub valid_symbols: BTreeSet<String>,
+    // alias symbols included in valid_symbols; they're easier to stumble on, so score less
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub aliases: BTreeSet<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct SpellingGeneMetadata {
+    pub outer_letters: Vec<&'static str>,
+    pub center_letter: &'static str,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum SpellingGeneGuessResult {
+    Invalid(InvalidSpellingGeneGuess),
+    Valid { symbol: String },
+}
+
+// near misses name the rule a real gene broke; NotInPuzzle covers genes the puzzle happened to skip
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidSpellingGeneGuess {
+    InternalError(String),
+    NotAGene,
+    TooShort { min_length: usize },
+    MissingCenterLetter,
+    DisallowedLetter { letter: char },
+    NotInPuzzle,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringPreset {
+    #[default]
+    Classic,
+    RarityWeighted,
+    Flat,
+}
+
+impl FromStr for ScoringPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(ScoringPreset::Classic),
+            "rarity_weighted" => Ok(ScoringPreset::RarityWeighted),
+            "flat" => Ok(ScoringPreset::Flat),
+            _ => Err(format!("Unknown scoring preset {s}")),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct LetterUsage {
+    pub letter: String,
+    pub found_starting: usize,
+    pub total_starting: usize,
+    pub found_containing: usize,
+    pub total_containing: usize,
+}
+
+// one line of the two-letter list
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct PrefixUsage {
+    pub prefix: String,
+    pub found: usize,
+    pub total: usize,
+}
+
+pub fn sample_letters<R: Rng>(rng: &mut R, count: usize) -> Vec<&'static str> {
+    let mut letters = VALID_LETTERS.to_vec();
+    letters.shuffle(rng);
+    letters.truncate(count);
+
+    letters
+}
+
+// the keyboard and the letter buttons both send uppercase, but typed guesses may not
+pub fn normalize_guess(guess: &str) -> String {
+    guess.trim().to_ascii_uppercase()
+}
+
+pub fn check_guess(
+    guess: &str,
+    game: &SpellingGeneGame,
+    min_length: usize,
+    is_gene: impl FnOnce(&str) -> bool,
+) -> SpellingGeneGuessResult {
+    if game.valid_symbols.contains(guess) {
+        return SpellingGeneGuessResult::Valid {
+            symbol: guess.to_string(),
+        };
+    }
+
+    let center_letter = game.metadata.center_letter;
+    let reason = if !is_gene(guess) {
+        InvalidSpellingGeneGuess::NotAGene
+    } else if guess.chars().count() < min_length {
+        InvalidSpellingGeneGuess::TooShort { min_length }
+    } else if !guess.contains(center_letter) {
+        InvalidSpellingGeneGuess::MissingCenterLetter
+    } else if let Some(letter) = guess.chars().find(|&c| {
+        !game
+            .metadata
+            .outer_letters
+            .iter()
+            .chain([&center_letter])
+            .any(|letter| letter.starts_with(c))
+    }) {
+        InvalidSpellingGeneGuess::DisallowedLetter { letter }
+    } else {
+        InvalidSpellingGeneGuess::NotInPuzzle
+    };
+
+    SpellingGeneGuessResult::Invalid(reason)
+}
+
+// every symbol spelled only from the puzzle's letters that uses the center one
+pub fn game_from_letters(
+    all_symbols: &BTreeSet<String>,
+    outer_letters: Vec<&'static str>,
+    center_letter: &'static str,
+) -> SpellingGeneGame {
+    let letters: BTreeSet<char> = outer_letters
+        .iter()
+        .chain([&center_letter])
+        .flat_map(|letter| letter.chars())
+        .collect();
+    let center_char = center_letter.chars().next().unwrap_or_default();
+
+    SpellingGeneGame {
+        valid_symbols: all_symbols
+            .iter()
+            .filter(|symbol| {
+                symbol.contains(center_char) && symbol.chars().all(|c| letters.contains(&c))
+            })
+            .cloned()
+            .collect(),
+        metadata: SpellingGeneMetadata {
+            outer_letters,
+            center_letter,
+        },
+        aliases: BTreeSet::new(),
+    }
+}
+
+pub fn pick_game<R: Rng>(
+    all_symbols: &BTreeSet<String>,
+    min_words: usize,
+    num_letters: u8,
+    max_iters: usize,
+    rng: &mut R,
+) -> Option<SpellingGeneGame> {
+    for _ in 0..max_iters {
+        let mut letters = sample_letters(rng, num_letters as usize);
+        let center_letter = letters.pop().unwrap();
+
+        let game = game_from_letters(all_symbols, letters, center_letter);
+        if game.valid_symbols.len() >= min_words {
+            return Some(game);
+        }
+    }
+
+    None
+}
+
+// classic mirrors word-game convention: minimum-length words are worth a point, longer ones their
+// length; rarity-weighted gives each letter more points the fewer valid symbols contain it
+pub fn score_word(preset: ScoringPreset, word: &str, valid_symbols: &BTreeSet<String>) -> u32 {
+    match preset {
+        ScoringPreset::Flat => 1,
+        ScoringPreset::Classic => match word.chars().count() {
+            len if len <= 4 => 1,
+            len => len as u32,
+        },
+        ScoringPreset::RarityWeighted => word
+            .chars()
+            .map(|letter| {
+                let containing = valid_symbols
+                    .iter()
+                    .filter(|symbol| symbol.contains(letter))
+                    .count()
+                    .max(1);
+                1 + (valid_symbols.len() / containing).max(1).ilog2()
+            })
+            .sum(),
+    }
+}
+
+pub fn total_score<'a>(
+    preset: ScoringPreset,
+    words: impl IntoIterator<Item = &'a String>,
+    valid_symbols: &BTreeSet<String>,
+    aliases: &BTreeSet<String>,
+) -> u32 {
+    words
+        .into_iter()
+        .map(|word| match score_word(preset, word, valid_symbols) {
+            score if aliases.contains(word) => score.div_ceil(2),
+            score => score,
+        })
+        .sum()
+}
+
+pub fn letter_usage(
+    letters: &[&str],
+    valid_symbols: &BTreeSet<String>,
+    found: &BTreeSet<String>,
+) -> Vec<LetterUsage> {
+    let count = |symbols: &BTreeSet<String>, f: &dyn Fn(&String) -> bool| {
+        symbols.iter().filter(|symbol| f(symbol)).count()
+    };
+
+    letters
+        .iter()
+        .map(|&letter| {
+            let starting = |symbol: &String| symbol.starts_with(letter);
+            let containing = |symbol: &String| symbol.contains(letter);
+
+            LetterUsage {
+                letter: letter.to_string(),
+                found_starting: count(found, &starting),
+                total_starting: count(valid_symbols, &starting),
+                found_containing: count(found, &containing),
+                total_containing: count(valid_symbols, &containing),
+            }
+        })
+        .collect()
+}
+
+// only prefixes the puzzle actually uses are listed, in alphabetical order
+pub fn prefix_usage(
+    valid_symbols: &BTreeSet<String>,
+    found: &BTreeSet<String>,
+) -> Vec<PrefixUsage> {
+    let mut usage: BTreeMap<String, PrefixUsage> = BTreeMap::new();
+    for symbol in valid_symbols {
+        let prefix: String = symbol.chars().take(2).collect();
+        let entry = usage.entry(prefix.clone()).or_insert(PrefixUsage {
+            prefix,
+            found: 0,
+            total: 0,
+        });
+        entry.total += 1;
+        entry.found += found.contains(symbol) as usize;
+    }
+
+    usage.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::spelling_gene::{
+        InvalidSpellingGeneGuess, PrefixUsage, ScoringPreset, SpellingGeneGame,
+        SpellingGeneGuessResult, SpellingGeneMetadata, check_guess, game_from_letters,
+        letter_usage, prefix_usage, score_word, total_score,
+    };
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_check_guess() {
+        let game = SpellingGeneGame {
+            metadata: SpellingGeneMetadata {
+                outer_letters: vec!["A", "B", "C", "D", "E", "F"],
+                center_letter: "X",
+            },
+            valid_symbols: ["ABCX"].into_iter().map(String::from).collect(),
+            aliases: BTreeSet::new(),
+        };
+        let genes = ["ABCX", "ABX", "ABCD", "ABCXZ", "FEDX"];
+        let check = |guess| check_guess(guess, &game, 4, |symbol| genes.contains(&symbol));
+
+        assert_eq!(
+            check("ABCX"),
+            SpellingGeneGuessResult::Valid {
+                symbol: "ABCX".to_string()
+            }
+        );
+        assert_eq!(
+            check("ABXX"),
+            SpellingGeneGuessResult::Invalid(InvalidSpellingGeneGuess::NotAGene)
+        );
+        assert_eq!(
+            check("ABX"),
+            SpellingGeneGuessResult::Invalid(InvalidSpellingGeneGuess::TooShort { min_length: 4 })
+        );
+        assert_eq!(
+            check("ABCD"),
+            SpellingGeneGuessResult::Invalid(InvalidSpellingGeneGuess::MissingCenterLetter)
+        );
+        assert_eq!(
+            check("ABCXZ"),
+            SpellingGeneGuessResult::Invalid(InvalidSpellingGeneGuess::DisallowedLetter {
+                letter: 'Z'
+            })
+        );
+        assert_eq!(
+            check("FEDX"),
+            SpellingGeneGuessResult::Invalid(InvalidSpellingGeneGuess::NotInPuzzle)
+        );
+    }
+
+    #[test]
+    fn test_game_from_letters() {
+        let symbols: BTreeSet<String> = ["ABCX", "XXAB", "ABCD", "ABXZ", "X-AB"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let game = game_from_letters(&symbols, vec!["A", "B", "C", "D", "E", "-"], "X");
+
+        assert_eq!(
+            game.valid_symbols,
+            ["ABCX", "X-AB", "XXAB"]
+                .into_iter()
+                .map(String::from)
+                .collect::<BTreeSet<_>>()
+        );
+        assert_eq!(game.metadata.center_letter, "X");
+    }
+
+    #[test]
+    fn test_letter_usage() {
+        let valid: BTreeSet<String> = ["ABCA", "CABA", "BACC"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let found: BTreeSet<String> = ["CABA"].into_iter().map(String::from).collect();
+
+        let usage = letter_usage(&["A", "C"], &valid, &found);
+
+        assert_eq!(usage[0].letter, "A");
+        assert_eq!(usage[0].found_starting, 0);
+        assert_eq!(usage[0].total_starting, 1);
+        assert_eq!(usage[0].found_containing, 1);
+        assert_eq!(usage[0].total_containing, 3);
+
+        assert_eq!(usage[1].letter, "C");
+        assert_eq!(usage[1].found_starting, 1);
+        assert_eq!(usage[1].total_starting, 1);
+    }
+
+    #[test]
+    fn test_prefix_usage() {
+        let valid: BTreeSet<String> = ["ABCA", "ABBA", "CABA"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let found: BTreeSet<String> = ["ABBA"].into_iter().map(String::from).collect();
+
+        assert_eq!(
+            prefix_usage(&valid, &found),
+            [
+                PrefixUsage {
+                    prefix: "AB".to_string(),
+                    found: 1,
+                    total: 2,
+                },
+                PrefixUsage {
+                    prefix: "CA".to_string(),
+                    found: 0,
+                    total: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_score_word() {
+        let valid: BTreeSet<String> = ["ABCA", "CABA", "BACCD", "ABCDEA"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert_eq!(score_word(ScoringPreset::Flat, "ABCDEA", &valid), 1);
+        assert_eq!(score_word(ScoringPreset::Classic, "ABCA", &valid), 1);
+        assert_eq!(score_word(ScoringPreset::Classic, "ABCDEA", &valid), 6);
+        // A, B and C appear everywhere; D in half the symbols; E in a quarter
+        assert_eq!(score_word(ScoringPreset::RarityWeighted, "ABCA", &valid), 4);
+        assert_eq!(
+            score_word(ScoringPreset::RarityWeighted, "ABCDEA", &valid),
+            9
+        );
+
+        assert_eq!(
+            total_score(ScoringPreset::Classic, &valid, &valid, &BTreeSet::new()),
+            13
+        );
+
+        let aliases: BTreeSet<String> = ["BACCD"].into_iter().map(String::from).collect();
+        assert_eq!(
+            total_score(ScoringPreset::Classic, &valid, &valid, &aliases),
+            11
+        );
+        assert_eq!("rarity_weighted".parse(), Ok(ScoringPreset::RarityWeighted));
+    }
+}
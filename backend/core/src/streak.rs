@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Streak {
+    pub current: usize,
+    pub max: usize,
+    pub last_period: Option<u64>,
+}
+
+impl Streak {
+    pub fn record(&mut self, period: u64, won: bool) {
+        if self.last_period == Some(period) {
+            return;
+        }
+
+        if won {
+            self.current = if self.last_period == period.checked_sub(1) {
+                self.current + 1
+            } else {
+                1
+            };
+            self.max = self.max.max(self.current);
+        } else {
+            self.current = 0;
+        }
+
+        self.last_period = Some(period);
+    }
+
+    pub fn as_of(self, period: u64) -> Self {
+        match self.last_period {
+            Some(last) if last + 1 >= period => self,
+            _ => Streak { current: 0, ..self },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::streak::Streak;
+
+    #[test]
+    fn test_streak() {
+        let mut streak = Streak::default();
+        streak.record(10, true);
+        streak.record(11, true);
+        assert_eq!(streak.current, 2);
+
+        // recording the same period twice should not count double
+        streak.record(11, true);
+        assert_eq!(streak.current, 2);
+
+        streak.record(13, true);
+        assert_eq!(streak.current, 1);
+        assert_eq!(streak.max, 2);
+
+        streak.record(14, false);
+        assert_eq!(streak.current, 0);
+        assert_eq!(streak.max, 2);
+
+        streak.record(15, true);
+        assert_eq!(streak.as_of(16).current, 1);
+        assert_eq!(streak.as_of(17).current, 0);
+    }
+}
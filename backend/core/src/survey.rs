@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Rating {
+    TooEasy,
+    JustRight,
+    TooHard,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct SurveyTally {
+    pub too_easy: usize,
+    pub just_right: usize,
+    pub too_hard: usize,
+}
+
+impl SurveyTally {
+    pub fn total(&self) -> usize {
+        self.too_easy + self.just_right + self.too_hard
+    }
+}
@@ -0,0 +1,35 @@
+use crate::feedback::{InvalidGuess, LetterFeedback, check_length, score_guess};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+#[wasm_bindgen(js_name = validateNormalGuess)]
+pub fn validate_normal_guess(guess: &str, num_letters: usize) -> Option<String> {
+    let guess: Vec<char> = guess.chars().collect();
+
+    check_length(&guess, num_letters).map(|reason| {
+        match reason {
+            InvalidGuess::NotEnoughLetters => "not_enough_letters",
+            InvalidGuess::TooManyLetters => "too_many_letters",
+            _ => "invalid_letter",
+        }
+        .to_string()
+    })
+}
+
+#[wasm_bindgen(js_name = scoreGuess)]
+pub fn score(guess: &str, answer: &str) -> Vec<String> {
+    let guess: Vec<char> = guess.chars().collect();
+    let answer: Vec<char> = answer.chars().collect();
+
+    score_guess(&guess, &answer)
+        .result
+        .into_iter()
+        .map(|feedback| {
+            match feedback {
+                LetterFeedback::Correct => "correct",
+                LetterFeedback::Present => "present",
+                LetterFeedback::Absent => "absent",
+            }
+            .to_string()
+        })
+        .collect()
+}
@@ -0,0 +1,316 @@
+use crate::announcements::{self, Announcement, NewAnnouncement};
+use crate::api::genedle::{self, DifficultyReport};
+use crate::api::{self, DriftStats};
+use crate::backup::{self, BackupStatus};
+use crate::config::{self, ConfigReload};
+use crate::corpus;
+use crate::curation::{
+    self, BoardEdit, BoardHistory, BoardVersion, CurationError, Rollback, SpecialPuzzle,
+};
+use crate::error::ApiError;
+use crate::extract::StrictJson;
+use crate::games::{self, PLAYER_KEY, PlayerStats};
+use crate::notifications::{self, Delivery, Notification, Notify};
+use crate::redact::redact;
+use crate::sessions::{SessionMetrics, TrackedStore};
+use crate::slo::{self, SloReport};
+use crate::storage::storage;
+use crate::upstream::{self, Circuit};
+use axum::Json;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use subtle::ConstantTimeEq;
+use tower_sessions::SessionStore;
+use tower_sessions::session::Id;
+
+const AUDIT_KEY: &str = "audit.session-inspections";
+const AUDIT_ENTRIES: usize = 1000;
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct Overview {
+    pub sessions: SessionMetrics,
+    pub backup: BackupStatus,
+    // running on a fallback gene list because HGNC couldn't be reached
+    pub degraded: bool,
+    // whether genenames.org is being called, or failed fast while it recovers
+    pub upstream: Circuit,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AnswerOverride {
+    pub symbol: String,
+}
+
+// a support view of someone else's session; it's read straight from the store, so nothing the
+// player sees changes
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct SessionInspection {
+    pub expires_at: i64,
+    pub data: HashMap<String, Value>,
+    pub stats: Option<PlayerStats>,
+}
+
+// session ids are credentials, so the audit trail only keeps their redacted form
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct InspectionAudit {
+    pub at: i64,
+    pub session: String,
+    pub found: bool,
+}
+
+// compared as digests, so neither the contents nor the length of the token leak through timing
+fn tokens_match(provided: &str, token: &str) -> bool {
+    Sha256::digest(provided.as_bytes())
+        .ct_eq(&Sha256::digest(token.as_bytes()))
+        .into()
+}
+
+pub async fn require_admin(request: Request, next: Next) -> Result<Response, StatusCode> {
+    let Ok(token) = std::env::var("GENEDLE_ADMIN_TOKEN") else {
+        return Err(StatusCode::FORBIDDEN);
+    };
+
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|provided| !token.is_empty() && tokens_match(provided, &token));
+
+    if authorized {
+        Ok(next.run(request).await)
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+pub async fn genedle_report(Path(day): Path<u64>) -> Json<Option<DifficultyReport>> {
+    Json(genedle::difficulty_report(day).await.ok())
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+pub async fn genedle_answer_pool(Query(query): Query<ExportQuery>) -> Response {
+    let pool = match genedle::answer_pool().await {
+        Ok(pool) => pool,
+        Err(_) => return StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    };
+
+    match query.format {
+        ExportFormat::Json => Json(pool).into_response(),
+        ExportFormat::Csv => (
+            [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+            genedle::pool_csv(&pool),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn override_genedle_answer(
+    Path(day): Path<u64>,
+    StrictJson(answer): StrictJson<AnswerOverride>,
+) -> Result<StatusCode, ApiError> {
+    if day <= crate::schedule::today() {
+        return Ok(StatusCode::CONFLICT);
+    }
+    genedle::check_override(&answer.symbol).await?;
+
+    storage().insert(&format!("genedle.daily.{day}"), &answer.symbol)?;
+    tracing::info!(
+        "Overrode answer for day {day} with {}",
+        redact(&answer.symbol)
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn curation_error(err: CurationError) -> (StatusCode, String) {
+    match err {
+        CurationError::AlreadyPlayed => (
+            StatusCode::CONFLICT,
+            "Puzzle may already have been played".to_string(),
+        ),
+        CurationError::Invalid(reason) => (StatusCode::UNPROCESSABLE_ENTITY, reason),
+        CurationError::NoSuchVersion => (StatusCode::NOT_FOUND, "No such version".to_string()),
+        CurationError::InternalError(err) => (StatusCode::INTERNAL_SERVER_ERROR, err),
+    }
+}
+
+pub async fn genections_board_history(Path(day): Path<u64>) -> Json<BoardHistory> {
+    Json(curation::history(day))
+}
+
+pub async fn publish_genections_board(
+    Path(day): Path<u64>,
+    StrictJson(edit): StrictJson<BoardEdit>,
+) -> Result<Json<BoardVersion>, (StatusCode, String)> {
+    curation::publish(day, edit)
+        .await
+        .map(Json)
+        .map_err(curation_error)
+}
+
+pub async fn rollback_genections_board(
+    Path((day, version)): Path<(u64, usize)>,
+    StrictJson(rollback): StrictJson<Rollback>,
+) -> Result<Json<BoardVersion>, (StatusCode, String)> {
+    curation::rollback(day, version, rollback.author)
+        .map(Json)
+        .map_err(curation_error)
+}
+
+pub async fn spelling_gene_specials() -> Json<BTreeMap<u64, SpecialPuzzle>> {
+    Json(curation::specials())
+}
+
+pub async fn schedule_spelling_gene_special(
+    Path(day): Path<u64>,
+    StrictJson(special): StrictJson<SpecialPuzzle>,
+) -> Result<Json<SpecialPuzzle>, (StatusCode, String)> {
+    curation::schedule_special(day, special)
+        .await
+        .map(Json)
+        .map_err(curation_error)
+}
+
+pub async fn session_metrics(State(store): State<TrackedStore>) -> Json<SessionMetrics> {
+    Json(store.metrics().await)
+}
+
+pub async fn overview(State(store): State<TrackedStore>) -> Json<Overview> {
+    Json(Overview {
+        sessions: store.metrics().await,
+        backup: backup::status(),
+        degraded: corpus::is_degraded(),
+        upstream: upstream::circuit(),
+    })
+}
+
+pub async fn upstream_drift() -> Json<BTreeMap<String, DriftStats>> {
+    Json(api::schema_drift())
+}
+
+pub async fn publish_announcement(
+    StrictJson(announcement): StrictJson<NewAnnouncement>,
+) -> Result<Json<Announcement>, StatusCode> {
+    announcements::publish(announcement)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// the new config only replaces the old one if every setting in it is valid
+pub async fn reload_config() -> Result<Json<ConfigReload>, (StatusCode, Json<Vec<String>>)> {
+    config::reload()
+        .map(Json)
+        .map_err(|errors| (StatusCode::UNPROCESSABLE_ENTITY, Json(errors)))
+}
+
+pub async fn slos() -> Json<Vec<SloReport>> {
+    Json(slo::reports())
+}
+
+pub async fn prometheus_metrics() -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        slo::prometheus(&slo::reports()),
+    )
+        .into_response()
+}
+
+// delivery status for debugging, newest first
+pub async fn notification_deliveries() -> Json<Vec<Delivery>> {
+    Json(notifications::deliveries(None))
+}
+
+pub async fn player_notification_deliveries(Path(player): Path<String>) -> Json<Vec<Delivery>> {
+    Json(notifications::deliveries(Some(&player)))
+}
+
+pub async fn send_notification(
+    StrictJson(notify): StrictJson<Notify>,
+) -> Result<Json<Vec<Delivery>>, StatusCode> {
+    let notification = Notification {
+        kind: notify.kind,
+        title: notify.title,
+        body: notify.body,
+    };
+    notifications::enqueue(&notify.players, &notification)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+pub async fn retract_announcement(Path(id): Path<u64>) -> StatusCode {
+    match announcements::retract(id) {
+        Ok(true) => StatusCode::NO_CONTENT,
+        Ok(false) => StatusCode::NOT_FOUND,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn audit_inspection(id: &str, found: bool) {
+    let session = redact(id).to_string();
+    tracing::info!("Admin inspected session {session} (found: {found})");
+
+    let entry = InspectionAudit {
+        at: chrono::Utc::now().timestamp(),
+        session,
+        found,
+    };
+    let logged = storage().update(AUDIT_KEY, |entries: &mut Vec<InspectionAudit>| {
+        entries.push(entry);
+        let overflow = entries.len().saturating_sub(AUDIT_ENTRIES);
+        entries.drain(..overflow);
+    });
+    if let Err(err) = logged {
+        tracing::error!("Failed to record session inspection: {err}");
+    }
+}
+
+pub async fn inspect_session(
+    State(store): State<TrackedStore>,
+    Path(id): Path<String>,
+) -> Result<Json<SessionInspection>, StatusCode> {
+    let record = match id.parse::<Id>() {
+        Ok(session_id) => store
+            .load(&session_id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        Err(_) => None,
+    };
+    audit_inspection(&id, record.is_some());
+
+    let record = record.ok_or(StatusCode::NOT_FOUND)?;
+    let stats = record
+        .data
+        .get(PLAYER_KEY)
+        .and_then(Value::as_u64)
+        .map(games::stats_of);
+
+    Ok(Json(SessionInspection {
+        expires_at: record.expiry_date.unix_timestamp(),
+        data: record.data,
+        stats,
+    }))
+}
+
+pub async fn inspection_audit() -> Json<Vec<InspectionAudit>> {
+    Json(storage().get(AUDIT_KEY).unwrap_or_default())
+}
@@ -0,0 +1,144 @@
+use crate::storage::storage;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+
+const ANNOUNCEMENTS_KEY: &str = "announcements";
+const LAST_ID_KEY: &str = "announcements.last_id";
+const READ_KEY: &str = "announcements.read";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Announcement {
+    pub id: u64,
+    pub title: String,
+    pub body: String,
+    pub published_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct NewAnnouncement {
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct AnnouncementEntry {
+    #[serde(flatten)]
+    pub announcement: Announcement,
+    pub read: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct AnnouncementFeed {
+    pub announcements: Vec<AnnouncementEntry>,
+    pub unread: usize,
+}
+
+pub fn announcements() -> Vec<Announcement> {
+    storage().get(ANNOUNCEMENTS_KEY).unwrap_or_default()
+}
+
+pub fn publish(announcement: NewAnnouncement) -> Result<Announcement, anyhow::Error> {
+    // retracted ids are never reused so a session's read marker stays meaningful
+    let id = storage().update(LAST_ID_KEY, |id: &mut u64| *id += 1)?;
+    let mut published = None;
+    storage().update(
+        ANNOUNCEMENTS_KEY,
+        |announcements: &mut Vec<Announcement>| {
+            let announcement = Announcement {
+                id,
+                title: announcement.title,
+                body: announcement.body,
+                published_at: chrono::Utc::now().timestamp(),
+            };
+            announcements.push(announcement.clone());
+            published = Some(announcement);
+        },
+    )?;
+
+    published.ok_or_else(|| anyhow::anyhow!("Failed to publish announcement"))
+}
+
+pub fn retract(id: u64) -> Result<bool, anyhow::Error> {
+    let mut retracted = false;
+    storage().update(
+        ANNOUNCEMENTS_KEY,
+        |announcements: &mut Vec<Announcement>| {
+            let before = announcements.len();
+            announcements.retain(|a| a.id != id);
+            retracted = announcements.len() != before;
+        },
+    )?;
+
+    Ok(retracted)
+}
+
+// ids only ever increase, so a session just remembers the newest one it has seen
+fn feed(announcements: Vec<Announcement>, last_read: u64) -> AnnouncementFeed {
+    let mut announcements: Vec<AnnouncementEntry> = announcements
+        .into_iter()
+        .map(|announcement| AnnouncementEntry {
+            read: announcement.id <= last_read,
+            announcement,
+        })
+        .collect();
+    announcements.sort_by_key(|entry| std::cmp::Reverse(entry.announcement.id));
+
+    AnnouncementFeed {
+        unread: announcements.iter().filter(|entry| !entry.read).count(),
+        announcements,
+    }
+}
+
+pub async fn list(session: Session) -> Json<AnnouncementFeed> {
+    let last_read = session
+        .get::<u64>(READ_KEY)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(0);
+
+    Json(feed(announcements(), last_read))
+}
+
+pub async fn mark_read(session: Session) -> Json<Option<AnnouncementFeed>> {
+    let announcements = announcements();
+    let last_read = announcements.iter().map(|a| a.id).max().unwrap_or(0);
+
+    Json(
+        session
+            .insert(READ_KEY, last_read)
+            .await
+            .ok()
+            .map(|_| feed(announcements, last_read)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announcement(id: u64) -> Announcement {
+        Announcement {
+            id,
+            title: format!("Announcement {id}"),
+            body: String::new(),
+            published_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_feed() {
+        let feed = feed(vec![announcement(1), announcement(3), announcement(2)], 2);
+
+        assert_eq!(feed.unread, 1);
+        assert_eq!(
+            feed.announcements
+                .iter()
+                .map(|entry| (entry.announcement.id, entry.read))
+                .collect::<Vec<_>>(),
+            vec![(3, false), (2, true), (1, true)]
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use crate::api::GeneNamesExt;
+use crate::cache;
+use crate::corpus;
+use crate::error::ApiError;
+use crate::upstream::{RetryExt, UpstreamError};
+use axum::Json;
+use axum::extract::{Path, State};
+use cached::TimedSizedCache;
+use cached::proc_macro::cached;
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct GeneRecord {
+    pub hgnc_id: String,
+    pub symbol: String,
+    pub name: String,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub alias_symbol: Vec<String>,
+    #[serde(default)]
+    pub prev_symbol: Vec<String>,
+    #[serde(default)]
+    pub entrez_id: Option<String>,
+    #[serde(default)]
+    pub ensembl_gene_id: Option<String>,
+    #[serde(default)]
+    pub uniprot_ids: Vec<String>,
+    #[serde(default)]
+    pub omim_id: Vec<String>,
+}
+
+// only symbols the corpus knows go to HGNC, so made-up ones can't run down its budget; an alias
+// looks up the gene it stands for
+pub async fn gene(
+    State(client): State<Client>,
+    Path(symbol): Path<String>,
+) -> Result<Json<Option<GeneRecord>>, ApiError> {
+    let corpus = corpus::corpus().await.map_err(ApiError::Upstream)?;
+    let symbol = if corpus.contains(&symbol) {
+        symbol
+    } else {
+        corpus
+            .alias_of(&symbol)
+            .map(String::from)
+            .ok_or_else(|| ApiError::NotFound("No such gene symbol".to_string()))?
+    };
+
+    // None is HGNC saying there's no such symbol; HGNC failing to say is an error
+    Ok(Json(fetch_gene(&client, symbol).await?))
+}
+
+// a path segment of its own, so nothing in the symbol can reach past it
+fn fetch_url(symbol: &str) -> Result<Url, UpstreamError> {
+    const API: &str = "https://rest.genenames.org/fetch/symbol";
+
+    let mut url = Url::parse(API).map_err(|err| UpstreamError::Failed(err.to_string()))?;
+    url.path_segments_mut()
+        .map_err(|()| UpstreamError::Failed(format!("{API} can't take a path")))?
+        .push(symbol);
+    Ok(url)
+}
+
+#[cached(
+    ty = "TimedSizedCache<String, Option<GeneRecord>>",
+    create = "{ cache::RECORDS.build() }",
+    result = true,
+    key = "String",
+    convert = r#"{ symbol.clone() }"#
+)]
+pub(crate) async fn fetch_gene(
+    client: &Client,
+    symbol: String,
+) -> Result<Option<GeneRecord>, UpstreamError> {
+    const STATUS_SUCCESS: usize = 0;
+
+    let response = client
+        .get(fetch_url(&symbol)?)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .send_with_retry()
+        .await?;
+
+    if response.status().is_success() {
+        response
+            .gene_names::<GeneRecord>()
+            .await
+            .map(|json| {
+                if json.response_header.status == STATUS_SUCCESS {
+                    json.response
+                        .docs
+                        .into_iter()
+                        .find(|doc| doc.symbol == symbol)
+                } else {
+                    None
+                }
+            })
+            .map_err(UpstreamError::from)
+    } else {
+        Err(UpstreamError::Failed(
+            "Unable to query genenames.org".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::gene::fetch_url;
+
+    #[test]
+    fn test_fetch_url() {
+        assert_eq!(
+            fetch_url("HLA-A").unwrap().as_str(),
+            "https://rest.genenames.org/fetch/symbol/HLA-A"
+        );
+        assert_eq!(
+            fetch_url("../search?q=1").unwrap().as_str(),
+            "https://rest.genenames.org/fetch/symbol/..%2Fsearch%3Fq=1"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use crate::corpus::corpus;
+use axum::Json;
+use axum::extract::{Path, Query};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_PER_PAGE: usize = 50;
+const MAX_PER_PAGE: usize = 500;
+
+// pages count from 1
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PageQuery {
+    #[serde(default = "first_page")]
+    pub page: usize,
+    #[serde(default = "default_per_page")]
+    pub per_page: usize,
+}
+
+fn first_page() -> usize {
+    1
+}
+
+fn default_per_page() -> usize {
+    DEFAULT_PER_PAGE
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: usize,
+    pub per_page: usize,
+    pub total: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct GeneGroupSummary {
+    pub id: u32,
+    pub name: String,
+    pub members: usize,
+}
+
+fn paginate<T>(items: impl ExactSizeIterator<Item = T>, query: PageQuery) -> Page<T> {
+    let page = query.page.max(1);
+    let per_page = query.per_page.clamp(1, MAX_PER_PAGE);
+    let total = items.len();
+
+    Page {
+        items: items
+            .skip((page - 1).saturating_mul(per_page))
+            .take(per_page)
+            .collect(),
+        page,
+        per_page,
+        total,
+    }
+}
+
+pub async fn groups(Query(query): Query<PageQuery>) -> Json<Option<Page<GeneGroupSummary>>> {
+    Json(corpus().await.ok().map(|corpus| {
+        paginate(
+            corpus.groups().map(|group| GeneGroupSummary {
+                id: group.id,
+                name: group.name.clone(),
+                members: group.members.len(),
+            }),
+            query,
+        )
+    }))
+}
+
+pub async fn members(
+    Path(id): Path<u32>,
+    Query(query): Query<PageQuery>,
+) -> Json<Option<Page<String>>> {
+    Json(
+        corpus()
+            .await
+            .ok()
+            .and_then(|corpus| corpus.group(id))
+            .map(|group| paginate(group.members.iter().cloned(), query)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::gene_group::{PageQuery, paginate};
+
+    #[test]
+    fn test_paginate() {
+        let page = paginate(
+            0..7,
+            PageQuery {
+                page: 2,
+                per_page: 3,
+            },
+        );
+        assert_eq!((page.items, page.total), (vec![3, 4, 5], 7));
+
+        let page = paginate(
+            0..7,
+            PageQuery {
+                page: 0,
+                per_page: 0,
+            },
+        );
+        assert_eq!((page.items, page.page, page.per_page), (vec![0], 1, 1));
+
+        assert!(
+            paginate(
+                0..7,
+                PageQuery {
+                    page: usize::MAX,
+                    per_page: 3
+                }
+            )
+            .items
+            .is_empty()
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use crate::cache::{self, Snapshot};
+use crate::config::config;
+use crate::corpus::corpus;
+use crate::puzzle_ids;
+use cached::TimedSizedCache;
+use cached::proc_macro::cached;
+use genedle_core::genections::pick_families;
+pub use genedle_core::genections::{Board, Category, GROUP_SIZE, NUM_GROUPS};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+const FAMILIES: [(&str, &str); 17] = [
+    ("HOX", "Homeobox genes"),
+    ("KRT", "Keratins"),
+    ("COL", "Collagens"),
+    ("COX", "Cytochrome c oxidase subunits"),
+    ("SLC", "Solute carriers"),
+    ("ZNF", "Zinc fingers"),
+    ("CDK", "Cyclin-dependent kinases"),
+    ("CDH", "Cadherins"),
+    ("WNT", "Wnt signalling"),
+    ("FGF", "Fibroblast growth factors"),
+    ("TLR", "Toll-like receptors"),
+    ("RPL", "Large ribosomal subunit"),
+    ("RPS", "Small ribosomal subunit"),
+    ("MYO", "Myosins"),
+    ("KCN", "Potassium channels"),
+    ("TNF", "Tumour necrosis factors"),
+    ("HLA", "Major histocompatibility complex"),
+];
+
+#[cached(
+    ty = "TimedSizedCache<u64, Board>",
+    create = "{ cache::GAMES.build() }",
+    result = true
+)]
+pub(crate) async fn generate_board(seed: u64) -> Result<Board, String> {
+    let corpus = corpus().await?;
+    let mut rng: StdRng = SeedableRng::seed_from_u64(puzzle_ids::keyed(seed));
+
+    let families = pick_families(&FAMILIES, config().genections_red_herrings, &mut rng);
+
+    let mut categories = Vec::with_capacity(NUM_GROUPS);
+    for (prefix, name) in families {
+        if categories.len() == NUM_GROUPS {
+            break;
+        }
+
+        let mut symbols: Vec<String> = corpus.starting_with(prefix).cloned().collect();
+        symbols.shuffle(&mut rng);
+        symbols.truncate(GROUP_SIZE);
+
+        if symbols.len() == GROUP_SIZE {
+            categories.push(Category {
+                name: name.to_string(),
+                symbols,
+            });
+        }
+    }
+
+    if categories.len() < NUM_GROUPS {
+        return Err("Failed to generate a valid board".to_string());
+    }
+
+    let mut layout: Vec<String> = categories
+        .iter()
+        .flat_map(|category| category.symbols.iter().cloned())
+        .collect();
+    layout.shuffle(&mut rng);
+
+    Ok(Board { categories, layout })
+}
+
+pub(crate) async fn save_caches(snapshot: &mut Snapshot) {
+    cache::save(
+        snapshot,
+        "genections.board",
+        cache::entries(&*GENERATE_BOARD.lock().await),
+    );
+}
+
+pub(crate) async fn load_caches(snapshot: &Snapshot) {
+    cache::load(
+        snapshot,
+        "genections.board",
+        &mut *GENERATE_BOARD.lock().await,
+    );
+}
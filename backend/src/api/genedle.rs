@@ -1,94 +1,209 @@
-use crate::api::{GeneNamesDoc, GeneNamesResponse};
+use crate::api::gene::fetch_gene;
+use crate::cache::{self, Snapshot};
+use crate::config::{HintEconomy, config};
+use crate::corpus::{self, Corpus};
+use crate::error::{ApiError, Outcome};
+use crate::puzzle_ids::{self, PuzzleId};
+use crate::redact::{redact, scrub};
+use crate::schedule;
+use crate::storage::storage;
+use crate::survey::{self, Rating, SurveyTally};
 use axum::Json;
 use axum::extract::Path;
+use cached::TimedSizedCache;
 use cached::proc_macro::cached;
+use genedle_core::difficulty::{self, DifficultyInputs};
+pub use genedle_core::feedback::{
+    GameMode, GuessResult, InvalidGuess, LetterFeedback, Temperature, ValidGuess, check_hints,
+    check_length, is_consistent, is_playable, score_guess,
+};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
-use reqwest::Client;
-use serde::ser::SerializeStruct;
+use reqwest::{Client, Url};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
-#[serde(rename_all = "snake_case")]
-pub enum GameMode {
-    Normal,
-    Hard,
-}
+const DAILY_PREFIX: &str = "genedle.daily.";
+// each day before a previewed one is drawn too, so how far ahead is capped
+const MAX_PREVIEW_DAYS: u64 = 31;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Hash)]
+#[serde(deny_unknown_fields)]
 pub struct Guess {
     pub word: Vec<char>,
-    pub session: u64,
+    pub session: PuzzleId,
     pub mode: GameMode,
+    #[serde(default)]
+    pub rating: Option<Rating>,
+    // asks for the temperature alongside the result
+    #[serde(default)]
+    pub assist: bool,
 }
 
-#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum GuessResult {
-    Invalid(InvalidGuess),
-    Valid(ValidGuess),
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct GenedleMetadata {
+    pub num_letters: usize,
+    pub digits_excluded: bool,
+    pub hints: HintEconomy,
 }
 
-impl Serialize for GuessResult {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        let mut state = serializer.serialize_struct("GuessResult", 2)?;
-
-        match self {
-            GuessResult::Invalid(invalid) => {
-                state.serialize_field("type", "invalid")?;
-                state.serialize_field("data", invalid)
-            }
-            GuessResult::Valid(valid) => {
-                state.serialize_field("type", "valid")?;
-                state.serialize_field("data", valid)
-            }
-        }?;
-
-        state.end()
+impl Guess {
+    pub fn normalized(mut self) -> Self {
+        self.word = normalize_word(&self.word);
+        self
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum InvalidGuess {
-    InternalError(String),
-    NotEnoughLetters,
-    TooManyLetters,
-    InvalidLetter,
-    NotInCorpus,
+// answers are all capitals, so a typed mib2 is scored as MIB2 rather than failing letter by letter
+pub fn normalize_word(word: &[char]) -> Vec<char> {
+    word.iter().map(char::to_ascii_uppercase).collect()
 }
 
+// ordered from vague to nearly giving the answer away
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
-pub struct ValidGuess {
-    is_correct: bool,
-    result: Vec<LetterFeedback>,
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum Hint {
+    Family(String),
+    Chromosome(String),
+    FirstLetter(char),
+}
+
+// what a finished game teaches about its answer; links work even when HGNC can't be reached
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct GameSummary {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub location: Option<String>,
+    pub hgnc_id: Option<String>,
+    pub hgnc_url: Option<String>,
+    pub genecards_url: Option<String>,
+}
+
+// the summary sits beside the result so clients that only read type and data are unaffected
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct GuessResponse {
+    #[serde(flatten)]
+    pub result: GuessResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<GameSummary>,
+    // the approved symbol, when the guess was one of its aliases or previous symbols
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias_of: Option<String>,
+    // the guess as it was scored, after normalization
+    pub canonical: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<Temperature>,
+}
+
+impl Outcome for GuessResponse {
+    fn error(&self) -> Option<ApiError> {
+        self.result.error()
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
-#[serde(rename_all = "snake_case")]
-pub enum LetterFeedback {
-    Correct,
-    Present,
-    Absent,
+fn hgnc_url(hgnc_id: &str) -> String {
+    format!("https://www.genenames.org/data/gene-symbol-report/#!/hgnc_id/{hgnc_id}")
 }
 
-pub async fn num_letters(Path(key): Path<u64>) -> Json<isize> {
-    let count = get_word(key)
+fn genecards_url(symbol: &str) -> Option<String> {
+    Url::parse_with_params(
+        "https://www.genecards.org/cgi-bin/carddisp.pl",
+        [("gene", symbol)],
+    )
+    .ok()
+    .map(String::from)
+}
+
+// fetch_gene is cached, so every player finishing the same puzzle shares one lookup
+pub(crate) async fn game_summary(client: &Client, symbol: &str) -> GameSummary {
+    if let Ok(Some(record)) = fetch_gene(client, symbol.to_string()).await {
+        return GameSummary {
+            genecards_url: genecards_url(&record.symbol),
+            hgnc_url: Some(hgnc_url(&record.hgnc_id)),
+            hgnc_id: Some(record.hgnc_id),
+            name: Some(record.name),
+            location: record.location,
+            symbol: record.symbol,
+        };
+    }
+
+    let gene = corpus::corpus()
         .await
-        .map_or(-1, |word| word.chars().count() as isize);
+        .ok()
+        .and_then(|corpus| corpus.gene(symbol));
+    let hgnc_id = gene
+        .and_then(|gene| gene.hgnc_id)
+        .map(|hgnc_id| format!("HGNC:{hgnc_id}"));
+    GameSummary {
+        symbol: symbol.to_string(),
+        name: None,
+        location: gene
+            .map(|gene| gene.location.clone())
+            .filter(|location| !location.is_empty()),
+        hgnc_url: hgnc_id.as_deref().map(hgnc_url),
+        hgnc_id,
+        genecards_url: genecards_url(symbol),
+    }
+}
+
+// levels HGNC has nothing for are skipped, so some puzzles offer fewer hints
+#[cached(
+    ty = "TimedSizedCache<u64, Vec<Hint>>",
+    create = "{ cache::WORDS.build() }",
+    result = true
+)]
+pub(crate) async fn puzzle_hints(puzzle: u64) -> Result<Vec<Hint>, String> {
+    let answer = get_word(puzzle).await?;
+    let corpus = corpus::corpus().await?;
+
+    let family = corpus
+        .groups()
+        .find(|group| group.members.contains(&answer))
+        .map(|group| Hint::Family(group.name.clone()));
+    let chromosome = corpus
+        .gene(&answer)
+        .and_then(|gene| gene.chromosome())
+        .map(|chromosome| Hint::Chromosome(chromosome.to_string()));
+    let first_letter = answer.chars().next().map(Hint::FirstLetter);
+
+    Ok([family, chromosome, first_letter]
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+pub async fn metadata(
+    Path(PuzzleId(key)): Path<PuzzleId>,
+) -> Result<Json<GenedleMetadata>, ApiError> {
+    Ok(Json(GenedleMetadata {
+        num_letters: _num_letters(key).await?,
+        digits_excluded: config().exclude_digits,
+        hints: config().hints,
+    }))
+}
 
-    Json(count)
+pub async fn num_letters(Path(PuzzleId(key)): Path<PuzzleId>) -> Result<Json<usize>, ApiError> {
+    _num_letters(key).await.map(Json)
 }
 
-#[cached]
-async fn _num_letters(key: u64) -> isize {
+#[cached(
+    ty = "TimedSizedCache<u64, usize>",
+    create = "{ cache::WORDS.build() }",
+    result = true
+)]
+async fn _num_letters(key: u64) -> Result<usize, ApiError> {
     match get_word(key).await {
-        Ok(word) => word.chars().count() as isize,
-        Err(_) => -1,
+        Ok(word) => Ok(word.chars().count()),
+        Err(err) => Err(word_error(err).await),
+    }
+}
+
+// an answer can't be drawn either because the corpus isn't there yet, which is HGNC's doing, or
+// because nothing in it fits the puzzle
+async fn word_error(err: String) -> ApiError {
+    match corpus::corpus().await {
+        Ok(_) => ApiError::NotFound(err),
+        Err(_) => ApiError::Upstream(err),
     }
 }
 
@@ -101,117 +216,596 @@ pub async fn valid_guess(Json(guess): Json<Guess>) -> Result<Option<InvalidGuess
     }
 }
 
-#[cached]
+#[cached(
+    ty = "TimedSizedCache<Guess, Result<Option<InvalidGuess>, String>>",
+    create = "{ cache::VALIDATIONS.build() }"
+)]
 async fn _valid_guess(guess: Guess) -> Result<Option<InvalidGuess>, String> {
-    const API: &str = "https://rest.genenames.org/search/symbol/";
-    const STATUS_SUCCESS: usize = 0;
-
-    let len = _num_letters(guess.session).await;
-    if len == -1 {
+    let Ok(len) = _num_letters(guess.session.0).await else {
         return Ok(Some(InvalidGuess::InternalError(
             "Unable to fetch gene symbol".to_string(),
         )));
-    }
-    let len = len as usize;
+    };
 
-    if guess.word.len() != len {
-        return Ok(Some(if guess.word.len() < len {
-            InvalidGuess::NotEnoughLetters
-        } else {
-            InvalidGuess::TooManyLetters
-        }));
+    if let Some(reason) = check_length(&guess.word, len) {
+        return Ok(Some(reason));
     }
 
     if guess.mode == GameMode::Normal {
         return Ok(None);
     }
 
-    let guess = guess.word.iter().collect::<String>();
+    if in_corpus(guess.word.iter().collect()).await? {
+        Ok(None)
+    } else {
+        Ok(Some(InvalidGuess::NotInCorpus))
+    }
+}
+
+// players often know a gene by an alias or a retired symbol, so hard mode takes those too
+pub(crate) async fn in_corpus(symbol: String) -> Result<bool, String> {
+    let corpus = corpus::corpus().await?;
+    Ok(corpus.contains(&symbol) || corpus.alias_of(&symbol).is_some())
+}
 
-    let client = Client::new();
-    let response = client
-        .get(format!("{API}{guess}"))
-        .header(reqwest::header::ACCEPT, "application/json")
-        .send()
+pub(crate) async fn alias_of(symbol: &str) -> Option<String> {
+    corpus::corpus()
         .await
+        .ok()?
+        .alias_of(symbol)
+        .map(String::from)
+}
+
+pub(crate) async fn get_word(key: u64) -> Result<String, String> {
+    if schedule::is_daily(key) {
+        get_daily_word(key).await
+    } else {
+        draw_answer(key).await
+    }
+}
+
+async fn draw_answer(seed: u64) -> Result<String, String> {
+    const MAX_DRAWS: usize = 50;
+
+    let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
+    let corpus = corpus::corpus().await?;
+    let mut word = draw_word(seed).await?;
+    for _ in 0..MAX_DRAWS {
+        match check_answer(&word).and_then(|()| check_gene(corpus, &word)) {
+            Ok(()) => return Ok(word),
+            Err(reason) => tracing::warn!("Redrawing answer for puzzle {seed}: {reason}"),
+        }
+
+        word = draw_word(rng.random()).await?;
+    }
+
+    Err("No gene symbol found".to_string())
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum AnswerRejection {
+    Digits,
+    TooShort(usize),
+    TooLong(usize),
+    Unplayable(char),
+    NotApproved,
+    Locus,
+}
+
+impl std::fmt::Display for AnswerRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnswerRejection::Digits => write!(f, "contains digits"),
+            AnswerRejection::TooShort(len) => write!(f, "{len} letters is too short"),
+            AnswerRejection::TooLong(len) => write!(f, "{len} letters is too long"),
+            AnswerRejection::Unplayable(_) => write!(f, "contains a character not on the keyboard"),
+            AnswerRejection::NotApproved => write!(f, "isn't a currently approved symbol"),
+            AnswerRejection::Locus => write!(f, "has a locus type that isn't drawn"),
+        }
+    }
+}
+
+fn check_answer(word: &str) -> Result<(), AnswerRejection> {
+    let len = word.chars().count();
+    if len < config().min_answer_length {
+        return Err(AnswerRejection::TooShort(len));
+    }
+    if len > config().max_answer_length {
+        return Err(AnswerRejection::TooLong(len));
+    }
+    if let Some(letter) = word.chars().find(|&c| !is_playable(c)) {
+        return Err(AnswerRejection::Unplayable(letter));
+    }
+    if config().exclude_digits && word.chars().any(|c| c.is_ascii_digit()) {
+        return Err(AnswerRejection::Digits);
+    }
+
+    Ok(())
+}
+
+// the HGNC record behind a symbol, as opposed to how it's spelled
+fn check_gene(corpus: &Corpus, symbol: &str) -> Result<(), AnswerRejection> {
+    let Some(gene) = corpus.gene(symbol) else {
+        return Err(AnswerRejection::NotApproved);
+    };
+    let filter = &config().answers;
+    if !filter.admits_status(&gene.status) {
+        return Err(AnswerRejection::NotApproved);
+    }
+    if !filter.admits_locus(&gene.locus_group, &gene.locus_type) {
+        return Err(AnswerRejection::Locus);
+    }
+
+    Ok(())
+}
+
+// a hand-picked answer is held to the same rules as a drawn one, so it can't publish an unwinnable
+// daily
+pub(crate) async fn check_override(symbol: &str) -> Result<(), ApiError> {
+    let corpus = corpus::corpus().await.map_err(ApiError::Upstream)?;
+    check_answer(symbol)
+        .and_then(|()| check_gene(corpus, symbol))
+        .map_err(|reason| ApiError::BadRequest(format!("{symbol} {reason}")))
+}
+
+fn is_allowed_answer(corpus: &Corpus, word: &str) -> bool {
+    check_answer(word).is_ok() && check_gene(corpus, word).is_ok()
+}
+
+// every daily answer drawn so far, by day; they live in storage, so restarts don't forget them
+pub(crate) fn answer_history() -> BTreeMap<u64, String> {
+    storage()
+        .keys(DAILY_PREFIX)
+        .iter()
+        .filter_map(|key| {
+            let day = key.strip_prefix(DAILY_PREFIX)?.parse().ok()?;
+            Some((day, storage().get(key)?))
+        })
+        .collect()
+}
+
+// counted in puzzles rather than calendar days, so days nobody played don't shorten the window
+fn recent_answers(history: &BTreeMap<u64, String>, day: u64, window: usize) -> HashSet<&str> {
+    history
+        .range(..day)
+        .rev()
+        .take(window)
+        .map(|(_, word)| word.as_str())
+        .collect()
+}
+
+// assist mode: how many playable symbols of the answer's length fit every guess so far
+pub(crate) async fn temperature(
+    length: usize,
+    previous: &[(Vec<char>, &[LetterFeedback])],
+) -> Result<Temperature, String> {
+    let candidates = corpus::corpus()
+        .await?
+        .symbols()
+        .filter(|symbol| symbol.len() == length && symbol.chars().all(is_playable))
+        .filter(|symbol| {
+            let candidate: Vec<char> = symbol.chars().collect();
+            is_consistent(
+                &candidate,
+                previous
+                    .iter()
+                    .map(|(word, feedback)| (word.as_slice(), *feedback)),
+            )
+        })
+        .count();
+
+    Ok(Temperature::for_candidates(candidates))
+}
+
+async fn get_daily_word(day: u64) -> Result<String, String> {
+    let key = format!("{DAILY_PREFIX}{day}");
+    if let Some(word) = storage().get::<String>(&key) {
+        return Ok(word);
+    }
+
+    let word = draw_daily_word(day, &answer_history()).await?;
+    storage()
+        .insert(&key, &word)
         .map_err(|err| err.to_string())?;
 
-    if response.status().is_success() {
-        let found = response
-            .json::<GeneNamesResponse<GeneNamesDoc>>()
-            .await
-            .map(|response| {
-                response.response_header.status == STATUS_SUCCESS
-                    && response.response.num_found >= 1
-                    && response.response.docs.iter().any(|doc| doc.symbol == guess)
-            })
-            .map_err(|err| err.to_string())?;
+    Ok(word)
+}
+
+async fn draw_daily_word(day: u64, history: &BTreeMap<u64, String>) -> Result<String, String> {
+    const MAX_DRAWS: usize = 50;
+
+    let recent = recent_answers(history, day, config().repeat_window);
+
+    // keyed, since the day number is no secret
+    let seed = puzzle_ids::keyed(day);
+    let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
+    let mut word = draw_answer(seed).await?;
+    for draw in 0..=MAX_DRAWS {
+        if !recent.contains(word.as_str()) {
+            break;
+        }
+        if draw == MAX_DRAWS {
+            tracing::warn!("Repeating recent answer {} for day {day}", redact(&word));
+            break;
+        }
+
+        word = draw_answer(rng.random()).await?;
+    }
 
-        if found {
-            Ok(None)
-        } else {
-            Ok(Some(InvalidGuess::NotInCorpus))
+    Ok(word)
+}
+
+// what a coming day will draw, without storing it; the days before it are drawn in order too,
+// so each sees the ones ahead of it in the repeat window just as it will when it comes
+async fn preview_daily_word(day: u64) -> Result<String, String> {
+    if day > schedule::today() + MAX_PREVIEW_DAYS {
+        return Err(format!(
+            "Can't preview more than {MAX_PREVIEW_DAYS} days ahead"
+        ));
+    }
+
+    let mut history = answer_history();
+    for next in schedule::today()..=day {
+        if !history.contains_key(&next) {
+            let word = draw_daily_word(next, &history).await?;
+            history.insert(next, word);
         }
+    }
+    history
+        .remove(&day)
+        .ok_or_else(|| "No gene symbol found".to_string())
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct DifficultyReport {
+    pub day: u64,
+    pub answer: String,
+    #[serde(flatten)]
+    pub inputs: DifficultyInputs,
+    pub estimated_solve_rate: f64,
+    pub survey: SurveyTally,
+}
+
+// a symbol the selector could pick, as curators audit it
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct PoolEntry {
+    pub symbol: String,
+    #[serde(flatten)]
+    pub inputs: DifficultyInputs,
+    pub estimated_solve_rate: f64,
+    // YYYY-MM-DD
+    pub last_used: Option<String>,
+    // within the repeat window, so the next daily draw skips it
+    pub recent: bool,
+}
+
+fn difficulty_inputs(answer: &str, stats: &CorpusStats) -> DifficultyInputs {
+    const COMMON_OPENERS: [&str; 4] = ["TP53", "BRCA1", "EGFR", "MYC"];
+
+    let length = answer.chars().count();
+    let letters: HashSet<char> = answer.chars().collect();
+    let opener_letters: HashSet<char> = COMMON_OPENERS.iter().flat_map(|s| s.chars()).collect();
+    let opener_overlap =
+        letters.intersection(&opener_letters).count() as f64 / letters.len().max(1) as f64;
+
+    DifficultyInputs {
+        length,
+        digits: answer.chars().filter(char::is_ascii_digit).count(),
+        candidates: stats.by_length.get(&length).copied().unwrap_or_default(),
+        opener_overlap,
+    }
+}
+
+pub(crate) async fn difficulty_report(day: u64) -> Result<DifficultyReport, String> {
+    let answer = if day > schedule::today() {
+        preview_daily_word(day).await?
     } else {
-        Err("Unable to query genenames.org".to_string())
+        get_daily_word(day).await?
+    };
+    let inputs = difficulty_inputs(&answer, &corpus_stats().await?);
+
+    let survey = survey::tally("genedle", day);
+
+    Ok(DifficultyReport {
+        day,
+        answer,
+        inputs,
+        estimated_solve_rate: difficulty::adjusted_solve_rate(
+            difficulty::estimated_solve_rate(&inputs),
+            &survey,
+        ),
+        survey,
+    })
+}
+
+// everything that passes the answer filters as they are currently configured
+pub(crate) async fn answer_pool() -> Result<Vec<PoolEntry>, String> {
+    let corpus = corpus::corpus().await?;
+    let stats = corpus_stats().await?;
+    let history = answer_history();
+
+    // the next draw is for whichever day comes after the latest one already drawn
+    let next_day = history
+        .keys()
+        .next_back()
+        .map_or(schedule::today(), |&day| (day + 1).max(schedule::today()));
+    let recent = recent_answers(&history, next_day, config().repeat_window);
+    let last_used: HashMap<&str, u64> = history
+        .iter()
+        .map(|(&day, word)| (word.as_str(), day))
+        .collect();
+
+    Ok(corpus
+        .symbols()
+        .filter(|symbol| is_allowed_answer(corpus, symbol))
+        .map(|symbol| {
+            let inputs = difficulty_inputs(symbol, &stats);
+            PoolEntry {
+                symbol: symbol.clone(),
+                inputs,
+                estimated_solve_rate: difficulty::estimated_solve_rate(&inputs),
+                last_used: last_used
+                    .get(symbol.as_str())
+                    .and_then(|&day| schedule::date(day))
+                    .map(|date| date.to_string()),
+                recent: recent.contains(symbol.as_str()),
+            }
+        })
+        .collect())
+}
+
+pub fn pool_csv(pool: &[PoolEntry]) -> String {
+    let mut csv = String::from(
+        "symbol,length,digits,candidates,opener_overlap,estimated_solve_rate,last_used,recent\n",
+    );
+    for entry in pool {
+        csv += &format!(
+            "{},{},{},{},{:.3},{:.3},{},{}\n",
+            entry.symbol,
+            entry.inputs.length,
+            entry.inputs.digits,
+            entry.inputs.candidates,
+            entry.inputs.opener_overlap,
+            entry.estimated_solve_rate,
+            entry.last_used.as_deref().unwrap_or_default(),
+            entry.recent,
+        );
     }
+    csv
 }
 
-#[cached]
-async fn get_word(key: u64) -> Result<String, String> {
-    const API: &str = "https://rest.genenames.org/search/symbol/";
-    const STATUS_SUCCESS: usize = 0;
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct CorpusStats {
+    pub total: usize,
+    pub by_length: BTreeMap<usize, usize>,
+    pub by_first_letter: BTreeMap<char, usize>,
+}
 
-    let mut rng: StdRng = SeedableRng::seed_from_u64(key);
-    let first_letter = rng.random_range(b'A'..=b'Z') as char;
+fn index_corpus<'a>(symbols: impl IntoIterator<Item = &'a String>) -> CorpusStats {
+    let mut stats = CorpusStats::default();
+    for symbol in symbols {
+        stats.total += 1;
+        *stats.by_length.entry(symbol.chars().count()).or_default() += 1;
+        if let Some(first) = symbol.chars().next() {
+            *stats.by_first_letter.entry(first).or_default() += 1;
+        }
+    }
 
-    let client = Client::new();
-    let response = client
-        .get(format!("{API}{first_letter}*"))
-        .header(reqwest::header::ACCEPT, "application/json")
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
+    stats
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct SymbolFormat {
+    pub pattern: String,
+    pub percent: usize,
+    pub example: String,
+}
+
+// only the answer's length goes in, which the board already reveals
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct SymbolGuidelines {
+    pub num_letters: usize,
+    pub candidates: usize,
+    pub digits_allowed: bool,
+    pub hyphens_allowed: bool,
+    pub digit_percent: usize,
+    pub hyphen_percent: usize,
+    pub formats: Vec<SymbolFormat>,
+}
+
+// letters become A and digits 9, so TP53 and MYC2 both read as AA99
+fn symbol_pattern(symbol: &str) -> String {
+    symbol
+        .chars()
+        .map(|c| match c {
+            c if c.is_ascii_digit() => '9',
+            '-' => '-',
+            _ => 'A',
+        })
+        .collect()
+}
+
+fn index_guidelines<'a>(
+    symbols: impl IntoIterator<Item = &'a String>,
+    num_letters: usize,
+) -> SymbolGuidelines {
+    const MAX_FORMATS: usize = 3;
+
+    let candidates: Vec<&String> = symbols
+        .into_iter()
+        .filter(|symbol| symbol.chars().count() == num_letters)
+        .collect();
+    let percent = |count: usize| count * 100 / candidates.len().max(1);
+
+    let mut formats: BTreeMap<String, (usize, &String)> = BTreeMap::new();
+    for &symbol in &candidates {
+        formats
+            .entry(symbol_pattern(symbol))
+            .or_insert((0, symbol))
+            .0 += 1;
+    }
+    let mut formats: Vec<(String, (usize, &String))> = formats.into_iter().collect();
+    formats.sort_by_key(|(_, (count, _))| std::cmp::Reverse(*count));
+
+    let with_digits = candidates
+        .iter()
+        .filter(|symbol| symbol.chars().any(|c| c.is_ascii_digit()))
+        .count();
+    let with_hyphens = candidates
+        .iter()
+        .filter(|symbol| symbol.contains('-'))
+        .count();
 
-    if response.status().is_success() {
-        let fetched_symbol = response
-            .json::<GeneNamesResponse<GeneNamesDoc>>()
-            .await
-            .map(|json| {
-                if json.response_header.status == STATUS_SUCCESS {
-                    let nth = rng.random_range(1..=json.response.num_found) - 1;
-                    json.response.docs.into_iter().nth(nth)
-                } else {
-                    None
-                }
+    SymbolGuidelines {
+        num_letters,
+        candidates: candidates.len(),
+        digits_allowed: with_digits > 0,
+        hyphens_allowed: with_hyphens > 0,
+        digit_percent: percent(with_digits),
+        hyphen_percent: percent(with_hyphens),
+        formats: formats
+            .into_iter()
+            .take(MAX_FORMATS)
+            .map(|(pattern, (count, example))| SymbolFormat {
+                pattern,
+                percent: percent(count),
+                example: example.clone(),
             })
-            .map_err(|err| err.to_string())?
-            .map(|doc| doc.symbol);
+            .collect(),
+    }
+}
 
-        if let Some(symbol) = fetched_symbol {
-            Ok(symbol)
-        } else {
-            Err("No gene symbol found".to_string())
+pub async fn guidelines(
+    Path(PuzzleId(key)): Path<PuzzleId>,
+) -> Result<Json<SymbolGuidelines>, ApiError> {
+    length_guidelines(_num_letters(key).await?)
+        .await
+        .map(Json)
+        .map_err(ApiError::Upstream)
+}
+
+#[cached(result = true)]
+async fn length_guidelines(num_letters: usize) -> Result<SymbolGuidelines, String> {
+    let corpus = corpus::corpus().await?;
+    Ok(index_guidelines(
+        corpus
+            .symbols()
+            .filter(|symbol| is_allowed_answer(corpus, symbol)),
+        num_letters,
+    ))
+}
+
+pub async fn corpus() -> Json<Option<CorpusStats>> {
+    Json(corpus_stats().await.ok())
+}
+
+// only symbols that could be drawn as answers count as candidates
+#[cached(result = true)]
+async fn corpus_stats() -> Result<CorpusStats, String> {
+    let corpus = corpus::corpus().await?;
+    Ok(index_corpus(
+        corpus
+            .symbols()
+            .filter(|symbol| is_allowed_answer(corpus, symbol)),
+    ))
+}
+
+#[cached(
+    ty = "TimedSizedCache<u64, Result<String, String>>",
+    create = "{ cache::WORDS.build() }"
+)]
+async fn draw_word(key: u64) -> Result<String, String> {
+    draw_from(corpus::corpus().await?, key).ok_or_else(|| "No gene symbol found".to_string())
+}
+
+// a letter no symbol starts with, as in a fallback corpus, moves the draw on to the next letter
+// that has some, so every symbol can come up and the same key always draws the same one
+fn draw_from(corpus: &Corpus, key: u64) -> Option<String> {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(key);
+    let first_letter = rng.random_range(b'A'..=b'Z') - b'A';
+
+    (0..26).find_map(|offset| {
+        let letter = ((b'A' + (first_letter + offset) % 26) as char).to_string();
+        let symbols: Vec<&String> = corpus.starting_with(&letter).collect();
+        (!symbols.is_empty()).then(|| symbols[rng.random_range(0..symbols.len())].clone())
+    })
+}
+
+#[cached(
+    ty = "TimedSizedCache<u64, String>",
+    create = "{ cache::WORDS.build() }",
+    result = true
+)]
+pub(crate) async fn get_weekly_word(week: u64) -> Result<String, String> {
+    const WEEKLY_SALT: u64 = 0x5745_454b_4c59;
+    const MIN_LENGTH: usize = 6;
+    const MAX_DRAWS: usize = 20;
+
+    let mut rng: StdRng = SeedableRng::seed_from_u64(puzzle_ids::keyed(week ^ WEEKLY_SALT));
+    for _ in 0..MAX_DRAWS {
+        let word = draw_answer(rng.random()).await?;
+        if word.chars().count() >= MIN_LENGTH {
+            return Ok(word);
         }
-    } else {
-        Err("Unable to query genenames.org".to_string())
     }
+
+    Err("No weekly gene symbol found".to_string())
+}
+
+#[cached(
+    ty = "TimedSizedCache<String, Vec<String>>",
+    create = "{ cache::WORDS.build() }",
+    result = true
+)]
+async fn symbols_on_chromosome(chromosome: String) -> Result<Vec<String>, String> {
+    let corpus = corpus::corpus().await?;
+    Ok(corpus
+        .on_chromosome(&chromosome)
+        .filter(|symbol| is_allowed_answer(corpus, symbol))
+        .cloned()
+        .collect())
+}
+
+#[cached(
+    ty = "TimedSizedCache<(String, u64), String>",
+    create = "{ cache::WORDS.build() }",
+    result = true
+)]
+pub(crate) async fn get_pack_word(chromosome: String, index: u64) -> Result<String, String> {
+    let symbols = symbols_on_chromosome(chromosome.clone()).await?;
+    if symbols.is_empty() {
+        return Err("No gene symbol found".to_string());
+    }
+
+    let seed = chromosome
+        .bytes()
+        .fold(index, |seed, byte| seed.rotate_left(8) ^ byte as u64);
+    let mut rng: StdRng = SeedableRng::seed_from_u64(puzzle_ids::keyed(seed));
+
+    Ok(symbols[rng.random_range(0..symbols.len())].clone())
 }
 
 pub async fn guess(Json(guess): Json<Guess>) -> Json<GuessResult> {
+    let guess = guess.normalized();
     match _valid_guess(guess.clone()).await {
         Ok(None) => (),
         Ok(Some(reason)) => {
             return Json(GuessResult::Invalid(reason));
         }
         Err(err) => {
+            tracing::error!(
+                "Failed to validate {} for puzzle {}: {}",
+                redact(&guess.word.iter().collect::<String>()),
+                guess.session.0,
+                scrub(&err)
+            );
             return Json(GuessResult::Invalid(InvalidGuess::InternalError(
                 err.to_string(),
             )));
         }
     };
 
-    let word = match get_word(guess.session).await {
+    let word = match get_word(guess.session.0).await {
         Ok(word) => word,
         Err(err) => {
             return Json(GuessResult::Invalid(InvalidGuess::InternalError(
@@ -222,62 +816,94 @@ pub async fn guess(Json(guess): Json<Guess>) -> Json<GuessResult> {
     .chars()
     .collect::<Vec<_>>();
 
-    let mut char_counts: HashMap<char, usize> = HashMap::new();
-    for letter in &word {
-        *char_counts.entry(*letter).or_default() += 1;
-    }
-
-    let mut result = vec![LetterFeedback::Absent; word.len()];
-
-    for (i, (guessed, actual)) in guess.word.iter().zip(&word).enumerate() {
-        if guessed == actual {
-            result[i] = LetterFeedback::Correct;
-            *char_counts.get_mut(guessed).unwrap() -= 1;
-        }
-    }
-
-    for (i, guessed) in guess.word.iter().enumerate() {
-        if result[i] == LetterFeedback::Absent {
-            if let Some(count) = char_counts.get_mut(guessed) {
-                if *count > 0 {
-                    result[i] = LetterFeedback::Present;
-                    *count -= 1;
-                }
-            }
-        }
-    }
+    Json(GuessResult::Valid(score_guess(&guess.word, &word)))
+}
 
-    let is_correct = result
-        .iter()
-        .all(|&feedback| feedback == LetterFeedback::Correct);
+pub(crate) async fn save_caches(snapshot: &mut Snapshot) {
+    cache::save(
+        snapshot,
+        "genedle.draw_word",
+        cache::entries(&*DRAW_WORD.lock().await)
+            .into_iter()
+            .filter(|(_, value)| value.is_ok())
+            .collect::<Vec<_>>(),
+    );
+    cache::save(
+        snapshot,
+        "genedle.weekly_word",
+        cache::entries(&*GET_WEEKLY_WORD.lock().await),
+    );
+    cache::save(
+        snapshot,
+        "genedle.pack_word",
+        cache::entries(&*GET_PACK_WORD.lock().await),
+    );
+}
 
-    Json(GuessResult::Valid(ValidGuess { is_correct, result }))
+pub(crate) async fn load_caches(snapshot: &Snapshot) {
+    cache::load(snapshot, "genedle.draw_word", &mut *DRAW_WORD.lock().await);
+    cache::load(
+        snapshot,
+        "genedle.weekly_word",
+        &mut *GET_WEEKLY_WORD.lock().await,
+    );
+    cache::load(
+        snapshot,
+        "genedle.pack_word",
+        &mut *GET_PACK_WORD.lock().await,
+    );
 }
 
 #[cfg(test)]
 mod tests {
     use crate::api::genedle::{
-        GameMode, Guess, GuessResult, InvalidGuess, LetterFeedback, ValidGuess,
+        AnswerRejection, GameMode, Guess, GuessResult, InvalidGuess, PoolEntry, SymbolFormat,
+        ValidGuess, check_answer, draw_from, index_corpus, index_guidelines, pool_csv,
+        recent_answers,
     };
+    use crate::corpus::Corpus;
+    use crate::puzzle_ids::PuzzleId;
     use axum::Json;
+    use genedle_core::difficulty::DifficultyInputs;
+    use genedle_core::feedback::LetterFeedback;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_draw_from() {
+        let corpus = Corpus::parse(include_str!("../../fixtures/hgnc/sample.tsv")).unwrap();
+
+        // most letters start no symbol in the sample, yet every symbol gets drawn
+        let drawn: HashSet<String> = (0..2000)
+            .map(|key| draw_from(&corpus, key).unwrap())
+            .collect();
+        assert_eq!(drawn.len(), corpus.symbols().count());
+        assert_eq!(draw_from(&corpus, 7), draw_from(&corpus, 7));
+        assert_eq!(draw_from(&Corpus::default(), 7), None);
+    }
 
     #[tokio::test]
     async fn test_get_word() -> Result<(), String> {
+        let corpus = crate::corpus::init_sample().await;
         let result = super::get_word(1234567890).await?;
-        assert_eq!(result, "MIB2".to_string());
+        assert_eq!(result, "TP53".to_string());
+        assert!(corpus.contains(&result));
 
-        // two nearby seeds should return unpredictable results
-        let result = super::get_word(1234567891).await?;
-        assert_eq!(result, "TLX3".to_string());
+        // nearby seeds should return unpredictable results
+        let result = super::get_word(1234567893).await?;
+        assert_eq!(result, "KRT10".to_string());
         Ok(())
     }
 
     #[tokio::test]
     async fn test_guess() -> Result<(), anyhow::Error> {
+        // 1234567890 draws TP53 from the sample
+        crate::corpus::init_sample().await;
         let guess = Guess {
-            word: "MIB".chars().collect(),
-            session: 1234567890,
+            word: "TP5".chars().collect(),
+            session: PuzzleId(1234567890),
             mode: GameMode::Normal,
+            rating: None,
+            assist: false,
         };
 
         let response = super::guess(Json(guess)).await;
@@ -287,9 +913,11 @@ mod tests {
         );
 
         let guess = Guess {
-            word: "MIB22".chars().collect(),
-            session: 1234567890,
+            word: "TP533".chars().collect(),
+            session: PuzzleId(1234567890),
             mode: GameMode::Normal,
+            rating: None,
+            assist: false,
         };
 
         let response = super::guess(Json(guess)).await;
@@ -299,115 +927,215 @@ mod tests {
         );
 
         let guess = Guess {
-            word: "MIB2".chars().collect(),
-            session: 1234567890,
+            word: "TP53".chars().collect(),
+            session: PuzzleId(1234567890),
             mode: GameMode::Normal,
+            rating: None,
+            assist: false,
         };
 
         let response = super::guess(Json(guess)).await;
         assert_eq!(
             response.0,
-            GuessResult::Valid(ValidGuess {
-                is_correct: true,
-                result: vec![LetterFeedback::Correct; 4],
-            })
+            GuessResult::Valid(ValidGuess::new(true, vec![LetterFeedback::Correct; 4]))
         );
 
         let guess = Guess {
             word: "AAAA".chars().collect(),
-            session: 1234567890,
+            session: PuzzleId(1234567890),
             mode: GameMode::Normal,
+            rating: None,
+            assist: false,
         };
 
         let response = super::guess(Json(guess)).await;
         assert_eq!(
             response.0,
-            GuessResult::Valid(ValidGuess {
-                is_correct: false,
-                result: vec![LetterFeedback::Absent; 4],
-            })
+            GuessResult::Valid(ValidGuess::new(false, vec![LetterFeedback::Absent; 4]))
         );
 
         let guess = Guess {
-            word: "MIB3".chars().collect(),
-            session: 1234567890,
+            word: "TP54".chars().collect(),
+            session: PuzzleId(1234567890),
             mode: GameMode::Normal,
+            rating: None,
+            assist: false,
         };
 
         let response = super::guess(Json(guess)).await;
         assert_eq!(
             response.0,
-            GuessResult::Valid(ValidGuess {
-                is_correct: false,
-                result: vec![
+            GuessResult::Valid(ValidGuess::new(
+                false,
+                vec![
                     LetterFeedback::Correct,
                     LetterFeedback::Correct,
                     LetterFeedback::Correct,
                     LetterFeedback::Absent
-                ],
-            })
+                ]
+            ))
         );
 
         let guess = Guess {
-            word: "2IBM".chars().collect(),
-            session: 1234567890,
+            word: "3P5T".chars().collect(),
+            session: PuzzleId(1234567890),
             mode: GameMode::Normal,
+            rating: None,
+            assist: false,
         };
 
         let response = super::guess(Json(guess)).await;
         assert_eq!(
             response.0,
-            GuessResult::Valid(ValidGuess {
-                is_correct: false,
-                result: vec![
+            GuessResult::Valid(ValidGuess::new(
+                false,
+                vec![
                     LetterFeedback::Present,
                     LetterFeedback::Correct,
                     LetterFeedback::Correct,
                     LetterFeedback::Present
-                ],
-            })
+                ]
+            ))
         );
 
         let guess = Guess {
-            word: "M2B2".chars().collect(),
-            session: 1234567890,
+            word: "T353".chars().collect(),
+            session: PuzzleId(1234567890),
             mode: GameMode::Normal,
+            rating: None,
+            assist: false,
         };
 
         let response = super::guess(Json(guess)).await;
         assert_eq!(
             response.0,
-            GuessResult::Valid(ValidGuess {
-                is_correct: false,
-                result: vec![
+            GuessResult::Valid(ValidGuess::new(
+                false,
+                vec![
                     LetterFeedback::Correct,
                     LetterFeedback::Absent,
                     LetterFeedback::Correct,
                     LetterFeedback::Correct
-                ],
-            })
+                ]
+            ))
         );
 
         let guess = Guess {
-            word: "2222".chars().collect(),
-            session: 1234567890,
+            word: "3333".chars().collect(),
+            session: PuzzleId(1234567890),
             mode: GameMode::Normal,
+            rating: None,
+            assist: false,
         };
 
         let response = super::guess(Json(guess)).await;
         assert_eq!(
             response.0,
-            GuessResult::Valid(ValidGuess {
-                is_correct: false,
-                result: vec![
+            GuessResult::Valid(ValidGuess::new(
+                false,
+                vec![
                     LetterFeedback::Absent,
                     LetterFeedback::Absent,
                     LetterFeedback::Absent,
                     LetterFeedback::Correct
-                ],
-            })
+                ]
+            ))
         );
 
         Ok(())
     }
+
+    #[test]
+    fn test_index_corpus() {
+        let symbols = ["TP53", "TNF", "BRCA1", "BRCA2", "A1BG"].map(String::from);
+        let stats = index_corpus(&symbols);
+
+        assert_eq!(stats.total, 5);
+        assert_eq!(stats.by_length.get(&3), Some(&1));
+        assert_eq!(stats.by_length.get(&4), Some(&2));
+        assert_eq!(stats.by_length.get(&5), Some(&2));
+        assert_eq!(stats.by_first_letter.get(&'T'), Some(&2));
+        assert_eq!(stats.by_first_letter.get(&'B'), Some(&2));
+        assert_eq!(stats.by_first_letter.get(&'Z'), None);
+    }
+
+    #[test]
+    fn test_recent_answers() {
+        let history = [(10, "TP53"), (11, "EGFR"), (14, "MYC"), (15, "KRT10")]
+            .into_iter()
+            .map(|(day, word)| (day, word.to_string()))
+            .collect();
+
+        // day 12 and 13 were never drawn, so the window reaches back past them
+        let mut recent: Vec<&str> = recent_answers(&history, 15, 3).into_iter().collect();
+        recent.sort();
+        assert_eq!(recent, ["EGFR", "MYC", "TP53"]);
+        assert!(recent_answers(&history, 10, 3).is_empty());
+    }
+
+    #[test]
+    fn test_pool_csv() {
+        let entry = PoolEntry {
+            symbol: "BRCA1".to_string(),
+            inputs: DifficultyInputs {
+                length: 5,
+                digits: 1,
+                candidates: 40,
+                opener_overlap: 1.0,
+            },
+            estimated_solve_rate: 0.8125,
+            last_used: Some("2025-03-01".to_string()),
+            recent: true,
+        };
+        let csv = pool_csv(&[entry]);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "symbol,length,digits,candidates,opener_overlap,estimated_solve_rate,last_used,recent"
+            )
+        );
+        assert_eq!(
+            lines.next(),
+            Some("BRCA1,5,1,40,1.000,0.812,2025-03-01,true")
+        );
+    }
+
+    #[test]
+    fn test_check_answer() {
+        assert_eq!(check_answer("BRCA1"), Ok(()));
+        assert_eq!(check_answer("HLA-DRB1"), Ok(()));
+        assert_eq!(check_answer("MYC"), Err(AnswerRejection::TooShort(3)));
+        assert_eq!(
+            check_answer("LINC-PINT-ANTISENSE"),
+            Err(AnswerRejection::TooLong(19))
+        );
+        assert_eq!(check_answer("C1orf"), Err(AnswerRejection::Unplayable('o')));
+    }
+
+    #[test]
+    fn test_index_guidelines() {
+        let symbols: Vec<String> = ["TP53", "MYC", "KRT1", "CD-4", "ABCA", "EGFR"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let guidelines = index_guidelines(&symbols, 4);
+        assert_eq!(guidelines.candidates, 5);
+        assert!(guidelines.digits_allowed);
+        assert!(guidelines.hyphens_allowed);
+        assert_eq!(guidelines.digit_percent, 60);
+        assert_eq!(guidelines.hyphen_percent, 20);
+        assert_eq!(
+            guidelines.formats[0],
+            SymbolFormat {
+                pattern: "AAAA".to_string(),
+                percent: 40,
+                example: "ABCA".to_string(),
+            }
+        );
+        assert_eq!(guidelines.formats.len(), 3);
+
+        assert_eq!(index_guidelines(&symbols, 7).candidates, 0);
+    }
 }
@@ -1,7 +1,13 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
 
+pub mod gene;
+pub mod gene_group;
 pub mod genections;
 pub mod genedle;
+pub mod public;
 pub mod spelling_gene;
 
 #[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -23,7 +29,123 @@ pub(crate) struct GeneNamesResponseBody<T: Serialize + PartialEq + Eq + Clone> {
     pub(crate) docs: Vec<T>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
-pub(crate) struct GeneNamesDoc {
-    pub(crate) symbol: String,
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct SchemaDrift {
+    pub(crate) endpoint: String,
+    pub(crate) error: String,
+}
+
+impl std::fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unexpected response shape from genenames.org {}: {}",
+            self.endpoint, self.error
+        )
+    }
+}
+
+impl std::error::Error for SchemaDrift {}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct DriftStats {
+    pub count: u64,
+    pub last_error: String,
+    pub last_seen: i64,
+}
+
+static DRIFT: LazyLock<Mutex<BTreeMap<String, DriftStats>>> = LazyLock::new(Default::default);
+
+pub fn schema_drift() -> BTreeMap<String, DriftStats> {
+    DRIFT.lock().unwrap().clone()
+}
+
+fn record_drift(drift: &SchemaDrift) {
+    tracing::warn!("{}", crate::redact::scrub(&drift.to_string()));
+
+    let mut stats = DRIFT.lock().unwrap();
+    let stats = stats.entry(drift.endpoint.clone()).or_default();
+    stats.count += 1;
+    stats.last_error = drift.error.clone();
+    stats.last_seen = chrono::Utc::now().timestamp();
+}
+
+pub(crate) fn parse_body<T>(
+    endpoint: &str,
+    body: &[u8],
+) -> Result<GeneNamesResponse<T>, SchemaDrift>
+where
+    T: DeserializeOwned + Serialize + PartialEq + Eq + Clone,
+{
+    serde_json::from_slice(body).map_err(|err| {
+        let drift = SchemaDrift {
+            endpoint: endpoint.to_string(),
+            error: err.to_string(),
+        };
+        record_drift(&drift);
+        drift
+    })
+}
+
+// the symbol being looked up is the last path segment, so only the first two identify the endpoint
+fn endpoint(url: &reqwest::Url) -> String {
+    url.path_segments()
+        .map(|segments| {
+            segments
+                .take(2)
+                .fold(String::new(), |path, segment| path + "/" + segment)
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) trait GeneNamesExt {
+    async fn gene_names<T>(self) -> Result<GeneNamesResponse<T>, anyhow::Error>
+    where
+        T: DeserializeOwned + Serialize + PartialEq + Eq + Clone;
+}
+
+impl GeneNamesExt for reqwest::Response {
+    async fn gene_names<T>(self) -> Result<GeneNamesResponse<T>, anyhow::Error>
+    where
+        T: DeserializeOwned + Serialize + PartialEq + Eq + Clone,
+    {
+        let endpoint = endpoint(self.url());
+        let body = self.bytes().await?;
+
+        Ok(parse_body(&endpoint, &body)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::gene::GeneRecord;
+    use crate::api::{parse_body, schema_drift};
+
+    const FETCH_SYMBOL: &str = include_str!("../../fixtures/genenames/fetch_symbol.json");
+    const FETCH_SYMBOL_EMPTY: &str =
+        include_str!("../../fixtures/genenames/fetch_symbol_empty.json");
+    const FETCH_SYMBOL_DRIFTED: &str =
+        include_str!("../../fixtures/genenames/fetch_symbol_drifted.json");
+
+    #[test]
+    fn test_fetch_contract() {
+        let json = parse_body::<GeneRecord>("/fetch/symbol", FETCH_SYMBOL.as_bytes()).unwrap();
+        let record = &json.response.docs[0];
+        assert_eq!(record.symbol, "TP53");
+        assert_eq!(record.location.as_deref(), Some("17p13.1"));
+        assert_eq!(record.alias_symbol, vec!["p53", "LFS1"]);
+
+        let json =
+            parse_body::<GeneRecord>("/fetch/symbol", FETCH_SYMBOL_EMPTY.as_bytes()).unwrap();
+        assert_eq!(json.response.num_found, 0);
+        assert!(json.response.docs.is_empty());
+    }
+
+    #[test]
+    fn test_schema_drift() {
+        let drift = parse_body::<GeneRecord>("/fetch/drifted", FETCH_SYMBOL_DRIFTED.as_bytes())
+            .unwrap_err();
+        assert_eq!(drift.endpoint, "/fetch/drifted");
+        assert_eq!(schema_drift()["/fetch/drifted"].count, 1);
+    }
 }
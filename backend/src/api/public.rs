@@ -0,0 +1,239 @@
+use crate::backup;
+use crate::curation::{self, SpecialInfo};
+use crate::games::spelling_gene::{UNIX_EPOCH_DAYS_FROM_CE, daily_seed};
+use crate::schedule;
+use crate::stats;
+use axum::Json;
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+
+const GAMES: [&str; 4] = ["genedle", "genedle-weekly", "genections", "spelling-gene"];
+const HEATMAP_DEFAULT_DAYS: u64 = 90;
+const HEATMAP_MAX_DAYS: u64 = 366;
+
+// only what is safe to show before playing: never answers, letters or categories
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct PuzzleMetadata {
+    pub game: &'static str,
+    pub puzzle: u64,
+    pub date: Option<String>,
+    pub number: Option<u64>,
+    pub participants: u64,
+    // a hand-picked Spelling Gene day's title and bonus, never its letters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub special: Option<SpecialInfo>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct PuzzleNumber {
+    pub game: &'static str,
+    pub puzzle: u64,
+    pub number: u64,
+    pub date: Option<String>,
+}
+
+fn current_puzzle(game: &str) -> u64 {
+    match game {
+        "genedle-weekly" => schedule::this_week(),
+        "spelling-gene" => daily_seed(),
+        _ => schedule::today(),
+    }
+}
+
+fn first_day(game: &str, puzzle: u64) -> u64 {
+    match game {
+        "genedle-weekly" => puzzle * 7 + 1,
+        "spelling-gene" => puzzle + UNIX_EPOCH_DAYS_FROM_CE,
+        _ => puzzle,
+    }
+}
+
+fn first_date(game: &str, puzzle: u64) -> Option<String> {
+    i32::try_from(first_day(game, puzzle))
+        .ok()
+        .and_then(NaiveDate::from_num_days_from_ce_opt)
+        .map(|date| date.to_string())
+}
+
+// what share strings and the archive call the puzzle
+pub(crate) fn puzzle_number(game: &str, puzzle: u64) -> Option<u64> {
+    match game {
+        "genedle-weekly" => schedule::week_number(puzzle),
+        _ => schedule::puzzle_number(first_day(game, puzzle)),
+    }
+}
+
+fn puzzle_metadata(game: &'static str, puzzle: u64) -> PuzzleMetadata {
+    PuzzleMetadata {
+        game,
+        puzzle,
+        date: first_date(game, puzzle),
+        number: puzzle_number(game, puzzle),
+        participants: stats::participation(game, puzzle),
+        special: (game == "spelling-gene")
+            .then(|| curation::special(first_day(game, puzzle)))
+            .flatten()
+            .map(|special| special.info()),
+    }
+}
+
+// aggregates over everyone, so nothing in here can be traced back to a player
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct DailySummary {
+    pub game: &'static str,
+    pub puzzle: u64,
+    pub attempted: u64,
+    pub finished: u64,
+    pub solve_percent: Option<u64>,
+    pub average_guesses: Option<f64>,
+}
+
+fn daily_summary_of(game: &'static str, puzzle: u64) -> DailySummary {
+    let outcomes = stats::outcomes(game, puzzle);
+    DailySummary {
+        game,
+        puzzle,
+        attempted: stats::participation(game, puzzle),
+        finished: outcomes.finished,
+        solve_percent: (outcomes.finished > 0).then(|| outcomes.solved * 100 / outcomes.finished),
+        average_guesses: (outcomes.solved > 0)
+            .then(|| (outcomes.guesses * 10 / outcomes.solved) as f64 / 10.0),
+    }
+}
+
+pub async fn puzzles() -> Json<Vec<PuzzleMetadata>> {
+    Json(
+        GAMES
+            .into_iter()
+            .map(|game| puzzle_metadata(game, current_puzzle(game)))
+            .collect(),
+    )
+}
+
+pub async fn puzzle(Path((game, puzzle)): Path<(String, u64)>) -> Json<Option<PuzzleMetadata>> {
+    Json(
+        GAMES
+            .into_iter()
+            .find(|&known| known == game)
+            .filter(|&game| puzzle <= current_puzzle(game))
+            .map(|game| puzzle_metadata(game, puzzle)),
+    )
+}
+
+pub async fn daily_summary(Path(game): Path<String>) -> Json<Option<DailySummary>> {
+    Json(
+        GAMES
+            .into_iter()
+            .find(|&known| known == game)
+            .map(|game| daily_summary_of(game, current_puzzle(game))),
+    )
+}
+
+pub async fn today_number(Path(game): Path<String>) -> Json<Option<PuzzleNumber>> {
+    Json(
+        GAMES
+            .into_iter()
+            .find(|&known| known == game)
+            .and_then(|game| {
+                let puzzle = current_puzzle(game);
+                Some(PuzzleNumber {
+                    game,
+                    puzzle,
+                    number: puzzle_number(game, puzzle)?,
+                    date: first_date(game, puzzle),
+                })
+            }),
+    )
+}
+
+// one cell of the public stats page's calendar
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct HeatmapDay {
+    pub date: Option<String>,
+    pub puzzle: u64,
+    pub number: Option<u64>,
+    pub attempted: u64,
+    pub completed: u64,
+}
+
+// YYYY-MM-DD, both ends included; defaults to the last few months up to today
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct HeatmapQuery {
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+// past days can't change once they're over, so they're kept until the nightly job next prunes
+// the counts they're read from; today's is always read fresh
+#[derive(Default)]
+struct HeatmapCache {
+    nightly: Option<i64>,
+    days: BTreeMap<u64, HeatmapDay>,
+}
+
+static HEATMAP: LazyLock<Mutex<HeatmapCache>> = LazyLock::new(Default::default);
+
+fn heatmap_day(seed: u64) -> HeatmapDay {
+    HeatmapDay {
+        date: first_date("spelling-gene", seed),
+        puzzle: seed,
+        number: puzzle_number("spelling-gene", seed),
+        attempted: stats::participation("spelling-gene", seed),
+        completed: stats::completions("spelling-gene", seed),
+    }
+}
+
+fn heatmap_seed(date: &str) -> Option<u64> {
+    let day = date.parse::<NaiveDate>().ok()?.num_days_from_ce();
+    u64::try_from(day)
+        .ok()?
+        .checked_sub(UNIX_EPOCH_DAYS_FROM_CE)
+}
+
+fn heatmap(from: u64, to: u64) -> Vec<HeatmapDay> {
+    let today = daily_seed();
+    let nightly = backup::status().last_run;
+
+    let mut cache = HEATMAP.lock().unwrap();
+    if cache.nightly != nightly {
+        *cache = HeatmapCache {
+            nightly,
+            days: BTreeMap::new(),
+        };
+    }
+    (from..=to.min(today))
+        .map(|seed| {
+            if seed == today {
+                heatmap_day(seed)
+            } else {
+                cache
+                    .days
+                    .entry(seed)
+                    .or_insert_with(|| heatmap_day(seed))
+                    .clone()
+            }
+        })
+        .collect()
+}
+
+pub async fn spelling_gene_heatmap(
+    Query(query): Query<HeatmapQuery>,
+) -> Result<Json<Vec<HeatmapDay>>, StatusCode> {
+    let parse =
+        |date: Option<String>| date.map(|date| heatmap_seed(&date).ok_or(StatusCode::BAD_REQUEST));
+    let to = parse(query.to).transpose()?.unwrap_or_else(daily_seed);
+    let from = parse(query.from)
+        .transpose()?
+        .unwrap_or_else(|| to.saturating_sub(HEATMAP_DEFAULT_DAYS - 1));
+    if from > to || to - from >= HEATMAP_MAX_DAYS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(Json(heatmap(from, to)))
+}
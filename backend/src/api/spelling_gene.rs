@@ -1,179 +1,449 @@
-use crate::api::{GeneNamesDoc, GeneNamesResponse};
+use crate::cache::{self, Snapshot};
+use crate::config::config;
+use crate::corpus::corpus;
+use crate::error::{ApiError, respond};
+use crate::extract::StrictJson;
+use crate::games::spelling_gene::{MIN_LENGTH, MIN_WORDS, NUM_LETTERS};
+use crate::puzzle_ids::{self, PuzzleId};
 use axum::Json;
 use axum::extract::Path;
 use cached::proc_macro::cached;
+use cached::{Cached, TimedSizedCache, UnboundCache};
+pub use genedle_core::spelling_gene::{
+    InvalidSpellingGeneGuess, SpellingGeneGame, SpellingGeneGuessResult, SpellingGeneMetadata,
+    normalize_guess,
+};
+use genedle_core::spelling_gene::{VALID_LETTERS, game_from_letters, pick_game, sample_letters};
 use rand::SeedableRng;
 use rand::rngs::StdRng;
-use rand::seq::SliceRandom;
-use reqwest::Client;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
 
-#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
-pub struct SpellingGeneGame {
-    #[serde(flatten)]
-    pub metadata: SpellingGeneMetadata,
-    pub valid_symbols: BTreeSet<String>,
+// outside these, generation can't find a puzzle and only spins through its iterations
+const MIN_LENGTH_RANGE: RangeInclusive<usize> = 2..=8;
+const MIN_WORDS_RANGE: RangeInclusive<usize> = 1..=50;
+const NUM_LETTERS_RANGE: RangeInclusive<u8> = 4..=10;
+
+// what a caller can ask of a generated puzzle; anything left out plays like the daily
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(default)]
+pub struct GameParams {
+    pub min_length: usize,
+    pub min_words: usize,
+    pub num_letters: u8,
 }
 
-#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
-pub struct SpellingGeneMetadata {
-    pub outer_letters: Vec<&'static str>,
-    pub center_letter: &'static str,
+impl Default for GameParams {
+    fn default() -> Self {
+        GameParams {
+            min_length: MIN_LENGTH,
+            min_words: MIN_WORDS,
+            num_letters: NUM_LETTERS,
+        }
+    }
 }
 
-pub async fn check_guess(
-    Path((seed, min_length, min_words, num_letters, guess)): Path<(u64, usize, usize, u8, String)>,
-) -> Json<bool> {
-    match generate_game(min_length, min_words, num_letters, seed).await {
-        Ok(game) => Json(game.valid_symbols.contains(&guess)),
-        Err(_) => Json(false),
+fn check_range<T: PartialOrd + std::fmt::Display>(
+    name: &str,
+    value: T,
+    range: RangeInclusive<T>,
+) -> Option<String> {
+    (!range.contains(&value)).then(|| {
+        format!(
+            "{name} must be between {} and {}, not {value}",
+            range.start(),
+            range.end()
+        )
+    })
+}
+
+impl GameParams {
+    // every out-of-range parameter is named, so one round trip fixes them all
+    pub fn validate(self) -> Result<Self, ApiError> {
+        let errors: Vec<String> = [
+            check_range("min_length", self.min_length, MIN_LENGTH_RANGE),
+            check_range("min_words", self.min_words, MIN_WORDS_RANGE),
+            check_range("num_letters", self.num_letters, NUM_LETTERS_RANGE),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(ApiError::BadRequest(errors.join("; ")))
+        }
     }
 }
 
-pub async fn get_letters(
-    Path((seed, min_length, min_words, num_letters)): Path<(u64, usize, usize, u8)>,
-) -> Json<SpellingGeneMetadata> {
-    generate_game(min_length, min_words, num_letters, seed)
-        .await
-        .map(|game| Json(game.metadata))
-        .unwrap_or_else(|_| {
-            Json(SpellingGeneMetadata {
-                outer_letters: Vec::new(),
-                center_letter: "",
-            })
+// the parameters are spelled out rather than flattened, since serde can't deny unknown fields
+// through a flatten and a misspelt one would quietly play the defaults
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PuzzleRequest {
+    pub puzzle: PuzzleId,
+    #[serde(default = "default_min_length")]
+    pub min_length: usize,
+    #[serde(default = "default_min_words")]
+    pub min_words: usize,
+    #[serde(default = "default_num_letters")]
+    pub num_letters: u8,
+}
+
+// the guess travels in the body, so symbols like HLA-A need no escaping
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GuessRequest {
+    pub puzzle: PuzzleId,
+    pub guess: String,
+    #[serde(default = "default_min_length")]
+    pub min_length: usize,
+    #[serde(default = "default_min_words")]
+    pub min_words: usize,
+    #[serde(default = "default_num_letters")]
+    pub num_letters: u8,
+}
+
+fn default_min_length() -> usize {
+    MIN_LENGTH
+}
+
+fn default_min_words() -> usize {
+    MIN_WORDS
+}
+
+fn default_num_letters() -> u8 {
+    NUM_LETTERS
+}
+
+impl PuzzleRequest {
+    fn params(&self) -> GameParams {
+        GameParams {
+            min_length: self.min_length,
+            min_words: self.min_words,
+            num_letters: self.num_letters,
+        }
+    }
+}
+
+impl GuessRequest {
+    fn params(&self) -> GameParams {
+        GameParams {
+            min_length: self.min_length,
+            min_words: self.min_words,
+            num_letters: self.num_letters,
+        }
+    }
+}
+
+pub async fn puzzle(
+    StrictJson(request): StrictJson<PuzzleRequest>,
+) -> Result<Json<SpellingGeneMetadata>, ApiError> {
+    let params = request.params().validate()?;
+    let game = generate_game(
+        params.min_length,
+        params.min_words,
+        params.num_letters,
+        request.puzzle.0,
+    )
+    .await?;
+    Ok(Json(game.metadata))
+}
+
+pub async fn guess(
+    StrictJson(request): StrictJson<GuessRequest>,
+) -> Result<Json<SpellingGeneGuessResult>, ApiError> {
+    let params = request.params().validate()?;
+    let game = generate_game(
+        params.min_length,
+        params.min_words,
+        params.num_letters,
+        request.puzzle.0,
+    )
+    .await?;
+    respond(judge_guess(&game, params.min_length, &request.guess).await)
+}
+
+// v1 spells everything out in the path; kept until its sunset as a shim over v2
+pub async fn check_guess(
+    Path((puzzle, min_length, min_words, num_letters, guess)): Path<(
+        PuzzleId,
+        usize,
+        usize,
+        u8,
+        String,
+    )>,
+) -> Result<Json<SpellingGeneGuessResult>, ApiError> {
+    self::guess(StrictJson(GuessRequest {
+        puzzle,
+        guess,
+        min_length,
+        min_words,
+        num_letters,
+    }))
+    .await
+}
+
+// HGNC keeps a few lowercase symbols (C1orf112), so the raw guess is tried as well
+pub(crate) async fn judge_guess(
+    game: &SpellingGeneGame,
+    min_length: usize,
+    guess: &str,
+) -> SpellingGeneGuessResult {
+    let normalized = normalize_guess(guess);
+    let corpus = match corpus().await {
+        Ok(corpus) => corpus,
+        Err(err) => {
+            return SpellingGeneGuessResult::Invalid(InvalidSpellingGeneGuess::InternalError(err));
+        }
+    };
+
+    genedle_core::spelling_gene::check_guess(&normalized, game, min_length, |symbol| {
+        [symbol, guess.trim()].into_iter().any(|symbol| {
+            corpus.contains(symbol)
+                || (config().spelling_gene_aliases && corpus.aliases().any(|alias| alias == symbol))
         })
+    })
 }
 
-async fn generate_game(
+pub async fn get_letters(
+    Path((puzzle, min_length, min_words, num_letters)): Path<(PuzzleId, usize, usize, u8)>,
+) -> Result<Json<SpellingGeneMetadata>, ApiError> {
+    self::puzzle(StrictJson(PuzzleRequest {
+        puzzle,
+        min_length,
+        min_words,
+        num_letters,
+    }))
+    .await
+}
+
+pub(crate) async fn generate_game(
     min_length: usize,
     min_words: usize,
     num_letters: u8,
     seed: u64,
 ) -> Result<SpellingGeneGame, anyhow::Error> {
-    _generate_game(min_length, min_words, num_letters, seed)
-        .await
-        .map_err(|err| anyhow::anyhow!(err))
+    _generate_game(
+        min_length,
+        min_words,
+        num_letters,
+        seed,
+        config().spelling_gene_aliases,
+    )
+    .await
+    .map_err(|err| anyhow::anyhow!(err))
 }
 
-#[cached]
+#[cached(
+    ty = "TimedSizedCache<(usize, usize, u8, u64, bool), Result<SpellingGeneGame, String>>",
+    create = "{ cache::GAMES.build() }"
+)]
 async fn _generate_game(
     min_length: usize,
     min_words: usize,
     num_letters: u8,
     seed: u64,
+    aliases: bool,
 ) -> Result<SpellingGeneGame, String> {
-    const API: &str = "https://rest.genenames.org/search/symbol/";
-    const STATUS_SUCCESS: usize = 0;
     const MAX_ITERS: usize = 10_000;
-    const VALID_LETTERS: [&str; 27] = [
-        "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R",
-        "S", "T", "U", "V", "W", "X", "Y", "Z", "-",
-    ];
-
-    let client = Client::new();
-    let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
-
-    let get_options = async |letter: &str| -> Result<BTreeSet<String>, anyhow::Error> {
-        let starting_with = client
-            .get(format!("{API}{letter}*"))
-            .header(reqwest::header::ACCEPT, "application/json")
-            .send()
-            .await?
-            .json::<GeneNamesResponse<GeneNamesDoc>>()
-            .await
-            .map(|json| {
-                if json.response_header.status == STATUS_SUCCESS {
-                    Some(json.response.docs)
-                } else {
-                    None
-                }
-            })?;
-        let containing = client
-            .get(format!("{API}*{letter}"))
-            .header(reqwest::header::ACCEPT, "application/json")
-            .send()
-            .await?
-            .json::<GeneNamesResponse<GeneNamesDoc>>()
-            .await
-            .map(|json| {
-                if json.response_header.status == STATUS_SUCCESS {
-                    Some(json.response.docs)
-                } else {
-                    None
-                }
-            })?;
-
-        Ok(starting_with
-            .into_iter()
-            .chain(containing)
-            .flat_map(|x| x.into_iter().map(|x| x.symbol))
-            .collect())
+
+    let corpus = corpus().await?;
+    // keyed, since the daily seed is just the date
+    let mut rng: StdRng = SeedableRng::seed_from_u64(puzzle_ids::keyed(seed));
+
+    let alias_symbols: BTreeSet<&String> = if aliases {
+        corpus.aliases().collect()
+    } else {
+        BTreeSet::new()
     };
 
     let mut all_symbols: BTreeSet<String> = BTreeSet::new();
+    for letter in sample_letters(&mut rng, num_letters as usize + 5) {
+        all_symbols.extend(
+            corpus
+                .symbols()
+                .chain(alias_symbols.iter().copied())
+                .filter(|s| s.starts_with(letter) || s.ends_with(letter))
+                .filter(|s| s.chars().count() >= min_length)
+                .cloned(),
+        );
+    }
 
-    let mut letters = VALID_LETTERS.to_vec();
-    letters.shuffle(&mut rng);
-    letters.truncate(num_letters as usize + 5);
+    let mut game = pick_game(&all_symbols, min_words, num_letters, MAX_ITERS, &mut rng)
+        .ok_or_else(|| "Failed to generate a valid game".to_string())?;
+    game.aliases = game
+        .valid_symbols
+        .iter()
+        .filter(|symbol| alias_symbols.contains(symbol))
+        .cloned()
+        .collect();
 
-    for letter in letters {
-        if let Ok(symbols) = get_options(letter).await {
-            all_symbols.extend(
-                symbols
-                    .into_iter()
-                    .filter(|s| s.chars().count() >= min_length),
-            );
-        }
-    }
+    Ok(game)
+}
 
-    let mut iter = 0;
-    while iter < MAX_ITERS {
-        iter += 1;
+// a special puzzle's letters are picked by hand, so only the symbols are left to find
+pub(crate) async fn fixed_game(
+    center_letter: &str,
+    outer_letters: &[String],
+    min_length: usize,
+) -> Result<SpellingGeneGame, anyhow::Error> {
+    _fixed_game(
+        center_letter.to_string(),
+        outer_letters.to_vec(),
+        min_length,
+        config().spelling_gene_aliases,
+    )
+    .await
+    .map_err(|err| anyhow::anyhow!(err))
+}
 
-        let mut letters = VALID_LETTERS.to_vec();
-        letters.shuffle(&mut rng);
-        letters.truncate(num_letters as usize);
+#[cached(
+    ty = "TimedSizedCache<(String, Vec<String>, usize, bool), Result<SpellingGeneGame, String>>",
+    create = "{ cache::GAMES.build() }"
+)]
+async fn _fixed_game(
+    center_letter: String,
+    outer_letters: Vec<String>,
+    min_length: usize,
+    aliases: bool,
+) -> Result<SpellingGeneGame, String> {
+    let corpus = corpus().await?;
+    let unknown = |letter: &str| format!("{letter:?} isn't a puzzle letter");
+    let center_letter = intern(&center_letter).ok_or_else(|| unknown(&center_letter))?;
+    let outer_letters = outer_letters
+        .iter()
+        .map(|letter| intern(letter).ok_or_else(|| unknown(letter)))
+        .collect::<Result<Vec<_>, _>>()?;
 
-        let mut letters_set: BTreeSet<char> = letters.iter().flat_map(|s| s.chars()).collect();
-        let center_letter = letters.pop().unwrap();
-        let center_char = center_letter.chars().next().unwrap();
-        letters_set.insert(center_char);
+    let alias_symbols: BTreeSet<&String> = if aliases {
+        corpus.aliases().collect()
+    } else {
+        BTreeSet::new()
+    };
+    let all_symbols: BTreeSet<String> = corpus
+        .symbols()
+        .chain(alias_symbols.iter().copied())
+        .filter(|s| s.chars().count() >= min_length)
+        .cloned()
+        .collect();
 
-        let filtered: BTreeSet<_> = all_symbols
-            .iter()
-            .filter(|symbol| {
-                symbol.contains(center_char) && symbol.chars().all(|c| letters_set.contains(&c))
-            })
-            .collect();
+    let mut game = game_from_letters(&all_symbols, outer_letters, center_letter);
+    game.aliases = game
+        .valid_symbols
+        .iter()
+        .filter(|symbol| alias_symbols.contains(symbol))
+        .cloned()
+        .collect();
 
-        if filtered.len() >= min_words {
-            return Ok(SpellingGeneGame {
-                metadata: SpellingGeneMetadata {
-                    outer_letters: letters,
-                    center_letter,
+    Ok(game)
+}
+
+// letters are interned in VALID_LETTERS, so snapshots store them as plain strings
+#[derive(Serialize, Deserialize)]
+struct CachedGame {
+    outer_letters: Vec<String>,
+    center_letter: String,
+    valid_symbols: BTreeSet<String>,
+    #[serde(default)]
+    aliases: BTreeSet<String>,
+}
+
+pub(crate) fn intern(letter: &str) -> Option<&'static str> {
+    VALID_LETTERS.into_iter().find(|&valid| valid == letter)
+}
+
+pub(crate) async fn save_caches(snapshot: &mut Snapshot) {
+    let games = _GENERATE_GAME.lock().await;
+    let entries: Vec<_> = cache::entries(&games)
+        .into_iter()
+        .filter_map(|(key, game)| {
+            let game = game.as_ref().ok()?;
+            Some((
+                *key,
+                CachedGame {
+                    outer_letters: game
+                        .metadata
+                        .outer_letters
+                        .iter()
+                        .map(|letter| letter.to_string())
+                        .collect(),
+                    center_letter: game.metadata.center_letter.to_string(),
+                    valid_symbols: game.valid_symbols.clone(),
+                    aliases: game.aliases.clone(),
                 },
-                valid_symbols: filtered.into_iter().cloned().collect(),
-            });
+            ))
+        })
+        .collect();
+
+    cache::save(snapshot, "spelling_gene.game", entries);
+}
+
+pub(crate) async fn load_caches(snapshot: &Snapshot) {
+    let mut games = UnboundCache::<(usize, usize, u8, u64, bool), CachedGame>::new();
+    cache::load(snapshot, "spelling_gene.game", &mut games);
+
+    let mut cache = _GENERATE_GAME.lock().await;
+    for (key, game) in games.get_store() {
+        let outer_letters: Option<Vec<_>> = game
+            .outer_letters
+            .iter()
+            .map(|letter| intern(letter))
+            .collect();
+        if let (Some(outer_letters), Some(center_letter)) =
+            (outer_letters, intern(&game.center_letter))
+        {
+            cache.cache_set(
+                *key,
+                Ok(SpellingGeneGame {
+                    metadata: SpellingGeneMetadata {
+                        outer_letters,
+                        center_letter,
+                    },
+                    valid_symbols: game.valid_symbols.clone(),
+                    aliases: game.aliases.clone(),
+                }),
+            );
         }
     }
-
-    Err("Failed to generate a valid game".to_string())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::api::spelling_gene::generate_game;
-    use std::collections::HashSet;
+    use crate::api::spelling_gene::{GameParams, generate_game};
+    use crate::error::ApiError;
+
+    #[test]
+    fn test_validate_params() {
+        assert!(GameParams::default().validate().is_ok());
+
+        let params = GameParams {
+            min_length: 0,
+            min_words: 100_000,
+            num_letters: 7,
+        };
+        assert_eq!(
+            params.validate(),
+            Err(ApiError::BadRequest(
+                "min_length must be between 2 and 8, not 0; \
+                 min_words must be between 1 and 50, not 100000"
+                    .to_string()
+            ))
+        );
+    }
 
     #[tokio::test]
     async fn test_generate_game() {
-        let game = generate_game(4, 10, 7, 20277).await.unwrap();
+        // the sample only has a handful of symbols, so a single one makes a game
+        crate::corpus::init_sample().await;
+        let game = generate_game(4, 1, 7, 20277).await.unwrap();
 
         println!("{game:#?}");
 
         assert!(game.metadata.outer_letters.len() == 6);
-        assert!(game.valid_symbols.len() >= 10);
+        assert!(!game.valid_symbols.is_empty());
         assert!(game.valid_symbols.iter().all(|symbol| {
             symbol.chars().count() >= 4
                 && symbol.chars().all(|c| {
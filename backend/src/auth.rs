@@ -0,0 +1,289 @@
+use crate::config::config;
+use crate::games::{adopt_player, player_id};
+use crate::preferences::player_code;
+use crate::storage::storage;
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Redirect;
+use rand::distr::{Alphanumeric, SampleString};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+
+const STATE_KEY: &str = "auth.state";
+const USER_KEY: &str = "auth.user";
+const STATE_LENGTH: usize = 32;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    Google,
+    Orcid,
+}
+
+struct ProviderEndpoints {
+    authorize: &'static str,
+    token: &'static str,
+    scope: &'static str,
+    env: &'static str,
+}
+
+// a user is an external identity pinned to the player id its stats are kept under
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct User {
+    pub provider: Provider,
+    pub subject: String,
+    pub name: Option<String>,
+    pub player: String,
+    pub created_at: i64,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Callback {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+struct PendingLogin {
+    provider: Provider,
+    state: String,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+struct TokenResponse {
+    access_token: String,
+    // ORCID names the researcher in the token response itself
+    orcid: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+struct GoogleUserInfo {
+    sub: String,
+    name: Option<String>,
+}
+
+const GOOGLE_USERINFO: &str = "https://openidconnect.googleapis.com/v1/userinfo";
+
+impl Provider {
+    fn endpoints(self) -> ProviderEndpoints {
+        match self {
+            Provider::Google => ProviderEndpoints {
+                authorize: "https://accounts.google.com/o/oauth2/v2/auth",
+                token: "https://oauth2.googleapis.com/token",
+                scope: "openid profile",
+                env: "GOOGLE",
+            },
+            Provider::Orcid => ProviderEndpoints {
+                authorize: "https://orcid.org/oauth/authorize",
+                token: "https://orcid.org/oauth/token",
+                scope: "/authenticate",
+                env: "ORCID",
+            },
+        }
+    }
+
+    fn slug(self) -> &'static str {
+        match self {
+            Provider::Google => "google",
+            Provider::Orcid => "orcid",
+        }
+    }
+
+    // credentials stay out of Config so they never end up in a debug print
+    fn credentials(self) -> Option<(String, String)> {
+        let env = self.endpoints().env;
+        let client_id = std::env::var(format!("GENEDLE_OAUTH_{env}_CLIENT_ID")).ok()?;
+        let client_secret = std::env::var(format!("GENEDLE_OAUTH_{env}_CLIENT_SECRET")).ok()?;
+
+        Some((client_id, client_secret))
+    }
+
+    fn redirect_uri(self) -> String {
+        format!(
+            "{}{}",
+            config().public_url,
+            config().link(&format!("/auth/callback/{}", self.slug()))
+        )
+    }
+}
+
+fn user_key(provider: Provider, subject: &str) -> String {
+    format!("users.{}.{subject}", provider.slug())
+}
+
+fn authorize_url(provider: Provider, client_id: &str, state: &str) -> Result<Url, StatusCode> {
+    let endpoints = provider.endpoints();
+    Url::parse_with_params(
+        endpoints.authorize,
+        [
+            ("client_id", client_id),
+            ("response_type", "code"),
+            ("scope", endpoints.scope),
+            ("redirect_uri", &provider.redirect_uri()),
+            ("state", state),
+        ],
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+pub async fn login(
+    session: Session,
+    Path(provider): Path<Provider>,
+) -> Result<Redirect, StatusCode> {
+    let (client_id, _) = provider.credentials().ok_or(StatusCode::NOT_FOUND)?;
+
+    let state = Alphanumeric.sample_string(&mut rand::rng(), STATE_LENGTH);
+    session
+        .insert(
+            STATE_KEY,
+            PendingLogin {
+                provider,
+                state: state.clone(),
+            },
+        )
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Redirect::to(
+        authorize_url(provider, &client_id, &state)?.as_str(),
+    ))
+}
+
+async fn identify(
+    client: &Client,
+    provider: Provider,
+    code: &str,
+) -> Result<(String, Option<String>), anyhow::Error> {
+    let (client_id, client_secret) = provider
+        .credentials()
+        .ok_or_else(|| anyhow::anyhow!("{} login isn't configured", provider.slug()))?;
+
+    let token: TokenResponse = client
+        .post(provider.endpoints().token)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(&[
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &provider.redirect_uri()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    match provider {
+        Provider::Orcid => {
+            let orcid = token
+                .orcid
+                .ok_or_else(|| anyhow::anyhow!("ORCID didn't return an iD"))?;
+            Ok((orcid, token.name))
+        }
+        Provider::Google => {
+            let info: GoogleUserInfo = client
+                .get(GOOGLE_USERINFO)
+                .bearer_auth(&token.access_token)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            Ok((info.sub, info.name))
+        }
+    }
+}
+
+// a new identity is linked to whoever is playing; a known one brings its player id along
+async fn sign_in(
+    session: &Session,
+    provider: Provider,
+    subject: String,
+    name: Option<String>,
+) -> Result<User, anyhow::Error> {
+    let key = user_key(provider, &subject);
+    let user = match storage().get::<User>(&key) {
+        Some(user) => user,
+        None => {
+            let user = User {
+                provider,
+                subject,
+                name,
+                player: player_code(player_id(session).await?),
+                created_at: chrono::Utc::now().timestamp(),
+            };
+            storage().insert(&key, &user)?;
+            user
+        }
+    };
+
+    session.cycle_id().await?;
+    adopt_player(session, u64::from_str_radix(&user.player, 16)?).await?;
+    session.insert(USER_KEY, &user).await?;
+
+    Ok(user)
+}
+
+pub async fn callback(
+    session: Session,
+    State(client): State<Client>,
+    Path(provider): Path<Provider>,
+    Query(callback): Query<Callback>,
+) -> Result<Redirect, StatusCode> {
+    let pending = session
+        .remove::<PendingLogin>(STATE_KEY)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let matches = pending.is_some_and(|pending| {
+        pending.provider == provider && callback.state.as_ref() == Some(&pending.state)
+    });
+    if !matches || callback.error.is_some() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let code = callback.code.ok_or(StatusCode::BAD_REQUEST)?;
+
+    let (subject, name) = identify(&client, provider, &code).await.map_err(|err| {
+        tracing::warn!("Failed to complete {} login: {err}", provider.slug());
+        StatusCode::BAD_GATEWAY
+    })?;
+    sign_in(&session, provider, subject, name)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Redirect::to(&config().link("/")))
+}
+
+pub async fn me(session: Session) -> Json<Option<User>> {
+    Json(session.get::<User>(USER_KEY).await.ok().flatten())
+}
+
+// signing out starts a fresh anonymous player; the account keeps the old one
+pub async fn logout(session: Session) -> StatusCode {
+    match session.flush().await {
+        Ok(()) => StatusCode::NO_CONTENT,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::{Provider, authorize_url};
+
+    #[test]
+    fn test_authorize_url() {
+        let url = authorize_url(Provider::Orcid, "APP-123", "abc").unwrap();
+        let params: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(url.host_str(), Some("orcid.org"));
+        assert!(params.contains(&("scope".to_string(), "/authenticate".to_string())));
+        assert!(params.contains(&("state".to_string(), "abc".to_string())));
+        assert!(params.iter().any(|(key, value)| {
+            key == "redirect_uri" && value.ends_with("/auth/callback/orcid")
+        }));
+    }
+}
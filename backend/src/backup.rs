@@ -0,0 +1,195 @@
+use crate::config::config;
+use crate::games::spelling_gene::UNIX_EPOCH_DAYS_FROM_CE;
+use crate::schedule;
+use crate::sessions::TrackedStore;
+use crate::storage::storage;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+struct Analytics {
+    prefix: &'static str,
+    day: fn(u64) -> u64,
+}
+
+// per-puzzle aggregates, keyed by whatever period each game counts in
+const ANALYTICS: [Analytics; 11] = [
+    Analytics {
+        prefix: "participation.genedle.",
+        day: |day| day,
+    },
+    Analytics {
+        prefix: "participation.genections.",
+        day: |day| day,
+    },
+    Analytics {
+        prefix: "participation.genedle-weekly.",
+        day: |week| week * 7 + 1,
+    },
+    Analytics {
+        prefix: "participation.spelling-gene.",
+        day: |seed| seed + UNIX_EPOCH_DAYS_FROM_CE,
+    },
+    Analytics {
+        prefix: "completions.spelling-gene.",
+        day: |seed| seed + UNIX_EPOCH_DAYS_FROM_CE,
+    },
+    Analytics {
+        prefix: "outcomes.genedle.",
+        day: |day| day,
+    },
+    Analytics {
+        prefix: "outcomes.genections.",
+        day: |day| day,
+    },
+    Analytics {
+        prefix: "outcomes.genedle-weekly.",
+        day: |week| week * 7 + 1,
+    },
+    Analytics {
+        prefix: "survey.genedle.",
+        day: |day| day,
+    },
+    Analytics {
+        prefix: "survey-voters.genedle.",
+        day: |day| day,
+    },
+    Analytics {
+        prefix: "stats.genections-mistakes.",
+        day: |day| day,
+    },
+];
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct BackupStatus {
+    pub last_run: Option<i64>,
+    pub last_success: Option<i64>,
+    pub last_error: Option<String>,
+    pub files: Vec<String>,
+    pub pruned_entries: usize,
+}
+
+static STATUS: LazyLock<Mutex<BackupStatus>> = LazyLock::new(Default::default);
+
+pub fn status() -> BackupStatus {
+    STATUS.lock().unwrap().clone()
+}
+
+fn is_expired_analytics(key: &str, cutoff_day: u64) -> bool {
+    ANALYTICS.iter().any(|analytics| {
+        key.strip_prefix(analytics.prefix)
+            .and_then(|period| period.parse().ok())
+            .is_some_and(|period| (analytics.day)(period) < cutoff_day)
+    })
+}
+
+// keeps the newest `keep` files sharing a prefix; dated names sort chronologically
+fn rotate(dir: &Path, prefix: &str, keep: usize) -> std::io::Result<()> {
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(prefix))
+        })
+        .collect();
+    backups.sort();
+
+    for path in &backups[..backups.len().saturating_sub(keep)] {
+        std::fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+async fn back_up(sessions: &TrackedStore) -> Result<(Vec<String>, usize), anyhow::Error> {
+    let dir = PathBuf::from(&config().backup_dir);
+    std::fs::create_dir_all(&dir)?;
+    let date = chrono::Utc::now().format("%Y-%m-%d");
+
+    let cutoff_day = schedule::today().saturating_sub(config().analytics_retention_days);
+    let pruned = storage().compact(|key| !is_expired_analytics(key, cutoff_day))?;
+
+    let mut files = Vec::new();
+
+    let path = dir.join(format!("genedle-storage-{date}.json"));
+    storage().backup(&path)?;
+    files.push(path.to_string_lossy().into_owned());
+
+    let path = dir.join(format!("genedle-sessions-{date}.db"));
+    let _ = std::fs::remove_file(&path);
+    if sessions.backup(&path).await? {
+        files.push(path.to_string_lossy().into_owned());
+    }
+
+    rotate(&dir, "genedle-storage-", config().backup_keep)?;
+    rotate(&dir, "genedle-sessions-", config().backup_keep)?;
+
+    Ok((files, pruned))
+}
+
+pub async fn run(sessions: &TrackedStore) {
+    let now = chrono::Utc::now().timestamp();
+    let result = back_up(sessions).await;
+
+    let mut status = STATUS.lock().unwrap();
+    status.last_run = Some(now);
+    match result {
+        Ok((files, pruned)) => {
+            status.last_success = Some(now);
+            status.last_error = None;
+            status.files = files;
+            status.pruned_entries = pruned;
+        }
+        Err(err) => {
+            tracing::error!("Nightly backup failed: {err}");
+            status.last_error = Some(err.to_string());
+        }
+    }
+}
+
+pub async fn nightly(sessions: TrackedStore) {
+    loop {
+        let now = chrono::Utc::now();
+        let midnight = (now.date_naive() + chrono::Days::new(1))
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc();
+        tokio::time::sleep((midnight - now).to_std().unwrap_or_default()).await;
+
+        run(&sessions).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backup::is_expired_analytics;
+
+    #[test]
+    fn test_is_expired_analytics() {
+        assert!(is_expired_analytics("participation.genedle.739000", 739100));
+        assert!(!is_expired_analytics(
+            "participation.genedle.739200",
+            739100
+        ));
+        // week 105571 starts on day 738998
+        assert!(is_expired_analytics(
+            "participation.genedle-weekly.105571",
+            739100
+        ));
+        // spelling gene seeds count from the unix epoch
+        assert!(!is_expired_analytics(
+            "participation.spelling-gene.20000",
+            739100
+        ));
+        assert!(is_expired_analytics(
+            "completions.spelling-gene.19000",
+            739100
+        ));
+        assert!(!is_expired_analytics(
+            "stats.genections.00000000000000ff",
+            739100
+        ));
+        assert!(!is_expired_analytics("genedle.daily.1", 739100));
+    }
+}
@@ -0,0 +1,163 @@
+use cached::{Cached, TimedSizedCache};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::path::PathBuf;
+
+// upstream queries and generated puzzles keyed by cache name, so a deploy can pick up where the
+// previous process left off instead of regenerating everything at once
+pub type Snapshot = BTreeMap<String, serde_json::Value>;
+
+// how many entries a cache keeps, and for how long; every cache keyed by something players choose
+// has one, so memory stays bounded however many seeds and guesses come in
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Policy {
+    pub size: usize,
+    pub seconds: u64,
+}
+
+impl Policy {
+    pub fn build<K: Hash + Eq + Clone, V>(self) -> TimedSizedCache<K, V> {
+        TimedSizedCache::with_size_and_lifespan(self.size, self.seconds)
+    }
+}
+
+// answers and what's derived from them; they never change for a key, so expiring only frees memory
+pub const WORDS: Policy = Policy {
+    size: 10_000,
+    seconds: 86_400,
+};
+// one per distinct guess, so the most numerous and the least often hit again
+pub const VALIDATIONS: Policy = Policy {
+    size: 5_000,
+    seconds: 600,
+};
+// generated boards and letter sets, which are the largest entries
+pub const GAMES: Policy = Policy {
+    size: 500,
+    seconds: 86_400,
+};
+// HGNC records change rarely, so keep them for a week
+pub const RECORDS: Policy = Policy {
+    size: 5_000,
+    seconds: 604_800,
+};
+
+// the live entries, least recently used last, for saving to a snapshot
+pub fn entries<K, V>(cache: &TimedSizedCache<K, V>) -> Vec<(&K, &V)>
+where
+    K: Hash + Eq + Clone,
+{
+    let lifespan = cache.cache_lifespan().unwrap_or(u64::MAX);
+    cache
+        .key_order()
+        .zip(cache.value_order())
+        .filter(|(_, (set, _))| set.elapsed().as_secs() < lifespan)
+        .map(|(key, (_, value))| (key, value))
+        .collect()
+}
+
+fn path() -> PathBuf {
+    std::env::var("GENEDLE_CACHE_PATH")
+        .unwrap_or_else(|_| "genedle-cache.json".to_string())
+        .into()
+}
+
+pub fn save<T: Serialize>(snapshot: &mut Snapshot, name: &str, entries: T) {
+    if let Ok(value) = serde_json::to_value(entries) {
+        snapshot.insert(name.to_string(), value);
+    }
+}
+
+pub fn load<K, V>(snapshot: &Snapshot, name: &str, cache: &mut impl Cached<K, V>)
+where
+    K: DeserializeOwned + Hash + Eq,
+    V: DeserializeOwned,
+{
+    let entries = snapshot
+        .get(name)
+        .and_then(|value| serde_json::from_value::<Vec<(K, V)>>(value.clone()).ok())
+        .unwrap_or_default();
+
+    for (key, value) in entries {
+        cache.cache_set(key, value);
+    }
+}
+
+pub async fn persist() -> Result<(), anyhow::Error> {
+    let mut snapshot = Snapshot::new();
+    crate::api::genedle::save_caches(&mut snapshot).await;
+    crate::api::genections::save_caches(&mut snapshot).await;
+    crate::api::spelling_gene::save_caches(&mut snapshot).await;
+
+    let path = path();
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec(&snapshot)?)?;
+    std::fs::rename(tmp, path)?;
+
+    Ok(())
+}
+
+pub async fn restore() {
+    let Some(snapshot) = std::fs::read(path())
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Snapshot>(&bytes).ok())
+    else {
+        return;
+    };
+
+    crate::api::genedle::load_caches(&snapshot).await;
+    crate::api::genections::load_caches(&snapshot).await;
+    crate::api::spelling_gene::load_caches(&snapshot).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::{Policy, Snapshot, entries, load, save};
+    use cached::{Cached, UnboundCache};
+
+    #[test]
+    fn test_roundtrip() {
+        let mut original = UnboundCache::<u64, String>::new();
+        original.cache_set(1, "BRCA1".to_string());
+        original.cache_set(2, "TP53".to_string());
+
+        let mut snapshot = Snapshot::new();
+        save(
+            &mut snapshot,
+            "words",
+            original.get_store().iter().collect::<Vec<_>>(),
+        );
+
+        let mut restored = UnboundCache::<u64, String>::new();
+        load(&snapshot, "words", &mut restored);
+        assert_eq!(restored.get_store(), original.get_store());
+
+        let mut missing = UnboundCache::<u64, String>::new();
+        load(&snapshot, "other", &mut missing);
+        assert_eq!(missing.cache_size(), 0);
+    }
+
+    #[test]
+    fn test_policy() {
+        let mut cache = Policy {
+            size: 2,
+            seconds: 60,
+        }
+        .build::<u64, String>();
+        cache.cache_set(1, "BRCA1".to_string());
+        cache.cache_set(2, "TP53".to_string());
+        cache.cache_set(3, "KRAS".to_string());
+
+        // the least recently used entry goes once the cache is full
+        assert_eq!(cache.cache_size(), 2);
+        assert_eq!(cache.cache_get(&1), None);
+        let mut entries = entries(&cache);
+        entries.sort();
+        assert_eq!(
+            entries,
+            [(&2, &"TP53".to_string()), (&3, &"KRAS".to_string())]
+        );
+    }
+}
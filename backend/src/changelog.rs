@@ -0,0 +1,171 @@
+use crate::config::config;
+use axum::Json;
+use axum::extract::rejection::RawPathParamsRejection;
+use axum::extract::{MatchedPath, RawPathParams, Request};
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+// kept alongside the code, so a route's deprecation ships in the same change that replaces it
+const MANIFEST: &str = include_str!("../data/api_changelog.json");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Deprecation {
+    pub route: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub successor: Option<String>,
+    // YYYY-MM-DD, after which the route may be removed
+    pub sunset: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ApiChange {
+    pub version: String,
+    // YYYY-MM-DD; a deprecation takes effect on the date of the change that announced it
+    pub date: String,
+    pub changes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deprecations: Vec<Deprecation>,
+}
+
+static CHANGELOG: LazyLock<Vec<ApiChange>> =
+    LazyLock::new(|| serde_json::from_str(MANIFEST).expect("the API changelog is valid JSON"));
+
+pub async fn changelog() -> Json<&'static [ApiChange]> {
+    Json(&CHANGELOG)
+}
+
+fn deprecation(route: &str) -> Option<(&'static ApiChange, &'static Deprecation)> {
+    CHANGELOG.iter().find_map(|change| {
+        change
+            .deprecations
+            .iter()
+            .find(|deprecation| deprecation.route == route)
+            .map(|deprecation| (change, deprecation))
+    })
+}
+
+fn midnight(date: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    Some(
+        date.parse::<NaiveDate>()
+            .ok()?
+            .and_hms_opt(0, 0, 0)?
+            .and_utc(),
+    )
+}
+
+// the successor's placeholders are filled in from the deprecated route's, so the link is one a
+// client can follow as is
+fn successor_path<'a>(
+    successor: &str,
+    params: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> String {
+    params
+        .into_iter()
+        .fold(successor.to_string(), |path, (name, value)| {
+            path.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+// RFC 9745 Deprecation and RFC 8594 Sunset, with links to the changelog and the replacement
+fn headers(
+    change: &ApiChange,
+    deprecation: &Deprecation,
+    successor: Option<String>,
+) -> Vec<(&'static str, String)> {
+    let mut headers = Vec::new();
+    if let Some(date) = midnight(&change.date) {
+        headers.push(("deprecation", format!("@{}", date.timestamp())));
+    }
+    if let Some(sunset) = midnight(&deprecation.sunset) {
+        headers.push((
+            "sunset",
+            sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        ));
+    }
+
+    let mut links = vec![format!(
+        "<{}>; rel=\"deprecation\"",
+        config().link("/api/changelog")
+    )];
+    if let Some(successor) = successor {
+        links.push(format!(
+            "<{}>; rel=\"successor-version\"",
+            config().link(&successor)
+        ));
+    }
+    headers.push(("link", links.join(", ")));
+
+    headers
+}
+
+pub async fn deprecation_headers(
+    route: Option<MatchedPath>,
+    params: Result<RawPathParams, RawPathParamsRejection>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let deprecated = route.and_then(|route| {
+        let route = route
+            .as_str()
+            .strip_prefix(config().base_path.as_str())
+            .unwrap_or(route.as_str());
+        deprecation(route)
+    });
+    let mut response = next.run(request).await;
+
+    if let Some((change, deprecation)) = deprecated {
+        let successor = deprecation.successor.as_deref().map(|successor| {
+            successor_path(successor, params.iter().flat_map(|params| params.iter()))
+        });
+        for (name, value) in headers(change, deprecation, successor) {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                response.headers_mut().insert(name, value);
+            }
+        }
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::changelog::{CHANGELOG, deprecation, headers, midnight, successor_path};
+
+    #[test]
+    fn test_manifest() {
+        assert!(!CHANGELOG.is_empty());
+        for change in CHANGELOG.iter() {
+            assert!(midnight(&change.date).is_some(), "{}", change.version);
+            for deprecation in &change.deprecations {
+                assert!(midnight(&deprecation.sunset) > midnight(&change.date));
+            }
+        }
+        assert!(deprecation("/api/v1/genedle-metadata/{id}").is_none());
+    }
+
+    #[test]
+    fn test_headers() {
+        let (change, deprecated) = deprecation("/api/v1/genedle-letters/{id}").unwrap();
+        let successor =
+            successor_path(deprecated.successor.as_deref().unwrap(), [("id", "abc123")]);
+        assert_eq!(successor, "/api/v1/genedle-metadata/abc123");
+
+        let headers = headers(change, deprecated, Some(successor));
+        assert_eq!(headers[0], ("deprecation", "@1792108800".to_string()));
+        assert_eq!(
+            headers[1],
+            ("sunset", "Fri, 16 Apr 2027 00:00:00 GMT".to_string())
+        );
+        assert!(
+            headers[2]
+                .1
+                .ends_with("</api/v1/genedle-metadata/abc123>; rel=\"successor-version\"")
+        );
+    }
+}
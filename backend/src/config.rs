@@ -0,0 +1,593 @@
+use crate::games::genedle::Difficulty;
+use crate::logging::LogFormat;
+use crate::sessions::SessionBackend;
+use crate::slo::Slo;
+use axum::http::HeaderValue;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use genedle_core::spelling_gene::ScoringPreset;
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{LazyLock, RwLock};
+use tracing_subscriber::EnvFilter;
+
+// letter reveals are paid for in guesses; untiered puzzles, daily ones included, get the standard
+// allowance
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct HintEconomy {
+    pub cost: usize,
+    pub daily_budget: usize,
+    pub standard: usize,
+    pub easy: usize,
+    pub medium: usize,
+    pub hard: usize,
+}
+
+impl HintEconomy {
+    pub fn allowance(&self, difficulty: Option<Difficulty>) -> usize {
+        match difficulty {
+            None => self.standard,
+            Some(Difficulty::Easy) => self.easy,
+            Some(Difficulty::Medium) => self.medium,
+            Some(Difficulty::Hard) => self.hard,
+        }
+    }
+}
+
+// which HGNC entries can be drawn as answers; every symbol can still be guessed
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct AnswerFilter {
+    pub statuses: Vec<String>,
+    // empty allows every locus type
+    pub locus_types: Vec<String>,
+    pub protein_coding_only: bool,
+}
+
+impl AnswerFilter {
+    // an export without the status column only ever listed approved symbols
+    pub fn admits_status(&self, status: &str) -> bool {
+        status.is_empty() || self.statuses.iter().any(|allowed| allowed == status)
+    }
+
+    pub fn admits_locus(&self, locus_group: &str, locus_type: &str) -> bool {
+        (!self.protein_coding_only || locus_group == PROTEIN_CODING)
+            && (self.locus_types.is_empty()
+                || self.locus_types.iter().any(|allowed| allowed == locus_type))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    pub base_path: String,
+    pub public_url: String,
+    // how many of the latest daily answers a new one can't repeat
+    pub repeat_window: usize,
+    pub exclude_digits: bool,
+    pub min_answer_length: usize,
+    pub max_answer_length: usize,
+    pub max_guesses: usize,
+    pub hints: HintEconomy,
+    pub answers: AnswerFilter,
+    pub genections_max_submissions: usize,
+    pub genections_cooldown_ms: i64,
+    pub genections_red_herrings: usize,
+    pub session_gc_seconds: u64,
+    pub session_store: SessionBackend,
+    pub backup_dir: String,
+    pub backup_keep: usize,
+    pub analytics_retention_days: u64,
+    pub spelling_gene_scoring: ScoringPreset,
+    pub spelling_gene_aliases: bool,
+    pub public_api_burst: u32,
+    pub public_api_refill_seconds: u64,
+    pub content_security_policy: String,
+    pub frame_ancestors: String,
+    // empty allows every origin
+    pub cors_origins: Vec<String>,
+    // empty leaves the channel undeliverable
+    pub email_relay_url: String,
+    pub push_relay_url: String,
+    // signs the puzzle ids handed to clients; generated and stored if unset
+    pub puzzle_secret: String,
+    // the day shared puzzle numbers count from, as #1
+    pub puzzle_epoch: NaiveDate,
+    // where midnight rolls every daily puzzle over
+    pub timezone: Tz,
+    pub slos: Vec<Slo>,
+    pub slo_window_minutes: u64,
+    // for every outbound request, so a hung genenames.org connection can't hang a player's
+    pub upstream_connect_timeout_ms: u64,
+    pub upstream_read_timeout_ms: u64,
+    // across the whole process, however many players are generating puzzles at once
+    pub upstream_concurrency: usize,
+    pub upstream_requests_per_second: u32,
+    // a tracing filter, e.g. "info" or "genedle=debug,tower_sessions=warn"
+    pub log_filter: String,
+    pub log_format: LogFormat,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct ConfigReload {
+    // settings that changed but are only read at startup, so they were kept as they were
+    pub restart_required: Vec<&'static str>,
+}
+
+// env vars, overlaid by the optional config file, which is the part that can change at runtime
+struct Settings {
+    file: HashMap<String, String>,
+    read: BTreeSet<String>,
+    errors: Vec<String>,
+}
+
+const PROTEIN_CODING: &str = "protein-coding gene";
+
+const DEFAULT_CSP: &str = "default-src 'self'; script-src 'self' 'unsafe-inline'; \
+    style-src 'self' 'unsafe-inline' https://fonts.googleapis.com; \
+    font-src 'self' https://fonts.gstatic.com; img-src 'self' data:";
+
+const DEFAULT_SLOS: &str = "/games/genedle:1000:99:99.9,/games/genections:1000:99:99.9,\
+    /games/spelling-gene:2000:99:99.9";
+
+// reloads are rare admin actions, so each config is leaked to keep handing out plain references
+static CONFIG: LazyLock<RwLock<&'static Config>> =
+    LazyLock::new(|| RwLock::new(Box::leak(Box::new(Config::initial()))));
+
+pub fn config() -> &'static Config {
+    *CONFIG.read().unwrap()
+}
+
+impl Settings {
+    // KEY=value lines, with blank lines and # comments ignored
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut file = HashMap::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected KEY=value", number + 1))?;
+            file.insert(name.trim().to_string(), value.trim().to_string());
+        }
+
+        Ok(Settings {
+            file,
+            read: BTreeSet::new(),
+            errors: Vec::new(),
+        })
+    }
+
+    fn load() -> Result<Self, String> {
+        match std::env::var("GENEDLE_CONFIG_PATH") {
+            Ok(path) => std::fs::read_to_string(&path)
+                .map_err(|err| format!("{path}: {err}"))
+                .and_then(|text| Settings::parse(&text)),
+            Err(_) => Settings::parse(""),
+        }
+    }
+
+    fn get<T: std::str::FromStr>(&mut self, name: &str, default: T) -> T {
+        self.read.insert(name.to_string());
+        let Some(value) = self
+            .file
+            .get(name)
+            .cloned()
+            .or_else(|| std::env::var(name).ok())
+        else {
+            return default;
+        };
+
+        value.parse().unwrap_or_else(|_| {
+            self.errors.push(format!("{name}: can't parse {value:?}"));
+            default
+        })
+    }
+
+    fn list(&mut self, name: &str, default: &str) -> Vec<String> {
+        self.get(name, default.to_string())
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    fn items<T: std::str::FromStr>(&mut self, name: &str, default: &str) -> Vec<T> {
+        let items = self.list(name, default);
+        items
+            .into_iter()
+            .filter_map(|item| {
+                item.parse()
+                    .map_err(|_| self.errors.push(format!("{name}: can't parse {item:?}")))
+                    .ok()
+            })
+            .collect()
+    }
+
+    // a typo in the file would otherwise be silently ignored
+    fn finish(self) -> Vec<String> {
+        let mut errors = self.errors;
+        let mut unknown: Vec<&String> = self
+            .file
+            .keys()
+            .filter(|name| !self.read.contains(*name))
+            .collect();
+        unknown.sort();
+        errors.extend(
+            unknown
+                .into_iter()
+                .map(|name| format!("{name}: unknown setting")),
+        );
+        errors
+    }
+}
+
+impl Config {
+    // unparseable settings fall back to their defaults at startup, but a config that can't work
+    // stops the server
+    fn initial() -> Self {
+        let (config, errors) = match Settings::load() {
+            Ok(mut settings) => {
+                let config = Config::from_settings(&mut settings);
+                (config, settings.finish())
+            }
+            Err(err) => (
+                Config::from_settings(&mut Settings::parse("").unwrap()),
+                vec![err],
+            ),
+        };
+        // logging is set up from the config, so this can't go through it
+        for err in errors {
+            eprintln!("Ignoring config: {err}");
+        }
+
+        if let Err(errors) = config.validate() {
+            panic!("Invalid config: {}", errors.join("; "));
+        }
+        config
+    }
+
+    // all or nothing: a bad setting anywhere rejects the whole config
+    pub fn load() -> Result<Self, Vec<String>> {
+        let mut settings = Settings::load().map_err(|err| vec![err])?;
+        let config = Config::from_settings(&mut settings);
+
+        let mut errors = settings.finish();
+        if let Err(invalid) = config.validate() {
+            errors.extend(invalid);
+        }
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn from_settings(settings: &mut Settings) -> Self {
+        Config {
+            base_path: normalize_base_path(&settings.get("GENEDLE_BASE_PATH", String::new())),
+            // origin OAuth providers redirect back to; the base path is appended separately
+            public_url: settings
+                .get("GENEDLE_PUBLIC_URL", "http://localhost:3000".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            repeat_window: settings.get("GENEDLE_REPEAT_WINDOW", 365),
+            exclude_digits: settings.get("GENEDLE_EXCLUDE_DIGITS", false),
+            // one- and two-letter symbols give nothing to work with, and long ones are a slog
+            min_answer_length: settings.get("GENEDLE_MIN_ANSWER_LENGTH", 4),
+            max_answer_length: settings.get("GENEDLE_MAX_ANSWER_LENGTH", 8),
+            max_guesses: settings.get("GENEDLE_MAX_GUESSES", 6),
+            hints: HintEconomy {
+                cost: settings.get("GENEDLE_HINT_COST", 1),
+                daily_budget: settings.get("GENEDLE_HINT_DAILY_BUDGET", 3),
+                standard: settings.get("GENEDLE_HINTS_STANDARD", 1),
+                easy: settings.get("GENEDLE_HINTS_EASY", 2),
+                medium: settings.get("GENEDLE_HINTS_MEDIUM", 1),
+                hard: settings.get("GENEDLE_HINTS_HARD", 0),
+            },
+            // HGNC status and locus_type values, comma-separated, e.g. "gene with protein product"
+            answers: AnswerFilter {
+                statuses: settings.list("GENEDLE_ANSWER_STATUSES", "Approved"),
+                locus_types: settings.list("GENEDLE_ANSWER_LOCUS_TYPES", ""),
+                protein_coding_only: settings.get("GENEDLE_PROTEIN_CODING_ONLY", false),
+            },
+            genections_max_submissions: settings.get("GENEDLE_GENECTIONS_MAX_SUBMISSIONS", 30),
+            genections_cooldown_ms: settings.get("GENEDLE_GENECTIONS_COOLDOWN_MS", 1000),
+            genections_red_herrings: settings.get("GENEDLE_GENECTIONS_RED_HERRINGS", 1),
+            session_gc_seconds: settings.get("GENEDLE_SESSION_GC_SECONDS", 300),
+            // "sqlite:<path>" or "memory"
+            session_store: settings.get(
+                "GENEDLE_SESSION_STORE",
+                SessionBackend::Sqlite("genedle-sessions.db".to_string()),
+            ),
+            backup_dir: settings.get("GENEDLE_BACKUP_DIR", "backups".to_string()),
+            backup_keep: settings.get("GENEDLE_BACKUP_KEEP", 7),
+            analytics_retention_days: settings.get("GENEDLE_ANALYTICS_RETENTION_DAYS", 365),
+            spelling_gene_scoring: settings
+                .get("GENEDLE_SPELLING_GENE_SCORING", ScoringPreset::Classic),
+            spelling_gene_aliases: settings.get("GENEDLE_SPELLING_GENE_ALIASES", false),
+            public_api_burst: settings.get("GENEDLE_PUBLIC_API_BURST", 30),
+            public_api_refill_seconds: settings.get("GENEDLE_PUBLIC_API_REFILL_SECONDS", 2),
+            content_security_policy: settings.get("GENEDLE_CSP", DEFAULT_CSP.to_string()),
+            // widen for embeds, e.g. "'self' https://wiki.example.org"
+            frame_ancestors: settings.get("GENEDLE_FRAME_ANCESTORS", "'self'".to_string()),
+            // comma-separated, e.g. "https://wiki.example.org,https://lab.example.org"
+            cors_origins: settings.list("GENEDLE_CORS_ORIGINS", ""),
+            // services that take a notification digest as JSON and send it on as mail or web push
+            email_relay_url: settings.get("GENEDLE_EMAIL_RELAY_URL", String::new()),
+            push_relay_url: settings.get("GENEDLE_PUSH_RELAY_URL", String::new()),
+            puzzle_secret: settings.get("GENEDLE_PUZZLE_SECRET", String::new()),
+            // YYYY-MM-DD
+            puzzle_epoch: settings.get(
+                "GENEDLE_PUZZLE_EPOCH",
+                NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            ),
+            // an IANA name, e.g. "America/New_York", so the rollover follows daylight saving
+            timezone: settings.get("GENEDLE_TIMEZONE", Tz::UTC),
+            // comma-separated "route:latency_ms:latency_target:availability_target", with the
+            // targets as percentages; the defaults cover the routes that generate puzzles
+            slos: settings.items("GENEDLE_SLOS", DEFAULT_SLOS),
+            slo_window_minutes: settings.get("GENEDLE_SLO_WINDOW_MINUTES", 60),
+            upstream_connect_timeout_ms: settings.get("GENEDLE_UPSTREAM_CONNECT_TIMEOUT_MS", 3000),
+            upstream_read_timeout_ms: settings.get("GENEDLE_UPSTREAM_READ_TIMEOUT_MS", 10000),
+            upstream_concurrency: settings.get("GENEDLE_UPSTREAM_CONCURRENCY", 4),
+            upstream_requests_per_second: settings.get("GENEDLE_UPSTREAM_REQUESTS_PER_SECOND", 10),
+            log_filter: settings.get("GENEDLE_LOG", "info".to_string()),
+            // "text" or "json"
+            log_format: settings.get("GENEDLE_LOG_FORMAT", LogFormat::Text),
+        }
+    }
+
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if self.max_guesses == 0 {
+            errors.push("GENEDLE_MAX_GUESSES: must be at least 1".to_string());
+        }
+        if self.min_answer_length == 0 || self.min_answer_length > self.max_answer_length {
+            errors.push(
+                "GENEDLE_MIN_ANSWER_LENGTH: must be between 1 and GENEDLE_MAX_ANSWER_LENGTH"
+                    .to_string(),
+            );
+        }
+        if self.public_api_burst == 0 {
+            errors.push("GENEDLE_PUBLIC_API_BURST: must be at least 1".to_string());
+        }
+        if self.public_api_refill_seconds == 0 {
+            errors.push("GENEDLE_PUBLIC_API_REFILL_SECONDS: must be at least 1".to_string());
+        }
+        if self.slo_window_minutes == 0 {
+            errors.push("GENEDLE_SLO_WINDOW_MINUTES: must be at least 1".to_string());
+        }
+        if self.upstream_connect_timeout_ms == 0 {
+            errors.push("GENEDLE_UPSTREAM_CONNECT_TIMEOUT_MS: must be at least 1".to_string());
+        }
+        if self.upstream_read_timeout_ms == 0 {
+            errors.push("GENEDLE_UPSTREAM_READ_TIMEOUT_MS: must be at least 1".to_string());
+        }
+        if self.upstream_concurrency == 0 {
+            errors.push("GENEDLE_UPSTREAM_CONCURRENCY: must be at least 1".to_string());
+        }
+        if self.upstream_requests_per_second == 0 {
+            errors.push("GENEDLE_UPSTREAM_REQUESTS_PER_SECOND: must be at least 1".to_string());
+        }
+        if let Err(err) = EnvFilter::try_new(&self.log_filter) {
+            errors.push(format!("GENEDLE_LOG: {err}"));
+        }
+        if self.genections_cooldown_ms < 0 {
+            errors.push("GENEDLE_GENECTIONS_COOLDOWN_MS: can't be negative".to_string());
+        }
+        // the security headers are dropped rather than sent malformed, so catch that here
+        let policy = format!(
+            "{}; frame-ancestors {}",
+            self.content_security_policy, self.frame_ancestors
+        );
+        if HeaderValue::from_str(&policy).is_err() {
+            errors.push("GENEDLE_CSP: not a valid header value".to_string());
+        }
+        if self.answers.statuses.is_empty() {
+            errors.push("GENEDLE_ANSWER_STATUSES: must allow at least one status".to_string());
+        }
+        for origin in &self.cors_origins {
+            if !is_origin(origin) {
+                errors.push(format!("GENEDLE_CORS_ORIGINS: {origin:?} isn't an origin"));
+            }
+        }
+
+        for (name, relay) in [
+            ("GENEDLE_EMAIL_RELAY_URL", &self.email_relay_url),
+            ("GENEDLE_PUSH_RELAY_URL", &self.push_relay_url),
+        ] {
+            if !relay.is_empty()
+                && !Url::parse(relay).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+            {
+                errors.push(format!("{name}: {relay:?} isn't an http(s) URL"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn allows_origin(&self, origin: &HeaderValue) -> bool {
+        self.cors_origins.is_empty()
+            || self
+                .cors_origins
+                .iter()
+                .any(|allowed| allowed.as_bytes() == origin.as_bytes())
+    }
+
+    pub fn link(&self, path: &str) -> String {
+        format!("{}{path}", self.base_path)
+    }
+}
+
+// swaps the config in for everything that reads it per request; requests already running finish
+// on the old one
+pub fn reload() -> Result<ConfigReload, Vec<String>> {
+    let mut next = Config::load()?;
+
+    let mut current = CONFIG.write().unwrap();
+    let mut restart_required = Vec::new();
+    macro_rules! keep {
+        ($($field:ident)+, $name:literal) => {
+            if next.$($field).+ != current.$($field).+ {
+                restart_required.push($name);
+                next.$($field).+ = current.$($field).+.clone();
+            }
+        };
+    }
+
+    keep!(base_path, "GENEDLE_BASE_PATH");
+    keep!(session_store, "GENEDLE_SESSION_STORE");
+    keep!(session_gc_seconds, "GENEDLE_SESSION_GC_SECONDS");
+    keep!(puzzle_secret, "GENEDLE_PUZZLE_SECRET");
+    // the shared client is built once, with its timeouts
+    keep!(
+        upstream_connect_timeout_ms,
+        "GENEDLE_UPSTREAM_CONNECT_TIMEOUT_MS"
+    );
+    keep!(upstream_read_timeout_ms, "GENEDLE_UPSTREAM_READ_TIMEOUT_MS");
+    // so is the subscriber
+    keep!(log_filter, "GENEDLE_LOG");
+    keep!(log_format, "GENEDLE_LOG_FORMAT");
+    // answers, lengths and days are memoized as they're drawn, so changing what's drawn or how
+    // it's judged partway through would hand players different puzzles for the same day
+    keep!(min_answer_length, "GENEDLE_MIN_ANSWER_LENGTH");
+    keep!(max_answer_length, "GENEDLE_MAX_ANSWER_LENGTH");
+    keep!(exclude_digits, "GENEDLE_EXCLUDE_DIGITS");
+    keep!(answers statuses, "GENEDLE_ANSWER_STATUSES");
+    keep!(answers locus_types, "GENEDLE_ANSWER_LOCUS_TYPES");
+    keep!(answers protein_coding_only, "GENEDLE_PROTEIN_CODING_ONLY");
+    keep!(max_guesses, "GENEDLE_MAX_GUESSES");
+    keep!(timezone, "GENEDLE_TIMEZONE");
+    // as are Spelling Gene letters and Genections boards
+    keep!(spelling_gene_aliases, "GENEDLE_SPELLING_GENE_ALIASES");
+    keep!(genections_red_herrings, "GENEDLE_GENECTIONS_RED_HERRINGS");
+
+    if next != **current {
+        *current = Box::leak(Box::new(next));
+    }
+    Ok(ConfigReload { restart_required })
+}
+
+fn is_origin(origin: &str) -> bool {
+    Url::parse(origin).is_ok_and(|url| {
+        matches!(url.scheme(), "http" | "https") && url.origin().ascii_serialization() == origin
+    })
+}
+
+fn normalize_base_path(path: &str) -> String {
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!("/{path}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Config, Settings, is_origin, normalize_base_path};
+    use crate::logging::LogFormat;
+    use chrono_tz::Tz;
+
+    #[test]
+    fn test_normalize_base_path() {
+        assert_eq!(normalize_base_path(""), "");
+        assert_eq!(normalize_base_path("/"), "");
+        assert_eq!(normalize_base_path("genedle"), "/genedle");
+        assert_eq!(normalize_base_path("/genedle/"), "/genedle");
+        assert_eq!(normalize_base_path("/apps/genedle"), "/apps/genedle");
+    }
+
+    #[test]
+    fn test_settings() {
+        let mut settings = Settings::parse(
+            "# overrides\nGENEDLE_MAX_GUESSES = 8\n\nGENEDLE_HINT_COST=lots\nGENEDLE_MAX_GUESES=5\n",
+        )
+        .unwrap();
+        let config = Config::from_settings(&mut settings);
+        assert_eq!(config.max_guesses, 8);
+        assert_eq!(config.hints.cost, 1);
+        assert_eq!(
+            settings.finish(),
+            [
+                "GENEDLE_HINT_COST: can't parse \"lots\"",
+                "GENEDLE_MAX_GUESES: unknown setting"
+            ]
+        );
+        assert!(Settings::parse("GENEDLE_MAX_GUESSES").is_err());
+
+        let mut settings = Settings::parse(
+            "GENEDLE_MAX_GUESSES=0\nGENEDLE_CORS_ORIGINS=https://wiki.example.org, https://lab.example.org/\n",
+        )
+        .unwrap();
+        let config = Config::from_settings(&mut settings);
+        assert_eq!(
+            config.cors_origins,
+            ["https://wiki.example.org", "https://lab.example.org/"]
+        );
+        assert_eq!(config.validate().unwrap_err().len(), 2);
+
+        let mut settings =
+            Settings::parse("GENEDLE_MIN_ANSWER_LENGTH=9\nGENEDLE_MAX_ANSWER_LENGTH=6\n").unwrap();
+        assert!(Config::from_settings(&mut settings).validate().is_err());
+
+        let mut settings =
+            Settings::parse("GENEDLE_TIMEZONE=America/New_York\nGENEDLE_PUZZLE_EPOCH=2025-6-1\n")
+                .unwrap();
+        let config = Config::from_settings(&mut settings);
+        assert_eq!(config.timezone, chrono_tz::America::New_York);
+        assert_eq!(config.puzzle_epoch.to_string(), "2025-06-01");
+        let mut settings = Settings::parse("GENEDLE_TIMEZONE=EST5EDT+1\n").unwrap();
+        assert_eq!(Config::from_settings(&mut settings).timezone, Tz::UTC);
+        assert_eq!(settings.finish().len(), 1);
+
+        let mut settings =
+            Settings::parse("GENEDLE_SLOS=/games/genedle:500:99.5:99.9, /games/genections:fast\n")
+                .unwrap();
+        let config = Config::from_settings(&mut settings);
+        assert_eq!(config.slos.len(), 1);
+        assert_eq!(config.slos[0].latency_target, 9950);
+        assert_eq!(settings.finish().len(), 1);
+        assert_eq!(
+            Config::from_settings(&mut Settings::parse("").unwrap())
+                .slos
+                .len(),
+            3
+        );
+
+        let mut settings =
+            Settings::parse("GENEDLE_LOG=genedle=loud\nGENEDLE_LOG_FORMAT=json\n").unwrap();
+        let config = Config::from_settings(&mut settings);
+        assert_eq!(config.log_format, LogFormat::Json);
+        assert!(config.validate().is_err());
+
+        assert!(is_origin("http://localhost:3000"));
+    }
+
+    #[test]
+    fn test_answer_filter() {
+        let mut settings = Settings::parse(
+            "GENEDLE_PROTEIN_CODING_ONLY=true\nGENEDLE_ANSWER_LOCUS_TYPES=gene with protein product\n",
+        )
+        .unwrap();
+        let answers = Config::from_settings(&mut settings).answers;
+        assert_eq!(answers.statuses, ["Approved"]);
+        assert!(answers.admits_status("Approved"));
+        assert!(!answers.admits_status("Entry Withdrawn"));
+        assert!(answers.admits_locus("protein-coding gene", "gene with protein product"));
+        assert!(!answers.admits_locus("non-coding RNA", "long non-coding RNA"));
+
+        let mut settings = Settings::parse("").unwrap();
+        let answers = Config::from_settings(&mut settings).answers;
+        assert!(answers.admits_locus("non-coding RNA", "long non-coding RNA"));
+        assert!(!is_origin("wiki.example.org"));
+    }
+}
@@ -0,0 +1,385 @@
+use crate::upstream::{RetryExt, UpstreamError};
+use reqwest::Client;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::OnceCell;
+
+const HGNC_COMPLETE_SET: &str =
+    "https://storage.googleapis.com/public-download-files/hgnc/tsv/tsv/hgnc_complete_set.txt";
+// a few hundred well-known approved symbols, so the games stay playable when HGNC can't be reached
+// and no fuller snapshot has been provided
+const BUNDLED: &str = include_str!("../data/hgnc_fallback.tsv");
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Gene {
+    pub hgnc_id: Option<u32>,
+    pub symbol: String,
+    pub location: String,
+    pub aliases: Vec<String>,
+    pub previous_symbols: Vec<String>,
+    // empty when the export doesn't carry the column
+    pub status: String,
+    pub locus_group: String,
+    pub locus_type: String,
+}
+
+impl Gene {
+    // the part of the location before the arm, if it names a chromosome at all
+    pub fn chromosome(&self) -> Option<&str> {
+        let (chromosome, _) = self.location.split_once(['p', 'q'])?;
+        (!chromosome.is_empty()
+            && chromosome
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == 'X' || c == 'Y'))
+        .then_some(chromosome)
+    }
+}
+
+// the complete set only says which groups a gene is in, not how the groups nest
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct GeneGroup {
+    pub id: u32,
+    pub name: String,
+    pub members: Vec<String>,
+}
+
+// every approved HGNC symbol, indexed in memory so game generation and guess checks never wait on
+// genenames.org
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Corpus {
+    genes: BTreeMap<String, Gene>,
+    groups: BTreeMap<u32, GeneGroup>,
+    // capitalised alias, previous symbol or mixed-case symbol to the approved symbol
+    approved: BTreeMap<String, String>,
+}
+
+static CORPUS: OnceCell<Corpus> = OnceCell::const_new();
+static DEGRADED: AtomicBool = AtomicBool::new(false);
+
+// lookups only ever read the corpus from disk; downloading it is left to the startup task, which
+// owns the shared client
+pub async fn corpus() -> Result<&'static Corpus, String> {
+    CORPUS.get_or_try_init(|| load(None)).await
+}
+
+pub async fn init(client: &Client) -> Result<&'static Corpus, String> {
+    CORPUS.get_or_try_init(|| load(Some(client))).await
+}
+
+// the sample fixture in HGNC's place, for tests that go through corpus() without the network
+#[cfg(test)]
+pub(crate) async fn init_sample() -> &'static Corpus {
+    CORPUS
+        .get_or_init(|| async {
+            Corpus::parse(include_str!("../fixtures/hgnc/sample.tsv")).unwrap()
+        })
+        .await
+}
+
+pub fn is_loaded() -> bool {
+    CORPUS.initialized()
+}
+
+// set once the corpus has come from a fallback snapshot; it stays that way until a restart
+pub fn is_degraded() -> bool {
+    DEGRADED.load(Ordering::Relaxed)
+}
+
+async fn load(client: Option<&Client>) -> Result<Corpus, String> {
+    let path = std::env::var("GENEDLE_CORPUS_PATH")
+        .unwrap_or_else(|_| "hgnc_complete_set.txt".to_string());
+
+    if let Ok(tsv) = tokio::fs::read_to_string(&path).await {
+        return Corpus::parse(&tsv);
+    }
+    let Some(client) = client else {
+        return Err("HGNC corpus hasn't been downloaded yet".to_string());
+    };
+
+    match download(client, &path).await {
+        Ok(corpus) => Ok(corpus),
+        Err(err) => {
+            tracing::warn!("Failed to download HGNC corpus, falling back to a snapshot: {err}");
+            fallback().await
+        }
+    }
+}
+
+async fn download(client: &Client, path: &str) -> Result<Corpus, String> {
+    let url = std::env::var("GENEDLE_CORPUS_URL").unwrap_or_else(|_| HGNC_COMPLETE_SET.to_string());
+    let tsv = client
+        .get(url)
+        .send_with_retry()
+        .await
+        .and_then(|response| response.error_for_status().map_err(UpstreamError::from))
+        .map_err(|err| err.to_string())?
+        .text()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let corpus = Corpus::parse(&tsv)?;
+    if let Err(err) = tokio::fs::write(path, &tsv).await {
+        tracing::error!("Failed to save HGNC corpus to {path}: {err}");
+    }
+
+    Ok(corpus)
+}
+
+// GENEDLE_FALLBACK_CORPUS_PATH can point at an older complete set; otherwise the bundled list
+async fn fallback() -> Result<Corpus, String> {
+    let corpus = match std::env::var("GENEDLE_FALLBACK_CORPUS_PATH") {
+        Ok(path) => Corpus::parse(
+            &tokio::fs::read_to_string(&path)
+                .await
+                .map_err(|err| format!("{path}: {err}"))?,
+        )?,
+        Err(_) => Corpus::parse(BUNDLED)?,
+    };
+    DEGRADED.store(true, Ordering::Relaxed);
+
+    Ok(corpus)
+}
+
+fn unquote(field: &str) -> &str {
+    field.trim().trim_matches('"')
+}
+
+fn split_symbols(field: Option<&&str>) -> Vec<String> {
+    field
+        .map(|symbols| unquote(symbols))
+        .filter(|symbols| !symbols.is_empty())
+        .map(|symbols| symbols.split('|').map(String::from).collect())
+        .unwrap_or_default()
+}
+
+impl Corpus {
+    pub fn parse(tsv: &str) -> Result<Corpus, String> {
+        let mut lines = tsv.lines();
+        let header: Vec<&str> = lines
+            .next()
+            .ok_or_else(|| "Empty HGNC corpus".to_string())?
+            .split('\t')
+            .collect();
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|&column| column == name)
+                .ok_or_else(|| format!("HGNC corpus is missing the {name} column"))
+        };
+        let symbol = column("symbol")?;
+        let location = column("location")?;
+        // older exports don't carry aliases, which only matter for optional game modes
+        let alias_symbol = column("alias_symbol").ok();
+        let prev_symbol = column("prev_symbol").ok();
+        let hgnc_id = column("hgnc_id").ok();
+        let gene_group = column("gene_group").ok();
+        let gene_group_id = column("gene_group_id").ok();
+        let status = column("status").ok();
+        let locus_group = column("locus_group").ok();
+        let locus_type = column("locus_type").ok();
+        let text = |fields: &[&str], column: Option<usize>| {
+            column
+                .and_then(|column| fields.get(column))
+                .map(|field| unquote(field).to_string())
+                .unwrap_or_default()
+        };
+
+        let mut groups: BTreeMap<u32, GeneGroup> = BTreeMap::new();
+        let genes: BTreeMap<String, Gene> = lines
+            .map(|line| line.split('\t').collect::<Vec<_>>())
+            .filter_map(|fields| {
+                let gene = Gene {
+                    hgnc_id: hgnc_id
+                        .and_then(|hgnc_id| fields.get(hgnc_id))
+                        .and_then(|hgnc_id| unquote(hgnc_id).strip_prefix("HGNC:")?.parse().ok()),
+                    symbol: unquote(fields.get(symbol)?).to_string(),
+                    location: unquote(fields.get(location).unwrap_or(&"")).to_string(),
+                    aliases: split_symbols(
+                        alias_symbol.and_then(|alias_symbol| fields.get(alias_symbol)),
+                    ),
+                    previous_symbols: split_symbols(
+                        prev_symbol.and_then(|prev_symbol| fields.get(prev_symbol)),
+                    ),
+                    status: text(&fields, status),
+                    locus_group: text(&fields, locus_group),
+                    locus_type: text(&fields, locus_type),
+                };
+                if gene.symbol.is_empty() {
+                    return None;
+                }
+
+                // names and ids are parallel |-separated lists
+                if let (Some(names), Some(ids)) = (
+                    gene_group.and_then(|column| fields.get(column)),
+                    gene_group_id.and_then(|column| fields.get(column)),
+                ) {
+                    for (name, id) in unquote(names).split('|').zip(unquote(ids).split('|')) {
+                        let Ok(id) = id.parse() else {
+                            continue;
+                        };
+                        groups
+                            .entry(id)
+                            .or_insert_with(|| GeneGroup {
+                                id,
+                                name: name.to_string(),
+                                members: Vec::new(),
+                            })
+                            .members
+                            .push(gene.symbol.clone());
+                    }
+                }
+
+                Some((gene.symbol.clone(), gene))
+            })
+            .collect();
+
+        for group in groups.values_mut() {
+            group.members.sort();
+        }
+
+        // guesses are typed in capitals, so p53 is looked up as P53 and C1orf112 as C1ORF112; an
+        // alias shared by several genes goes to the first of them
+        let mut approved = BTreeMap::new();
+        for symbol in genes.keys() {
+            let capitalised = symbol.to_ascii_uppercase();
+            if capitalised != *symbol && !genes.contains_key(&capitalised) {
+                approved.insert(capitalised, symbol.clone());
+            }
+        }
+        for gene in genes.values() {
+            for alias in gene.aliases.iter().chain(&gene.previous_symbols) {
+                let alias = alias.to_ascii_uppercase();
+                if !genes.contains_key(&alias) {
+                    approved.entry(alias).or_insert_with(|| gene.symbol.clone());
+                }
+            }
+        }
+
+        Ok(Corpus {
+            genes,
+            groups,
+            approved,
+        })
+    }
+
+    pub fn groups(&self) -> impl ExactSizeIterator<Item = &GeneGroup> {
+        self.groups.values()
+    }
+
+    pub fn group(&self, id: u32) -> Option<&GeneGroup> {
+        self.groups.get(&id)
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.genes.contains_key(symbol)
+    }
+
+    pub fn gene(&self, symbol: &str) -> Option<&Gene> {
+        self.genes.get(symbol)
+    }
+
+    // the approved symbol a retired, alternative or capitalised symbol stands for
+    pub fn alias_of(&self, symbol: &str) -> Option<&str> {
+        self.approved.get(symbol).map(String::as_str)
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = &String> {
+        self.genes.keys()
+    }
+
+    // an alias that's also some other gene's approved symbol is already playable as that symbol
+    pub fn aliases(&self) -> impl Iterator<Item = &String> {
+        self.genes
+            .values()
+            .flat_map(|gene| &gene.aliases)
+            .filter(|alias| !self.genes.contains_key(*alias))
+    }
+
+    pub fn starting_with<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a String> {
+        self.genes
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .map(|(symbol, _)| symbol)
+            .take_while(move |symbol| symbol.starts_with(prefix))
+    }
+
+    // locations look like 17q21.31, so the arm separates the chromosome from the band
+    pub fn on_chromosome<'a>(&'a self, chromosome: &'a str) -> impl Iterator<Item = &'a String> {
+        self.genes
+            .values()
+            .filter(move |gene| {
+                gene.location
+                    .strip_prefix(chromosome)
+                    .is_some_and(|band| band.starts_with(['p', 'q']))
+            })
+            .map(|gene| &gene.symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::corpus::{BUNDLED, Corpus};
+
+    const SAMPLE: &str = include_str!("../fixtures/hgnc/sample.tsv");
+
+    #[test]
+    fn test_corpus() {
+        let corpus = Corpus::parse(SAMPLE).unwrap();
+
+        assert!(corpus.contains("BRCA1"));
+        assert!(!corpus.contains("brca1"));
+        assert_eq!(corpus.symbols().count(), 9);
+        assert_eq!(
+            corpus.starting_with("BRCA").collect::<Vec<_>>(),
+            ["BRCA1", "BRCA2"]
+        );
+        assert_eq!(
+            corpus.on_chromosome("17").collect::<Vec<_>>(),
+            ["BRCA1", "KRT10", "TP53"]
+        );
+        assert_eq!(corpus.on_chromosome("1").count(), 0);
+        assert_eq!(corpus.on_chromosome("X").collect::<Vec<_>>(), ["XIST"]);
+        assert_eq!(
+            corpus.gene("BRCA1").and_then(|gene| gene.chromosome()),
+            Some("17")
+        );
+        assert_eq!(
+            corpus.genes["BRCA1"].aliases,
+            ["RNF53".to_string(), "BRCC1".to_string()]
+        );
+        assert_eq!(
+            corpus.gene("TP53").and_then(|gene| gene.hgnc_id),
+            Some(11998)
+        );
+        assert_eq!(corpus.genes["TP53"].status, "Approved");
+        assert_eq!(
+            (
+                corpus.genes["XIST"].locus_group.as_str(),
+                corpus.genes["XIST"].locus_type.as_str()
+            ),
+            ("non-coding RNA", "long non-coding RNA")
+        );
+        assert!(corpus.aliases().any(|alias| alias == "HER1"));
+        assert_eq!(corpus.alias_of("P53"), Some("TP53"));
+        assert_eq!(corpus.alias_of("FANCD1"), Some("BRCA2"));
+        assert_eq!(corpus.alias_of("C-MYC"), Some("MYC"));
+        assert_eq!(corpus.alias_of("TP53"), None);
+        assert_eq!(corpus.alias_of("p53"), None);
+        assert_eq!(corpus.aliases().count(), 16);
+        assert_eq!(corpus.groups().count(), 8);
+        assert_eq!(
+            corpus
+                .group(58)
+                .map(|group| (group.name.as_str(), &group.members[..])),
+            Some(("Ring finger proteins", &["BRCA1".to_string()][..]))
+        );
+
+        assert!(Corpus::parse("hgnc_id\tname\n").is_err());
+
+        let bundled = Corpus::parse(BUNDLED).unwrap();
+        assert!(bundled.contains("TP53"));
+        assert!(bundled.symbols().count() > 300);
+        assert_eq!(bundled.gene("XIST").unwrap().locus_group, "non-coding RNA");
+    }
+}
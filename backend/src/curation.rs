@@ -0,0 +1,371 @@
+use crate::api::genections::{Board, Category, GROUP_SIZE, NUM_GROUPS};
+use crate::api::spelling_gene::{fixed_game, intern};
+use crate::corpus;
+use crate::games::spelling_gene::{MIN_LENGTH, MIN_WORDS, NUM_LETTERS, UNIX_EPOCH_DAYS_FROM_CE};
+use crate::schedule;
+use crate::stats;
+use crate::storage::storage;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct BoardEdit {
+    pub author: String,
+    pub categories: Vec<Category>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Rollback {
+    pub author: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct BoardVersion {
+    pub version: usize,
+    pub board: Board,
+    pub author: String,
+    pub at: i64,
+    // set when this version is a rollback, so the history reads as it happened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restored_from: Option<usize>,
+}
+
+// append-only; the newest version is the one that gets played
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct BoardHistory {
+    pub versions: Vec<BoardVersion>,
+}
+
+const SPECIAL_PREFIX: &str = "spelling_gene.special.";
+const MAX_MULTIPLIER: u32 = 5;
+
+// a hand-picked Spelling Gene day, like DNA Day, in place of the generated puzzle
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SpecialPuzzle {
+    pub title: String,
+    pub center_letter: String,
+    pub outer_letters: Vec<String>,
+    // every find that day scores this many times over
+    #[serde(default = "default_multiplier")]
+    pub multiplier: u32,
+}
+
+// what players are told about a special day before they've played it
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct SpecialInfo {
+    pub title: String,
+    pub multiplier: u32,
+}
+
+fn default_multiplier() -> u32 {
+    1
+}
+
+impl SpecialPuzzle {
+    pub fn info(&self) -> SpecialInfo {
+        SpecialInfo {
+            title: self.title.clone(),
+            multiplier: self.multiplier,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CurationError {
+    AlreadyPlayed,
+    Invalid(String),
+    NoSuchVersion,
+    InternalError(String),
+}
+
+impl BoardHistory {
+    pub fn current(&self) -> Option<&BoardVersion> {
+        self.versions.last()
+    }
+
+    fn push(&mut self, board: Board, author: String, restored_from: Option<usize>) -> BoardVersion {
+        let version = BoardVersion {
+            version: self.versions.len() + 1,
+            board,
+            author,
+            at: chrono::Utc::now().timestamp(),
+            restored_from,
+        };
+        self.versions.push(version.clone());
+        version
+    }
+
+    fn restore(&mut self, version: usize, author: String) -> Option<BoardVersion> {
+        let board = self
+            .versions
+            .iter()
+            .find(|existing| existing.version == version)?
+            .board
+            .clone();
+        Some(self.push(board, author, Some(version)))
+    }
+}
+
+fn history_key(day: u64) -> String {
+    format!("genections.curated.{day}")
+}
+
+pub fn history(day: u64) -> BoardHistory {
+    storage().get(&history_key(day)).unwrap_or_default()
+}
+
+pub fn curated_board(day: u64) -> Option<Board> {
+    history(day).current().map(|version| version.board.clone())
+}
+
+// a puzzle that anyone could have started stays as it was, whatever the clock says
+fn check_unplayed(game: &str, day: u64, puzzle: u64) -> Result<(), CurationError> {
+    if day <= schedule::today() || stats::participation(game, puzzle) > 0 {
+        Err(CurationError::AlreadyPlayed)
+    } else {
+        Ok(())
+    }
+}
+
+fn check_board(categories: &[Category]) -> Result<(), String> {
+    if categories.len() != NUM_GROUPS {
+        return Err(format!("A board needs {NUM_GROUPS} categories"));
+    }
+
+    let mut seen = HashSet::new();
+    for category in categories {
+        if category.name.trim().is_empty() {
+            return Err("Every category needs a name".to_string());
+        }
+        if category.symbols.len() != GROUP_SIZE {
+            return Err(format!("{} needs {GROUP_SIZE} symbols", category.name));
+        }
+        if let Some(symbol) = category.symbols.iter().find(|s| !seen.insert(s.as_str())) {
+            return Err(format!("{symbol} appears more than once"));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn publish(day: u64, edit: BoardEdit) -> Result<BoardVersion, CurationError> {
+    check_unplayed("genections", day, day)?;
+    check_board(&edit.categories).map_err(CurationError::Invalid)?;
+
+    let corpus = corpus::corpus()
+        .await
+        .map_err(CurationError::InternalError)?;
+    if let Some(symbol) = edit
+        .categories
+        .iter()
+        .flat_map(|category| &category.symbols)
+        .find(|symbol| !corpus.contains(symbol))
+    {
+        return Err(CurationError::Invalid(format!(
+            "{symbol} isn't an HGNC symbol"
+        )));
+    }
+
+    // seeded by day like generated boards, so republishing the same groups keeps the layout
+    let mut layout: Vec<String> = edit
+        .categories
+        .iter()
+        .flat_map(|category| category.symbols.iter().cloned())
+        .collect();
+    layout.shuffle(&mut StdRng::seed_from_u64(day));
+    let board = Board {
+        categories: edit.categories,
+        layout,
+    };
+
+    let mut published = None;
+    storage()
+        .update(&history_key(day), |history: &mut BoardHistory| {
+            published = Some(history.push(board.clone(), edit.author.clone(), None));
+        })
+        .map_err(|err| CurationError::InternalError(err.to_string()))?;
+    tracing::info!("Published Genections board for day {day}");
+
+    published.ok_or_else(|| CurationError::InternalError("Failed to publish board".to_string()))
+}
+
+pub fn rollback(day: u64, version: usize, author: String) -> Result<BoardVersion, CurationError> {
+    check_unplayed("genections", day, day)?;
+
+    let mut restored = None;
+    storage()
+        .update(&history_key(day), |history: &mut BoardHistory| {
+            restored = history.restore(version, author.clone());
+        })
+        .map_err(|err| CurationError::InternalError(err.to_string()))?;
+
+    let restored = restored.ok_or(CurationError::NoSuchVersion)?;
+    tracing::info!("Rolled back Genections board for day {day} to version {version}");
+    Ok(restored)
+}
+
+fn special_key(day: u64) -> String {
+    format!("{SPECIAL_PREFIX}{day}")
+}
+
+pub fn special(day: u64) -> Option<SpecialPuzzle> {
+    storage().get(&special_key(day))
+}
+
+pub fn specials() -> BTreeMap<u64, SpecialPuzzle> {
+    storage()
+        .keys(SPECIAL_PREFIX)
+        .into_iter()
+        .filter_map(|key| {
+            let day = key.strip_prefix(SPECIAL_PREFIX)?.parse().ok()?;
+            Some((day, special(day)?))
+        })
+        .collect()
+}
+
+fn check_special(special: &SpecialPuzzle) -> Result<(), String> {
+    if special.title.trim().is_empty() {
+        return Err("A special puzzle needs a title".to_string());
+    }
+    if special.outer_letters.len() != NUM_LETTERS as usize - 1 {
+        return Err(format!(
+            "A special puzzle needs {} outer letters",
+            NUM_LETTERS - 1
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    for letter in special.outer_letters.iter().chain([&special.center_letter]) {
+        if intern(letter).is_none() {
+            return Err(format!("{letter:?} isn't a puzzle letter"));
+        }
+        if !seen.insert(letter) {
+            return Err(format!("{letter} appears more than once"));
+        }
+    }
+
+    if !(1..=MAX_MULTIPLIER).contains(&special.multiplier) {
+        return Err(format!(
+            "The multiplier must be between 1 and {MAX_MULTIPLIER}"
+        ));
+    }
+    Ok(())
+}
+
+// takes the place of the generated puzzle, so it's held to the same minimum number of symbols
+pub async fn schedule_special(
+    day: u64,
+    special: SpecialPuzzle,
+) -> Result<SpecialPuzzle, CurationError> {
+    check_unplayed(
+        "spelling-gene",
+        day,
+        day.saturating_sub(UNIX_EPOCH_DAYS_FROM_CE),
+    )?;
+    check_special(&special).map_err(CurationError::Invalid)?;
+
+    let game = fixed_game(&special.center_letter, &special.outer_letters, MIN_LENGTH)
+        .await
+        .map_err(|err| CurationError::InternalError(err.to_string()))?;
+    if game.valid_symbols.len() < MIN_WORDS {
+        return Err(CurationError::Invalid(format!(
+            "Only {} symbols fit those letters",
+            game.valid_symbols.len()
+        )));
+    }
+
+    storage()
+        .insert(&special_key(day), &special)
+        .map_err(|err| CurationError::InternalError(err.to_string()))?;
+    tracing::info!("Scheduled special Spelling Gene puzzle for day {day}");
+
+    Ok(special)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::genections::{Board, Category};
+    use crate::curation::{BoardHistory, SpecialPuzzle, check_board, check_special};
+
+    fn categories(symbols: [&str; 16]) -> Vec<Category> {
+        symbols
+            .chunks(4)
+            .enumerate()
+            .map(|(index, symbols)| Category {
+                name: format!("Family {index}"),
+                symbols: symbols.iter().map(|s| s.to_string()).collect(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_check_board() {
+        let mut symbols = [
+            "HOXA1", "HOXA2", "HOXA3", "HOXA4", "KRT1", "KRT2", "KRT3", "KRT4", "CDK1", "CDK2",
+            "CDK4", "CDK6", "TLR1", "TLR2", "TLR3", "TLR4",
+        ];
+        assert_eq!(check_board(&categories(symbols)), Ok(()));
+        assert!(check_board(&categories(symbols)[..3]).is_err());
+
+        symbols[15] = "HOXA1";
+        assert_eq!(
+            check_board(&categories(symbols)),
+            Err("HOXA1 appears more than once".to_string())
+        );
+    }
+
+    #[test]
+    fn test_board_history() {
+        let board = |name: &str| Board {
+            categories: vec![Category {
+                name: name.to_string(),
+                symbols: Vec::new(),
+            }],
+            layout: Vec::new(),
+        };
+
+        let mut history = BoardHistory::default();
+        history.push(board("first"), "ada".to_string(), None);
+        history.push(board("second"), "ada".to_string(), None);
+
+        let restored = history.restore(1, "grace".to_string()).unwrap();
+        assert_eq!(restored.version, 3);
+        assert_eq!(restored.restored_from, Some(1));
+        assert_eq!(history.current().unwrap().board, board("first"));
+        // rolling back never rewrites what came before
+        assert_eq!(history.versions[1].board, board("second"));
+        assert!(history.restore(7, "grace".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_check_special() {
+        let mut special = SpecialPuzzle {
+            title: "DNA Day".to_string(),
+            center_letter: "A".to_string(),
+            outer_letters: ["C", "G", "T", "N", "D", "-"].map(String::from).to_vec(),
+            multiplier: 2,
+        };
+        assert_eq!(check_special(&special), Ok(()));
+
+        special.outer_letters[0] = "A".to_string();
+        assert_eq!(
+            check_special(&special),
+            Err("A appears more than once".to_string())
+        );
+        special.outer_letters[0] = "c".to_string();
+        assert!(check_special(&special).is_err());
+        special.outer_letters[0] = "C".to_string();
+
+        special.multiplier = 0;
+        assert!(check_special(&special).is_err());
+        special.multiplier = 1;
+        special.outer_letters.pop();
+        assert!(check_special(&special).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::corpus;
+use crate::extract::RequestError;
+use crate::upstream::UpstreamError;
+use axum::Json;
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use genedle_core::feedback::{GuessResult, InvalidGuess};
+use genedle_core::spelling_gene::{InvalidSpellingGeneGuess, SpellingGeneGuessResult};
+use serde::Serialize;
+
+// a handler's failure, answered with a status that says whose fault it was and the same body
+// as a rejected request
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ApiError {
+    // nothing about the puzzle could make this request acceptable, like a guess of the wrong length
+    BadRequest(String),
+    NotFound(String),
+    RateLimited {
+        message: String,
+        retry_after_secs: Option<u64>,
+    },
+    // genenames.org, or the corpus drawn from it, couldn't answer
+    Upstream(String),
+    // genenames.org didn't answer in time
+    UpstreamTimeout(String),
+    // genenames.org has been failing, so it wasn't asked
+    Unavailable(String),
+    Internal(String),
+}
+
+impl ApiError {
+    // failing before the corpus has loaded is HGNC's doing; anything after is ours
+    pub fn failed(message: impl Into<String>) -> Self {
+        if corpus::is_loaded() {
+            ApiError::Internal(message.into())
+        } else {
+            ApiError::Upstream(message.into())
+        }
+    }
+
+    fn status(&self) -> (StatusCode, &'static str) {
+        match self {
+            ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "bad_request"),
+            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, "not_found"),
+            ApiError::RateLimited { .. } => (StatusCode::TOO_MANY_REQUESTS, "rate_limited"),
+            ApiError::Upstream(_) => (StatusCode::BAD_GATEWAY, "upstream"),
+            ApiError::UpstreamTimeout(_) => (StatusCode::GATEWAY_TIMEOUT, "upstream_timeout"),
+            ApiError::Unavailable(_) => (StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+            ApiError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal"),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::BadRequest(message)
+            | ApiError::NotFound(message)
+            | ApiError::RateLimited { message, .. }
+            | ApiError::Upstream(message)
+            | ApiError::UpstreamTimeout(message)
+            | ApiError::Unavailable(message)
+            | ApiError::Internal(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        ApiError::failed(err.to_string())
+    }
+}
+
+impl From<UpstreamError> for ApiError {
+    fn from(err: UpstreamError) -> Self {
+        match err {
+            UpstreamError::Timeout => ApiError::UpstreamTimeout(err.to_string()),
+            UpstreamError::CircuitOpen => ApiError::Unavailable(err.to_string()),
+            UpstreamError::Failed(message) => ApiError::Upstream(message),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, error) = self.status();
+        let retry_after = match &self {
+            ApiError::RateLimited {
+                retry_after_secs: Some(secs),
+                ..
+            } => Some(HeaderValue::from(*secs)),
+            _ => None,
+        };
+
+        let mut response = (
+            status,
+            Json(RequestError {
+                error,
+                message: self.to_string(),
+            }),
+        )
+            .into_response();
+        if let Some(retry_after) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, retry_after);
+        }
+        response
+    }
+}
+
+// a game's answer to a move; most rejections are just how the game went and come back as a
+// normal response, but the ones the request or the server is to blame for are errors
+pub trait Outcome: Serialize {
+    fn error(&self) -> Option<ApiError>;
+}
+
+pub fn respond<T: Outcome>(outcome: T) -> Result<Json<T>, ApiError> {
+    match outcome.error() {
+        Some(err) => Err(err),
+        None => Ok(Json(outcome)),
+    }
+}
+
+impl Outcome for GuessResult {
+    fn error(&self) -> Option<ApiError> {
+        let GuessResult::Invalid(reason) = self else {
+            return None;
+        };
+        let message = match reason {
+            InvalidGuess::InternalError(err) => return Some(ApiError::failed(err)),
+            InvalidGuess::NotEnoughLetters => "Not enough letters",
+            InvalidGuess::TooManyLetters => "Too many letters",
+            InvalidGuess::InvalidLetter => "Contains a letter that isn't on the keyboard",
+            _ => return None,
+        };
+        Some(ApiError::BadRequest(message.to_string()))
+    }
+}
+
+impl Outcome for SpellingGeneGuessResult {
+    fn error(&self) -> Option<ApiError> {
+        match self {
+            SpellingGeneGuessResult::Invalid(InvalidSpellingGeneGuess::InternalError(err)) => {
+                Some(ApiError::failed(err))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{ApiError, Outcome, respond};
+    use crate::upstream::UpstreamError;
+    use axum::http::{StatusCode, header};
+    use axum::response::IntoResponse;
+    use genedle_core::feedback::{GuessResult, InvalidGuess};
+
+    #[test]
+    fn test_status() {
+        let response = ApiError::NotFound("No gene symbol found".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = ApiError::Upstream("HGNC is unavailable".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+        let response = ApiError::from(UpstreamError::Timeout).into_response();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        let response = ApiError::from(UpstreamError::CircuitOpen).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let response = ApiError::RateLimited {
+            message: "Cooling down".to_string(),
+            retry_after_secs: Some(2),
+        }
+        .into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+    }
+
+    #[test]
+    fn test_outcome() {
+        let malformed = GuessResult::Invalid(InvalidGuess::TooManyLetters);
+        assert!(matches!(malformed.error(), Some(ApiError::BadRequest(_))));
+
+        // a wrong answer is still a normal response
+        let played = GuessResult::Invalid(InvalidGuess::NotInCorpus);
+        assert!(respond(played).is_ok());
+    }
+}
@@ -0,0 +1,96 @@
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use serde::Serialize;
+
+// guesses are a handful of characters; admin payloads get more room for announcement text
+pub const GAME_BODY_LIMIT: usize = 1024;
+pub const ADMIN_BODY_LIMIT: usize = 64 * 1024;
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct RequestError {
+    pub error: &'static str,
+    pub message: String,
+}
+
+// like Json, but every rejection comes back as a structured body the frontend can show
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StrictJson<T>(pub T);
+
+fn reject(rejection: JsonRejection) -> (StatusCode, Json<RequestError>) {
+    let (status, error) = match &rejection {
+        JsonRejection::JsonDataError(_) => (StatusCode::BAD_REQUEST, "invalid_data"),
+        JsonRejection::JsonSyntaxError(_) => (StatusCode::BAD_REQUEST, "invalid_json"),
+        JsonRejection::MissingJsonContentType(_) => {
+            (StatusCode::UNSUPPORTED_MEDIA_TYPE, "missing_content_type")
+        }
+        rejection if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+            (StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large")
+        }
+        rejection => (rejection.status(), "invalid_body"),
+    };
+
+    (
+        status,
+        Json(RequestError {
+            error,
+            message: rejection.body_text(),
+        }),
+    )
+}
+
+impl<T, S> FromRequest<S> for StrictJson<T>
+where
+    Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Json<RequestError>);
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::<T>::from_request(request, state).await {
+            Ok(Json(value)) => Ok(StrictJson(value)),
+            Err(rejection) => Err(reject(rejection)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::extract::StrictJson;
+    use crate::games::genedle::WeeklyGuess;
+    use axum::body::Body;
+    use axum::extract::{FromRequest, Request};
+    use axum::http::{StatusCode, header};
+
+    async fn extract(body: &'static str) -> Result<WeeklyGuess, (StatusCode, &'static str)> {
+        let request = Request::builder()
+            .method("POST")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+
+        StrictJson::<WeeklyGuess>::from_request(request, &())
+            .await
+            .map(|StrictJson(guess)| guess)
+            .map_err(|(status, body)| (status, body.0.error))
+    }
+
+    #[tokio::test]
+    async fn test_strict_json() {
+        assert_eq!(
+            extract(r#"{"word": ["T", "P", "5", "3"]}"#).await,
+            Ok(WeeklyGuess {
+                word: vec!['T', 'P', '5', '3']
+            })
+        );
+        assert_eq!(
+            extract(r#"{"word": ["T", "P", "5", "3"], "answer": "TP53"}"#).await,
+            Err((StatusCode::BAD_REQUEST, "invalid_data"))
+        );
+        assert_eq!(
+            extract(r#"{"word": "#).await,
+            Err((StatusCode::BAD_REQUEST, "invalid_json"))
+        );
+    }
+}
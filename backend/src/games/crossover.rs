@@ -0,0 +1,253 @@
+use crate::api::genections::{Board, Category, GROUP_SIZE};
+use crate::api::genedle::{
+    GuessResult, InvalidGuess, check_length, in_corpus, normalize_word, score_guess,
+};
+use crate::extract::StrictJson;
+use crate::games::genedle::RecordedGuess;
+use crate::games::{GameProgress, genections, genedle, player_id};
+use crate::puzzle_ids;
+use crate::schedule;
+use crate::stats;
+use axum::Json;
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+
+const GAME_KEY: &str = "crossover.game";
+const MAX_GUESSES: usize = 3;
+// awarded by how many guesses the bonus took; a miss earns nothing
+const BONUS_XP: [u64; MAX_GUESSES] = [30, 20, 10];
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+struct CrossoverGame {
+    day: u64,
+    guesses: Vec<RecordedGuess>,
+    solved: bool,
+}
+
+impl CrossoverGame {
+    fn is_finished(&self) -> bool {
+        self.solved || self.guesses.len() >= MAX_GUESSES
+    }
+}
+
+// the category is the clue, so nothing about the bonus shows until it's unlocked
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct CrossoverState {
+    pub day: u64,
+    pub unlocked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+    pub guesses: Vec<RecordedGuess>,
+    pub guesses_remaining: usize,
+    pub solved: bool,
+    pub finished: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    pub xp: u64,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct CrossoverResponse {
+    #[serde(flatten)]
+    pub result: GuessResult,
+    pub crossover: CrossoverState,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CrossoverGuess {
+    pub word: Vec<char>,
+}
+
+// one member of one of the day's Genections categories, the same for everyone that day
+fn pick_answer(board: &Board, day: u64) -> Option<(&Category, &String)> {
+    let key = puzzle_ids::keyed(day);
+    let category = board
+        .categories
+        .get(key as usize % board.categories.len().max(1))?;
+    let symbol = category.symbols.get((key >> 32) as usize % GROUP_SIZE)?;
+
+    Some((category, symbol))
+}
+
+fn bonus_xp(guesses: Option<usize>) -> u64 {
+    guesses
+        .and_then(|guesses| BONUS_XP.get(guesses.checked_sub(1)?))
+        .copied()
+        .unwrap_or_default()
+}
+
+fn xp_for(game: &CrossoverGame) -> u64 {
+    if game.solved {
+        bonus_xp(Some(game.guesses.len()))
+    } else {
+        0
+    }
+}
+
+// both dailies have to be over, won or lost, before the bonus opens
+async fn is_unlocked(session: &Session) -> bool {
+    genedle::daily_progress(session).await.finished
+        && genections::daily_progress(session).await.finished
+}
+
+async fn crossover_game(session: &Session) -> CrossoverGame {
+    let today = schedule::today();
+    session
+        .get::<CrossoverGame>(GAME_KEY)
+        .await
+        .ok()
+        .flatten()
+        .filter(|game| game.day == today)
+        .unwrap_or(CrossoverGame {
+            day: today,
+            ..Default::default()
+        })
+}
+
+fn crossover_state(game: CrossoverGame, answer: Option<(&Category, &String)>) -> CrossoverState {
+    let finished = game.is_finished();
+
+    CrossoverState {
+        day: game.day,
+        unlocked: answer.is_some(),
+        category: answer.map(|(category, _)| category.name.clone()),
+        length: answer.map(|(_, symbol)| symbol.chars().count()),
+        guesses_remaining: MAX_GUESSES.saturating_sub(game.guesses.len()),
+        solved: game.solved,
+        finished,
+        answer: answer
+            .filter(|_| finished)
+            .map(|(_, symbol)| symbol.clone()),
+        xp: xp_for(&game),
+        guesses: game.guesses,
+    }
+}
+
+pub(crate) async fn daily_progress(session: &Session) -> GameProgress {
+    let game = crossover_game(session).await;
+
+    GameProgress {
+        started: !game.guesses.is_empty(),
+        finished: game.is_finished(),
+    }
+}
+
+pub async fn crossover(session: Session) -> Json<CrossoverState> {
+    let game = crossover_game(&session).await;
+    if !is_unlocked(&session).await {
+        return Json(crossover_state(game, None));
+    }
+
+    let board = genections::load_board(game.day).await.ok();
+    let answer = board
+        .as_ref()
+        .and_then(|board| pick_answer(board, game.day));
+    Json(crossover_state(game, answer))
+}
+
+pub async fn guess(
+    session: Session,
+    StrictJson(guess): StrictJson<CrossoverGuess>,
+) -> Result<Json<CrossoverResponse>, StatusCode> {
+    if !is_unlocked(&session).await {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let mut game = crossover_game(&session).await;
+    let board = genections::load_board(game.day)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let answer = pick_answer(&board, game.day).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let result = match _guess(&session, &mut game, answer.1, guess).await {
+        Ok(result) => result,
+        Err(err) => GuessResult::Invalid(InvalidGuess::InternalError(err.to_string())),
+    };
+
+    Ok(Json(CrossoverResponse {
+        result,
+        crossover: crossover_state(game, Some(answer)),
+    }))
+}
+
+async fn _guess(
+    session: &Session,
+    game: &mut CrossoverGame,
+    answer: &str,
+    guess: CrossoverGuess,
+) -> Result<GuessResult, anyhow::Error> {
+    if game.is_finished() {
+        return Ok(GuessResult::Invalid(InvalidGuess::GameOver));
+    }
+
+    let word = normalize_word(&guess.word);
+    let answer: Vec<char> = answer.chars().collect();
+    if let Some(reason) = check_length(&word, answer.len()) {
+        return Ok(GuessResult::Invalid(reason));
+    }
+    // a curated board's symbols are always worth a guess, even if HGNC has since renamed one
+    if word != answer
+        && !in_corpus(word.iter().collect())
+            .await
+            .map_err(|err| anyhow::anyhow!(err))?
+    {
+        return Ok(GuessResult::Invalid(InvalidGuess::NotInCorpus));
+    }
+
+    let scored = score_guess(&word, &answer);
+    game.solved = scored.is_correct;
+    game.guesses.push(RecordedGuess {
+        word: word.iter().collect(),
+        feedback: scored.clone(),
+    });
+    session.insert(GAME_KEY, &*game).await?;
+
+    if game.is_finished() {
+        stats::record_crossover(
+            player_id(session).await?,
+            game.day,
+            game.solved,
+            xp_for(game),
+        )?;
+    }
+
+    Ok(GuessResult::Valid(scored))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::genections::{Board, Category};
+    use crate::games::crossover::{bonus_xp, pick_answer};
+
+    #[test]
+    fn test_pick_answer() {
+        let board = Board {
+            categories: (0..4)
+                .map(|group| Category {
+                    name: format!("Family {group}"),
+                    symbols: (0..4)
+                        .map(|member| format!("FAM{group}M{member}"))
+                        .collect(),
+                })
+                .collect(),
+            layout: Vec::new(),
+        };
+
+        let (category, symbol) = pick_answer(&board, 739_000).unwrap();
+        assert!(category.symbols.contains(symbol));
+        assert_eq!(pick_answer(&board, 739_000), Some((category, symbol)));
+    }
+
+    #[test]
+    fn test_bonus_xp() {
+        assert_eq!(bonus_xp(Some(1)), 30);
+        assert_eq!(bonus_xp(Some(3)), 10);
+        assert_eq!(bonus_xp(Some(4)), 0);
+        assert_eq!(bonus_xp(Some(0)), 0);
+        assert_eq!(bonus_xp(None), 0);
+    }
+}
@@ -1,6 +1,803 @@
+use crate::api::genections::{Board, Category, GROUP_SIZE, NUM_GROUPS, generate_board};
+use crate::config::config;
+use crate::curation;
+use crate::error::{ApiError, Outcome, respond};
+use crate::extract::StrictJson;
+use crate::games::genedle::random_code;
+use crate::games::{GameProgress, player_id};
+use crate::render::{Canvas, Rgb};
+use crate::schedule;
+use crate::stats;
+use crate::storage::storage;
 use axum::Json;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use axum::response::IntoResponse;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use tower_sessions::Session;
 
-pub async fn genections(_session: Session) -> Json<String> {
-    Json(String::new())
+pub(crate) const GAME_KEY: &str = "genections.game";
+const MAX_MISTAKES: usize = 4;
+const COMMON_MISTAKES: usize = 5;
+const GROUPING_HINT_COST: usize = 2;
+const SNAPSHOT_TTL_HOURS: i64 = 48;
+const COLORS: [char; NUM_GROUPS] = ['🟨', '🟩', '🟦', '🟪'];
+const CATEGORY_COLORS: [Rgb; NUM_GROUPS] = [
+    [0xf9, 0xdf, 0x6d],
+    [0xa0, 0xc3, 0x5a],
+    [0xb0, 0xc4, 0xef],
+    [0xba, 0x81, 0xc5],
+];
+const MISTAKE_COLOR: Rgb = [0x5a, 0x59, 0x4e];
+const BACKGROUND: Rgb = [0xff, 0xff, 0xff];
+const INK: Rgb = [0x1f, 0x1f, 0x1f];
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Submission {
+    pub symbols: Vec<String>,
+    pub categories: Vec<Option<usize>>,
+    pub correct: bool,
+    pub submitted_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+struct GenectionsGame {
+    day: u64,
+    submissions: Vec<Submission>,
+    solved: Vec<usize>,
+    mistakes: usize,
+    attempts: usize,
+    last_attempt_ms: i64,
+    #[serde(default)]
+    grouped: bool,
+    // minted on the first share so every share of a game links the same image
+    #[serde(default)]
+    snapshot: Option<String>,
+}
+
+// what a shared image shows: the colors of every guess and the names of solved groups, never symbols
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+struct BoardSnapshot {
+    day: u64,
+    rows: Vec<Vec<Option<usize>>>,
+    solved: Vec<usize>,
+}
+
+impl GenectionsGame {
+    fn is_finished(&self) -> bool {
+        self.solved.len() == NUM_GROUPS || self.mistakes >= MAX_MISTAKES
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct GenectionsBoard {
+    pub day: u64,
+    pub symbols: Vec<String>,
+    pub solved: Vec<Category>,
+    pub mistakes_remaining: usize,
+    pub finished: bool,
+    // once the grouping hint is used, symbols come in rows as wide as the unsolved categories
+    pub grouped: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GenectionsGuess {
+    pub symbols: Vec<String>,
+}
+
+// indices follow the board layout and never shift as groups are solved
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct IndexedSymbol {
+    pub index: usize,
+    pub symbol: String,
+    pub solved: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct TextBoard {
+    pub day: u64,
+    pub symbols: Vec<IndexedSymbol>,
+    pub solved: Vec<Category>,
+    pub mistakes_remaining: usize,
+    pub finished: bool,
+    pub grouped: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct IndexedGuess {
+    pub indices: Vec<usize>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum GenectionsGuessResult {
+    Invalid(InvalidGenectionsGuess),
+    Valid(ValidGenectionsGuess),
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidGenectionsGuess {
+    WrongGroupSize,
+    UnknownSymbol,
+    AlreadySubmitted,
+    GameOver,
+    TooManySubmissions,
+    CoolingDown { retry_after_ms: i64 },
+    HintTooCostly,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum GenectionsHintResult {
+    Invalid(InvalidGenectionsGuess),
+    Valid(GenectionsBoard),
+}
+
+impl InvalidGenectionsGuess {
+    fn error(&self) -> Option<ApiError> {
+        match self {
+            InvalidGenectionsGuess::WrongGroupSize => Some(ApiError::BadRequest(format!(
+                "A group is {GROUP_SIZE} symbols"
+            ))),
+            InvalidGenectionsGuess::TooManySubmissions => Some(ApiError::RateLimited {
+                message: "No submissions left for this board".to_string(),
+                retry_after_secs: None,
+            }),
+            InvalidGenectionsGuess::CoolingDown { retry_after_ms } => Some(ApiError::RateLimited {
+                message: format!("Wait {retry_after_ms}ms before guessing again"),
+                retry_after_secs: Some((*retry_after_ms as u64).div_ceil(1000)),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Outcome for GenectionsGuessResult {
+    fn error(&self) -> Option<ApiError> {
+        match self {
+            GenectionsGuessResult::Invalid(reason) => reason.error(),
+            GenectionsGuessResult::Valid(_) => None,
+        }
+    }
+}
+
+impl Outcome for GenectionsHintResult {
+    fn error(&self) -> Option<ApiError> {
+        match self {
+            GenectionsHintResult::Invalid(reason) => reason.error(),
+            GenectionsHintResult::Valid(_) => None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct ValidGenectionsGuess {
+    pub category: Option<Category>,
+    pub one_away: bool,
+    pub mistakes_remaining: usize,
+    pub finished: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct CommonMistake {
+    pub symbols: [String; 2],
+    pub percent: u64,
+    pub grouped: bool,
+}
+
+// a pair only counts as a mistake when its symbols were submitted together but belong apart
+fn mistaken_pairs(submissions: &[Submission]) -> BTreeSet<String> {
+    let mut pairs = BTreeSet::new();
+    for submission in submissions.iter().filter(|submission| !submission.correct) {
+        let symbols: Vec<(&String, Option<usize>)> = submission
+            .symbols
+            .iter()
+            .zip(submission.categories.iter().copied())
+            .collect();
+
+        for (i, (a, category_a)) in symbols.iter().enumerate() {
+            for (b, category_b) in &symbols[i + 1..] {
+                if category_a != category_b {
+                    let (first, second) = if a < b { (a, b) } else { (b, a) };
+                    pairs.insert(format!("{first}+{second}"));
+                }
+            }
+        }
+    }
+
+    pairs
+}
+
+async fn init_game(session: &Session) -> Result<GenectionsGame, anyhow::Error> {
+    let day = schedule::today();
+
+    match session.get::<GenectionsGame>(GAME_KEY).await? {
+        Some(game) if game.day == day => Ok(game),
+        _ => {
+            let game = GenectionsGame {
+                day,
+                ..Default::default()
+            };
+            session.insert(GAME_KEY, &game).await?;
+            stats::record_participation("genections", day)?;
+
+            Ok(game)
+        }
+    }
+}
+
+// a curated board for the day replaces the generated one
+pub(crate) async fn load_board(day: u64) -> Result<Board, anyhow::Error> {
+    if let Some(board) = curation::curated_board(day) {
+        return Ok(board);
+    }
+
+    generate_board(day)
+        .await
+        .map_err(|err| anyhow::anyhow!(err))
+}
+
+// row i holds one member of every unsolved category, shuffled so columns don't give groups away
+fn grouped_layout(board: &Board, solved: &[usize], seed: u64) -> Vec<String> {
+    let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
+    let mut remaining: Vec<Vec<&String>> = board
+        .categories
+        .iter()
+        .enumerate()
+        .filter(|(category, _)| !solved.contains(category))
+        .map(|(_, category)| category.symbols.iter().collect())
+        .collect();
+    for symbols in &mut remaining {
+        symbols.shuffle(&mut rng);
+    }
+
+    (0..GROUP_SIZE)
+        .flat_map(|row| {
+            let mut row: Vec<String> = remaining
+                .iter()
+                .map(|symbols| symbols[row].clone())
+                .collect();
+            row.shuffle(&mut rng);
+            row
+        })
+        .collect()
+}
+
+fn board_view(game: &GenectionsGame, board: &Board) -> GenectionsBoard {
+    GenectionsBoard {
+        day: game.day,
+        symbols: if game.grouped {
+            grouped_layout(board, &game.solved, game.day)
+        } else {
+            board
+                .layout
+                .iter()
+                .filter(|symbol| {
+                    board
+                        .category_of(symbol)
+                        .is_some_and(|category| !game.solved.contains(&category))
+                })
+                .cloned()
+                .collect()
+        },
+        solved: game
+            .solved
+            .iter()
+            .map(|&category| board.categories[category].clone())
+            .collect(),
+        mistakes_remaining: MAX_MISTAKES.saturating_sub(game.mistakes),
+        finished: game.is_finished(),
+        grouped: game.grouped,
+    }
+}
+
+pub(crate) async fn daily_progress(session: &Session) -> GameProgress {
+    session
+        .get::<GenectionsGame>(GAME_KEY)
+        .await
+        .ok()
+        .flatten()
+        .filter(|game| game.day == schedule::today())
+        .map(|game| GameProgress {
+            started: !game.submissions.is_empty(),
+            finished: game.is_finished(),
+        })
+        .unwrap_or_default()
+}
+
+pub async fn genections(session: Session) -> Json<Option<GenectionsBoard>> {
+    let Ok(game) = init_game(&session).await else {
+        return Json(None);
+    };
+
+    Json(
+        load_board(game.day)
+            .await
+            .ok()
+            .map(|board| board_view(&game, &board)),
+    )
+}
+
+pub async fn text_board(session: Session) -> Json<Option<TextBoard>> {
+    let Ok(game) = init_game(&session).await else {
+        return Json(None);
+    };
+    let Ok(board) = load_board(game.day).await else {
+        return Json(None);
+    };
+
+    let view = board_view(&game, &board);
+    let mut symbols: Vec<IndexedSymbol> = board
+        .layout
+        .iter()
+        .enumerate()
+        .map(|(index, symbol)| IndexedSymbol {
+            index,
+            symbol: symbol.clone(),
+            solved: !view.symbols.contains(symbol),
+        })
+        .collect();
+    // a grouped board is listed row by row, with solved symbols trailing
+    if view.grouped {
+        symbols.sort_by_key(|indexed| {
+            view.symbols
+                .iter()
+                .position(|symbol| *symbol == indexed.symbol)
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    Json(Some(TextBoard {
+        day: view.day,
+        symbols,
+        solved: view.solved,
+        mistakes_remaining: view.mistakes_remaining,
+        finished: view.finished,
+        grouped: view.grouped,
+    }))
+}
+
+pub async fn text_guess(
+    session: Session,
+    StrictJson(guess): StrictJson<IndexedGuess>,
+) -> Result<Json<GenectionsGuessResult>, ApiError> {
+    let result = async {
+        let game = init_game(&session).await?;
+        let board = load_board(game.day).await?;
+
+        let Some(symbols) = guess
+            .indices
+            .iter()
+            .map(|&index| board.layout.get(index).cloned())
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(GenectionsGuessResult::Invalid(
+                InvalidGenectionsGuess::UnknownSymbol,
+            ));
+        };
+
+        _guess(&session, GenectionsGuess { symbols }).await
+    };
+
+    respond(result.await?)
+}
+
+pub async fn guess(
+    session: Session,
+    StrictJson(guess): StrictJson<GenectionsGuess>,
+) -> Result<Json<GenectionsGuessResult>, ApiError> {
+    respond(_guess(&session, guess).await?)
+}
+
+async fn _guess(
+    session: &Session,
+    guess: GenectionsGuess,
+) -> Result<GenectionsGuessResult, anyhow::Error> {
+    let mut game = init_game(session).await?;
+    if game.is_finished() {
+        return Ok(GenectionsGuessResult::Invalid(
+            InvalidGenectionsGuess::GameOver,
+        ));
+    }
+
+    // every submission counts against the budget, even ones rejected below
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let retry_after_ms = game.last_attempt_ms + config().genections_cooldown_ms - now_ms;
+    if retry_after_ms > 0 {
+        return Ok(GenectionsGuessResult::Invalid(
+            InvalidGenectionsGuess::CoolingDown { retry_after_ms },
+        ));
+    }
+    if game.attempts >= config().genections_max_submissions {
+        return Ok(GenectionsGuessResult::Invalid(
+            InvalidGenectionsGuess::TooManySubmissions,
+        ));
+    }
+
+    game.attempts += 1;
+    game.last_attempt_ms = now_ms;
+    session.insert(GAME_KEY, &game).await?;
+
+    let unique: BTreeSet<&String> = guess.symbols.iter().collect();
+    if guess.symbols.len() != GROUP_SIZE || unique.len() != GROUP_SIZE {
+        return Ok(GenectionsGuessResult::Invalid(
+            InvalidGenectionsGuess::WrongGroupSize,
+        ));
+    }
+
+    let already_submitted = game
+        .submissions
+        .iter()
+        .any(|submission| submission.symbols.iter().collect::<BTreeSet<_>>() == unique);
+    if already_submitted {
+        return Ok(GenectionsGuessResult::Invalid(
+            InvalidGenectionsGuess::AlreadySubmitted,
+        ));
+    }
+
+    let board = load_board(game.day).await?;
+    let categories: Vec<Option<usize>> = guess
+        .symbols
+        .iter()
+        .map(|symbol| board.category_of(symbol))
+        .collect();
+    if categories
+        .iter()
+        .any(|category| category.is_none_or(|category| game.solved.contains(&category)))
+    {
+        return Ok(GenectionsGuessResult::Invalid(
+            InvalidGenectionsGuess::UnknownSymbol,
+        ));
+    }
+
+    let largest_overlap = (0..board.categories.len())
+        .map(|category| categories.iter().filter(|&&c| c == Some(category)).count())
+        .max()
+        .unwrap_or_default();
+    let correct = largest_overlap == GROUP_SIZE;
+
+    game.submissions.push(Submission {
+        symbols: guess.symbols,
+        categories: categories.clone(),
+        correct,
+        submitted_at: chrono::Utc::now().timestamp(),
+    });
+
+    let category = if correct {
+        let category = categories[0].unwrap();
+        game.solved.push(category);
+        Some(board.categories[category].clone())
+    } else {
+        game.mistakes += 1;
+        None
+    };
+    session.insert(GAME_KEY, &game).await?;
+
+    if game.is_finished() {
+        stats::record_genections(
+            player_id(session).await?,
+            game.day,
+            game.solved.len() == NUM_GROUPS,
+            game.mistakes,
+            game.submissions.len(),
+        )?;
+        stats::record_genections_mistakes(game.day, &mistaken_pairs(&game.submissions))?;
+    }
+
+    Ok(GenectionsGuessResult::Valid(ValidGenectionsGuess {
+        category,
+        one_away: largest_overlap == GROUP_SIZE - 1,
+        mistakes_remaining: MAX_MISTAKES.saturating_sub(game.mistakes),
+        finished: game.is_finished(),
+    }))
+}
+
+pub async fn grouping_hint(session: Session) -> Result<Json<GenectionsHintResult>, ApiError> {
+    let result = async {
+        let mut game = init_game(&session).await?;
+        if game.is_finished() {
+            return Ok(GenectionsHintResult::Invalid(
+                InvalidGenectionsGuess::GameOver,
+            ));
+        }
+
+        if !game.grouped {
+            // the hint may not be what ends the game
+            if MAX_MISTAKES - game.mistakes <= GROUPING_HINT_COST {
+                return Ok(GenectionsHintResult::Invalid(
+                    InvalidGenectionsGuess::HintTooCostly,
+                ));
+            }
+
+            game.grouped = true;
+            game.mistakes += GROUPING_HINT_COST;
+            session.insert(GAME_KEY, &game).await?;
+        }
+
+        let board = load_board(game.day).await?;
+        Ok::<_, anyhow::Error>(GenectionsHintResult::Valid(board_view(&game, &board)))
+    };
+
+    respond(result.await?)
+}
+
+pub async fn submissions(session: Session) -> Json<Vec<Submission>> {
+    Json(
+        init_game(&session)
+            .await
+            .map(|game| game.submissions)
+            .unwrap_or_default(),
+    )
+}
+
+pub async fn mistakes(session: Session) -> Json<Vec<CommonMistake>> {
+    let game = match init_game(&session).await {
+        Ok(game) if game.is_finished() => game,
+        _ => return Json(Vec::new()),
+    };
+
+    let grouped = mistaken_pairs(&game.submissions);
+    let mistakes = stats::genections_mistakes(game.day);
+
+    let mut pairs: Vec<(&String, u64)> = mistakes
+        .pairs
+        .iter()
+        .map(|(pair, &count)| (pair, count))
+        .collect();
+    pairs.sort_by_key(|&(pair, count)| (std::cmp::Reverse(count), pair));
+
+    Json(
+        pairs
+            .into_iter()
+            .take(COMMON_MISTAKES)
+            .filter_map(|(pair, count)| {
+                let (a, b) = pair.split_once('+')?;
+                Some(CommonMistake {
+                    symbols: [a.to_string(), b.to_string()],
+                    percent: count * 100 / mistakes.games.max(1),
+                    grouped: grouped.contains(pair),
+                })
+            })
+            .collect(),
+    )
+}
+
+fn snapshot_key(code: &str) -> String {
+    format!("genections.snapshot.{code}")
+}
+
+async fn share_snapshot(
+    session: &Session,
+    game: &mut GenectionsGame,
+) -> Result<String, anyhow::Error> {
+    if let Some(code) = &game.snapshot {
+        return Ok(code.clone());
+    }
+
+    let code = loop {
+        let code = random_code();
+        if storage()
+            .get::<BoardSnapshot>(&snapshot_key(&code))
+            .is_none()
+        {
+            break code;
+        }
+    };
+    storage().insert_with_ttl(
+        &snapshot_key(&code),
+        &BoardSnapshot {
+            day: game.day,
+            rows: game
+                .submissions
+                .iter()
+                .map(|submission| submission.categories.clone())
+                .collect(),
+            solved: game.solved.clone(),
+        },
+        chrono::Duration::hours(SNAPSHOT_TTL_HOURS),
+    )?;
+
+    game.snapshot = Some(code.clone());
+    session.insert(GAME_KEY, &*game).await?;
+
+    Ok(code)
+}
+
+pub async fn share(session: Session) -> Json<String> {
+    let mut game = match init_game(&session).await {
+        Ok(game) if game.is_finished() => game,
+        Ok(_) => return Json(String::new()),
+        Err(err) => return Json(format!("Error loading game: {err}")),
+    };
+
+    let mut share = schedule::numbered("Genections", schedule::puzzle_number(game.day));
+    share.push('\n');
+    for submission in &game.submissions {
+        share.push('\n');
+        share.extend(
+            submission
+                .categories
+                .iter()
+                .map(|category| category.map_or('⬛', |category| COLORS[category])),
+        );
+    }
+
+    // some platforms strip emoji, so the grid is also linked as an image
+    if let Ok(code) = share_snapshot(&session, &mut game).await {
+        share.push_str(&format!(
+            "\n\n{}{}",
+            config().public_url,
+            config().link(&format!("/games/genections/snapshot/{code}"))
+        ));
+    }
+
+    Json(share)
+}
+
+fn render_snapshot(snapshot: &BoardSnapshot, board: &Board) -> Canvas {
+    const WIDTH: u32 = 420;
+    const PADDING: u32 = 16;
+    const SCALE: u32 = 2;
+    const GAP: u32 = 6;
+    const BAR_HEIGHT: u32 = 36;
+    const CELL: u32 = 24;
+
+    let text_height = Canvas::text_height(SCALE);
+    let grid_width = GROUP_SIZE as u32 * (CELL + GAP) - GAP;
+    let height = PADDING
+        + text_height
+        + PADDING
+        + snapshot.solved.len() as u32 * (BAR_HEIGHT + GAP)
+        + snapshot.rows.len() as u32 * (CELL + GAP)
+        + PADDING;
+
+    let mut canvas = Canvas::new(WIDTH, height, BACKGROUND);
+    let title = schedule::numbered("GENECTIONS", schedule::puzzle_number(snapshot.day));
+    canvas.draw_text(
+        (WIDTH - Canvas::text_width(&title, SCALE)) / 2,
+        PADDING,
+        &title,
+        SCALE,
+        INK,
+    );
+
+    let mut y = PADDING + text_height + PADDING;
+    for &category in &snapshot.solved {
+        canvas.fill_rect(
+            PADDING,
+            y,
+            WIDTH - 2 * PADDING,
+            BAR_HEIGHT,
+            CATEGORY_COLORS[category],
+        );
+        let name = Canvas::fit_text(&board.categories[category].name, SCALE, WIDTH - 4 * PADDING);
+        canvas.draw_text(
+            2 * PADDING,
+            y + (BAR_HEIGHT - text_height) / 2,
+            name,
+            SCALE,
+            INK,
+        );
+        y += BAR_HEIGHT + GAP;
+    }
+
+    for row in &snapshot.rows {
+        let mut x = (WIDTH - grid_width) / 2;
+        for category in row {
+            let color = category.map_or(MISTAKE_COLOR, |category| CATEGORY_COLORS[category]);
+            canvas.fill_rect(x, y, CELL, CELL, color);
+            x += CELL + GAP;
+        }
+        y += CELL + GAP;
+    }
+
+    canvas
+}
+
+// fetched by link previews, so it's served without a session
+pub async fn snapshot(Path(code): Path<String>) -> Result<impl IntoResponse, StatusCode> {
+    let snapshot = storage()
+        .get::<BoardSnapshot>(&snapshot_key(&code.to_ascii_uppercase()))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let board = load_board(snapshot.day)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let png = render_snapshot(&snapshot, &board)
+        .to_png()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, "image/png"),
+            (CACHE_CONTROL, "public, max-age=3600"),
+        ],
+        png,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission(symbols: [&str; GROUP_SIZE], categories: [usize; GROUP_SIZE]) -> Submission {
+        Submission {
+            symbols: symbols.iter().map(|symbol| symbol.to_string()).collect(),
+            categories: categories.iter().copied().map(Some).collect(),
+            correct: categories.iter().all(|&category| category == categories[0]),
+            submitted_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_mistaken_pairs() {
+        let submissions = [
+            submission(["HOXA1", "HOXA2", "HOXA3", "KRT1"], [0, 0, 0, 1]),
+            submission(["HOXA1", "HOXA2", "HOXA3", "HOXA4"], [0, 0, 0, 0]),
+        ];
+
+        assert_eq!(
+            mistaken_pairs(&submissions),
+            BTreeSet::from(["HOXA1+KRT1", "HOXA2+KRT1", "HOXA3+KRT1"].map(String::from))
+        );
+    }
+
+    #[test]
+    fn test_grouped_layout() {
+        let board = Board {
+            categories: (0..NUM_GROUPS)
+                .map(|category| Category {
+                    name: category.to_string(),
+                    symbols: (0..GROUP_SIZE)
+                        .map(|member| format!("G{category}{member}"))
+                        .collect(),
+                })
+                .collect(),
+            layout: Vec::new(),
+        };
+
+        let layout = grouped_layout(&board, &[1], 739000);
+        assert_eq!(layout.len(), (NUM_GROUPS - 1) * GROUP_SIZE);
+        for row in layout.chunks(NUM_GROUPS - 1) {
+            let categories: BTreeSet<Option<usize>> =
+                row.iter().map(|symbol| board.category_of(symbol)).collect();
+            assert_eq!(categories, BTreeSet::from([Some(0), Some(2), Some(3)]));
+        }
+        assert_eq!(layout, grouped_layout(&board, &[1], 739000));
+    }
+
+    #[test]
+    fn test_render_snapshot() {
+        let board = Board {
+            categories: (0..NUM_GROUPS)
+                .map(|category| Category {
+                    name: format!("Family {category}"),
+                    symbols: Vec::new(),
+                })
+                .collect(),
+            layout: Vec::new(),
+        };
+        let snapshot = BoardSnapshot {
+            day: 739000,
+            rows: vec![
+                vec![Some(0), Some(0), Some(0), Some(2)],
+                vec![Some(2); GROUP_SIZE],
+            ],
+            solved: vec![2],
+        };
+
+        let canvas = render_snapshot(&snapshot, &board);
+        assert_eq!(canvas.height(), 16 + 14 + 16 + 42 + 2 * 30 + 16);
+        // the solved bar, then the first and last cells of the one-away row
+        assert_eq!(canvas.pixel(20, 50), CATEGORY_COLORS[2]);
+        assert_eq!(canvas.pixel(153, 90), CATEGORY_COLORS[0]);
+        assert_eq!(canvas.pixel(243, 90), CATEGORY_COLORS[2]);
+    }
 }
@@ -1,28 +1,1154 @@
+use crate::api::genedle::get_word as get_answer;
+use crate::api::genedle::{
+    self, GameMode, GameSummary, Guess, GuessResponse, GuessResult, Hint, InvalidGuess,
+    Temperature, ValidGuess, check_hints, check_length, get_weekly_word, in_corpus, normalize_word,
+    puzzle_hints, score_guess,
+};
+use crate::config::config;
+use crate::corpus::{self, Gene};
+use crate::error::{ApiError, respond};
+use crate::extract::StrictJson;
+use crate::games::{GameProgress, player_id};
+use crate::leaderboard::{self, Board};
+use crate::puzzle_ids::{self, PuzzleId};
+use crate::schedule;
+use crate::stats::{self, GenedleRecord, WeeklyStats};
+use crate::storage::storage;
+use crate::survey;
+use crate::themes::themes;
 use axum::Json;
-use chrono::Datelike;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use genedle_core::feedback::LetterFeedback;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use tower_sessions::Session;
 
-const WORD_KEY: &str = "genedle.word";
+pub(crate) const WORD_KEY: &str = "genedle.word";
+pub(crate) const WEEKLY_KEY: &str = "genedle.weekly";
+const PRACTICE_KEY: &str = "genedle.practice";
+const ARCHIVE_KEY: &str = "genedle.archive";
+const GUESSES_KEY: &str = "genedle.guesses";
+// enough to cover the daily puzzle plus a run of practice games without letting the session grow
+const TRACKED_PUZZLES: usize = 8;
+const SHARE_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const SHARE_CODE_LENGTH: usize = 6;
+const SHARE_CODE_TTL_HOURS: i64 = 48;
+const WEEKLY_MAX_GUESSES: usize = 6;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+struct WeeklyGame {
+    week: u64,
+    started_at: i64,
+    guesses: Vec<ValidGuess>,
+    // what was guessed, alongside how it scored, for the hard-mode checks
+    #[serde(default)]
+    words: Vec<String>,
+    finished: bool,
+    #[serde(default)]
+    finished_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RecordedGuess {
+    pub word: String,
+    #[serde(flatten)]
+    pub feedback: ValidGuess,
+}
+
+// the clock starts at the first guess, not when the page loads
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+struct PuzzleGuesses {
+    puzzle: u64,
+    guesses: Vec<RecordedGuess>,
+    solved: bool,
+    #[serde(default)]
+    started_at: i64,
+    #[serde(default)]
+    finished_at: Option<i64>,
+    // positions given away by letter reveals
+    #[serde(default)]
+    revealed: Vec<usize>,
+    // how many of the puzzle's escalating hints have been shown
+    #[serde(default)]
+    hint_level: usize,
+}
+
+impl PuzzleGuesses {
+    // each reveal costs guesses, so it counts toward the score as well as the limit
+    fn spent(&self) -> usize {
+        self.guesses.len() + self.revealed.len() * config().hints.cost
+    }
+
+    fn is_finished(&self) -> bool {
+        self.solved || self.spent() >= config().max_guesses
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FinishedGame {
+    pub puzzle: u64,
+    pub guesses: Option<usize>,
+    pub seconds: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct GenedleState {
+    pub puzzle: PuzzleId,
+    pub guesses: Vec<RecordedGuess>,
+    pub solved: bool,
+    pub guesses_remaining: usize,
+    pub revealed: Vec<usize>,
+    pub hints: Vec<Hint>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct GenedleStats {
+    #[serde(flatten)]
+    pub record: GenedleRecord,
+    pub win_percent: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RevealRequest {
+    pub puzzle: PuzzleId,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum RevealResult {
+    Invalid(InvalidReveal),
+    Valid(Reveal),
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidReveal {
+    GameOver,
+    NotAvailable,
+    TooCostly,
+    DailyBudgetSpent,
+    NothingToReveal,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct Reveal {
+    pub position: usize,
+    pub letter: char,
+    pub reveals_remaining: usize,
+    pub guesses_remaining: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum HintResult {
+    Invalid(InvalidHint),
+    Valid(HintProgress),
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidHint {
+    GameOver,
+    NoMoreHints,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct HintProgress {
+    pub hints: Vec<Hint>,
+    pub hints_remaining: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct ArchivePuzzle {
+    pub puzzle: PuzzleId,
+    pub day: u64,
+    pub number: Option<u64>,
+    pub date: Option<String>,
+}
+
+impl ArchivePuzzle {
+    fn new(day: u64) -> Self {
+        ArchivePuzzle {
+            puzzle: PuzzleId(day),
+            day,
+            number: schedule::puzzle_number(day),
+            date: schedule::date(day).map(|date| date.to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct StateQuery {
+    pub puzzle: Option<PuzzleId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+// HGNC ids were handed out roughly in order of discovery, so low ids stand in for well-known genes
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DifficultyTier {
+    pub difficulty: Difficulty,
+    pub min_length: usize,
+    pub max_length: usize,
+    pub max_hgnc_id: Option<u32>,
+    pub digits: bool,
+}
+
+impl Difficulty {
+    pub fn tier(self) -> DifficultyTier {
+        let (min_length, max_length, max_hgnc_id, digits) = match self {
+            Difficulty::Easy => (3, 5, Some(10_000), false),
+            Difficulty::Medium => (4, 6, Some(30_000), true),
+            Difficulty::Hard => (5, config().max_answer_length, None, true),
+        };
+
+        DifficultyTier {
+            difficulty: self,
+            min_length,
+            max_length,
+            max_hgnc_id,
+            digits,
+        }
+    }
+}
+
+impl DifficultyTier {
+    fn allows(&self, gene: &Gene) -> bool {
+        let length = gene.symbol.chars().count();
+
+        (self.min_length..=self.max_length).contains(&length)
+            && self
+                .max_hgnc_id
+                .is_none_or(|max| gene.hgnc_id.is_some_and(|id| id <= max))
+            && (self.digits || !gene.symbol.chars().any(|c| c.is_ascii_digit()))
+    }
+}
+
+// share codes from before difficulty tiers stored the bare puzzle key
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(untagged)]
+enum SharedPractice {
+    Puzzle(u64),
+    Tiered { puzzle: u64, difficulty: Difficulty },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct PracticeGame {
+    pub puzzle: PuzzleId,
+    pub code: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<DifficultyTier>,
+}
+
+impl PracticeGame {
+    fn new(puzzle: u64, code: String, difficulty: Option<Difficulty>) -> Self {
+        let url = config().link(&format!("/games/genedle-practice/{code}"));
+        PracticeGame {
+            puzzle: PuzzleId(puzzle),
+            code,
+            url,
+            difficulty: difficulty.map(Difficulty::tier),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct PracticeOptions {
+    #[serde(default)]
+    pub no_repeats: bool,
+    #[serde(default)]
+    pub difficulty: Option<Difficulty>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WeeklyGuess {
+    pub word: Vec<char>,
+}
 
 async fn get_word(session: &Session) -> Option<u64> {
     session.get::<u64>(WORD_KEY).await.ok().flatten()
 }
 
 async fn init_word(session: &Session) -> Result<u64, anyhow::Error> {
+    let word_of_the_day = schedule::today();
     match get_word(session).await {
-        None => {
-            let word_of_the_day = chrono::Utc::now().num_days_from_ce() as u64;
+        Some(word_selection) if word_selection == word_of_the_day => Ok(word_selection),
+        // a board from before the rollover is rebound now rather than whenever the sweep gets to it
+        _ => {
             session.insert(WORD_KEY, word_of_the_day).await?;
+            stats::record_participation("genedle", word_of_the_day)?;
+            let _ = remember_answer(session, word_of_the_day).await;
 
             Ok(word_of_the_day)
         }
-        Some(word_selection) => Ok(word_selection),
     }
 }
 
 pub async fn genedle(session: Session) -> Json<String> {
     match init_word(&session).await {
-        Ok(word) => Json(word.to_string()),
+        Ok(word) => Json(puzzle_ids::seal(word)),
+        Err(err) => Json(format!("Error initializing word: {err}")),
+    }
+}
+
+async fn tracked_puzzles(session: &Session) -> Vec<PuzzleGuesses> {
+    session
+        .get::<Vec<PuzzleGuesses>>(GUESSES_KEY)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+// practice rounds are unlimited, so they make room by evicting each other before any daily board;
+// today's is never evicted, or playing enough archive days would hand back its guesses
+fn track(tracked: &mut Vec<PuzzleGuesses>, puzzle: u64) -> usize {
+    match tracked.iter().position(|p| p.puzzle == puzzle) {
+        Some(index) => index,
+        None => {
+            if tracked.len() >= TRACKED_PUZZLES {
+                let today = schedule::today();
+                let oldest = tracked
+                    .iter()
+                    .position(|p| !schedule::is_daily(p.puzzle))
+                    .or_else(|| tracked.iter().position(|p| p.puzzle != today));
+                if let Some(oldest) = oldest {
+                    tracked.remove(oldest);
+                }
+            }
+            tracked.push(PuzzleGuesses {
+                puzzle,
+                started_at: chrono::Utc::now().timestamp(),
+                ..Default::default()
+            });
+            tracked.len() - 1
+        }
+    }
+}
+
+// puzzles are tracked by key so switching between the daily and practice games can't reset either
+pub async fn guess(
+    session: Session,
+    State(client): State<Client>,
+    StrictJson(guess): StrictJson<Guess>,
+) -> Result<Json<GuessResponse>, ApiError> {
+    // normalized before the hard-mode checks, which compare against earlier scored guesses
+    let guess = guess.normalized();
+    let puzzle = guess.session.0;
+    let assist = guess.assist;
+    let canonical: String = guess.word.iter().collect();
+    let result = record_guess(&session, guess).await;
+
+    // the answer's details come back with the guess that ends the game
+    let summary = match &result {
+        GuessResult::Valid(_) if is_finished(&session, puzzle).await => {
+            game_summary(puzzle, &client).await
+        }
+        _ => None,
+    };
+    let alias_of = match &result {
+        GuessResult::Valid(_) => genedle::alias_of(&canonical).await,
+        _ => None,
+    };
+    let temperature = match &result {
+        GuessResult::Valid(_) if assist && summary.is_none() => temperature(&session, puzzle).await,
+        _ => None,
+    };
+
+    respond(GuessResponse {
+        result,
+        summary,
+        alias_of,
+        canonical,
+        temperature,
+    })
+}
+
+async fn temperature(session: &Session, puzzle: u64) -> Option<Temperature> {
+    let progress = tracked_puzzles(session)
+        .await
+        .into_iter()
+        .find(|progress| progress.puzzle == puzzle)?;
+    let length = progress.guesses.last()?.word.chars().count();
+    let previous: Vec<(Vec<char>, &[LetterFeedback])> = progress
+        .guesses
+        .iter()
+        .map(|recorded| {
+            (
+                recorded.word.chars().collect(),
+                recorded.feedback.result.as_slice(),
+            )
+        })
+        .collect();
+
+    genedle::temperature(length, &previous).await.ok()
+}
+
+async fn game_summary(puzzle: u64, client: &Client) -> Option<GameSummary> {
+    let answer = get_answer(puzzle).await.ok()?;
+    Some(genedle::game_summary(client, &answer).await)
+}
+
+async fn record_guess(session: &Session, guess: Guess) -> GuessResult {
+    let mut tracked = tracked_puzzles(session).await;
+    let index = track(&mut tracked, guess.session.0);
+
+    let progress = &mut tracked[index];
+    if progress.is_finished() {
+        return GuessResult::Invalid(InvalidGuess::GameOver);
+    }
+
+    // hints only make sense against a guess of the same length; anything else fails the length check
+    let previous: Vec<(Vec<char>, &[LetterFeedback])> = progress
+        .guesses
+        .iter()
+        .map(|recorded| {
+            let word: Vec<char> = recorded.word.chars().collect();
+            (word, recorded.feedback.result.as_slice())
+        })
+        .filter(|(word, _)| word.len() == guess.word.len())
+        .collect();
+    if guess.mode == GameMode::Hard
+        && let Some(reason) = check_hints(
+            &guess.word,
+            previous
+                .iter()
+                .map(|(word, feedback)| (word.as_slice(), *feedback)),
+        )
+    {
+        return GuessResult::Invalid(reason);
+    }
+
+    let word = guess.word.iter().collect();
+    let rating = guess.rating;
+    let result = genedle::guess(Json(guess)).await.0;
+    if let GuessResult::Valid(scored) = &result {
+        progress.solved = scored.is_correct;
+        progress.guesses.push(RecordedGuess {
+            word,
+            feedback: scored.clone(),
+        });
+
+        let finished = progress.is_finished();
+        if finished {
+            progress.finished_at = Some(chrono::Utc::now().timestamp());
+        }
+
+        let progress = &tracked[index];
+        let guesses = progress.solved.then_some(progress.spent());
+        let puzzle = progress.puzzle;
+
+        if let Err(err) = session.insert(GUESSES_KEY, &tracked).await {
+            return GuessResult::Invalid(InvalidGuess::InternalError(err.to_string()));
+        }
+
+        // only the session's own daily counts toward streaks; a bound archive day is kept apart,
+        // and practice can't pad either
+        if finished && let Ok(player) = player_id(session).await {
+            let counted = if get_word(session).await == Some(puzzle) {
+                let _ = stats::record_genedle(player, puzzle, guesses);
+                true
+            } else if session.get::<u64>(ARCHIVE_KEY).await.ok().flatten() == Some(puzzle) {
+                let _ = stats::record_archive(player, puzzle, guesses);
+                true
+            } else {
+                false
+            };
+
+            // rated with the guess that ends the game, and only for days everyone plays
+            if counted && let Some(rating) = rating {
+                let _ = survey::record("genedle", puzzle, player, rating);
+            }
+            if counted
+                && guesses.is_some()
+                && let Ok(answer) = get_answer(puzzle).await
+            {
+                let _ = stats::record_themes(player, &answer, themes().of(&answer));
+            }
+        }
+    }
+
+    result
+}
+
+pub(crate) async fn finished_daily(
+    session: &Session,
+) -> Result<Option<FinishedGame>, anyhow::Error> {
+    let Some(puzzle) = get_word(session).await else {
+        return Ok(None);
+    };
+
+    Ok(tracked_puzzles(session)
+        .await
+        .into_iter()
+        .find(|progress| progress.puzzle == puzzle)
+        .and_then(|progress| {
+            progress.finished_at.map(|finished_at| FinishedGame {
+                puzzle,
+                guesses: progress.solved.then_some(progress.spent()),
+                seconds: finished_at - progress.started_at,
+            })
+        }))
+}
+
+pub(crate) async fn daily_progress(session: &Session) -> GameProgress {
+    let today = schedule::today();
+    tracked_puzzles(session)
+        .await
+        .into_iter()
+        .find(|progress| progress.puzzle == today)
+        .map(|progress| GameProgress {
+            started: progress.spent() > 0,
+            finished: progress.is_finished(),
+        })
+        .unwrap_or_default()
+}
+
+// a day is archived once its answer has been drawn and the day is over
+fn archived_days() -> BTreeSet<u64> {
+    let today = schedule::today();
+    storage()
+        .keys("genedle.daily.")
+        .iter()
+        .filter_map(|key| key.strip_prefix("genedle.daily.")?.parse().ok())
+        .filter(|&day| day < today)
+        .collect()
+}
+
+pub async fn genedle_archive() -> Json<Vec<ArchivePuzzle>> {
+    Json(
+        archived_days()
+            .into_iter()
+            .rev()
+            .map(ArchivePuzzle::new)
+            .collect(),
+    )
+}
+
+pub async fn start_archive(session: Session, Path(day): Path<u64>) -> Json<Option<ArchivePuzzle>> {
+    if !archived_days().contains(&day) {
+        return Json(None);
+    }
+
+    Json(
+        session
+            .insert(ARCHIVE_KEY, day)
+            .await
+            .ok()
+            .map(|_| ArchivePuzzle::new(day)),
+    )
+}
+
+pub async fn genedle_stats(session: Session) -> Json<Option<GenedleStats>> {
+    Json(player_id(&session).await.ok().map(|player| {
+        let mut record = stats::genedle_record(player);
+        record.streak = record.streak_as_of(schedule::today());
+        GenedleStats {
+            win_percent: record.won * 100 / record.played.max(1),
+            record,
+        }
+    }))
+}
+
+async fn requested_puzzle(session: &Session, puzzle: Option<PuzzleId>) -> u64 {
+    match puzzle {
+        Some(PuzzleId(puzzle)) => puzzle,
+        None => get_word(session).await.unwrap_or_else(schedule::today),
+    }
+}
+
+// defaults to the session's daily puzzle so a reloaded page can restore its board
+pub async fn genedle_state(
+    session: Session,
+    Query(query): Query<StateQuery>,
+) -> Json<GenedleState> {
+    let puzzle = requested_puzzle(&session, query.puzzle).await;
+
+    let progress = tracked_puzzles(&session)
+        .await
+        .into_iter()
+        .find(|progress| progress.puzzle == puzzle)
+        .unwrap_or_default();
+
+    let guesses_remaining = if progress.solved {
+        0
+    } else {
+        config().max_guesses.saturating_sub(progress.spent())
+    };
+
+    let hints = match progress.hint_level {
+        0 => Vec::new(),
+        level => puzzle_hints(puzzle)
+            .await
+            .map(|hints| hints.into_iter().take(level).collect())
+            .unwrap_or_default(),
+    };
+
+    Json(GenedleState {
+        puzzle: PuzzleId(puzzle),
+        guesses_remaining,
+        guesses: progress.guesses,
+        solved: progress.solved,
+        revealed: progress.revealed,
+        hints,
+    })
+}
+
+async fn is_finished(session: &Session, puzzle: u64) -> bool {
+    tracked_puzzles(session)
+        .await
+        .iter()
+        .any(|progress| progress.puzzle == puzzle && progress.is_finished())
+}
+
+// the answer only leaves the server once the game can no longer be played
+pub async fn genedle_answer(
+    session: Session,
+    Query(query): Query<StateQuery>,
+) -> Result<Json<String>, StatusCode> {
+    let puzzle = requested_puzzle(&session, query.puzzle).await;
+    if !is_finished(&session, puzzle).await {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    get_answer(puzzle)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+pub(crate) async fn solved_answer(session: &Session, puzzle: Option<PuzzleId>) -> Option<String> {
+    let puzzle = requested_puzzle(session, puzzle).await;
+    let solved = tracked_puzzles(session)
+        .await
+        .iter()
+        .any(|progress| progress.puzzle == puzzle && progress.solved);
+    if !solved {
+        return None;
+    }
+
+    get_answer(puzzle).await.ok()
+}
+
+// the daily puzzle and untiered practice share the standard allowance
+async fn puzzle_difficulty(session: &Session, puzzle: u64) -> Option<Difficulty> {
+    if schedule::is_daily(puzzle) {
+        return None;
+    }
+
+    session
+        .get::<PracticeGame>(PRACTICE_KEY)
+        .await
+        .ok()
+        .flatten()
+        .filter(|game| game.puzzle.0 == puzzle)
+        .and_then(|game| game.difficulty)
+        .map(|tier| tier.difficulty)
+}
+
+pub async fn reveal_letter(
+    session: Session,
+    StrictJson(request): StrictJson<RevealRequest>,
+) -> Result<Json<RevealResult>, ApiError> {
+    Ok(Json(_reveal_letter(&session, request.puzzle.0).await?))
+}
+
+async fn _reveal_letter(session: &Session, puzzle: u64) -> Result<RevealResult, anyhow::Error> {
+    let hints = config().hints;
+    let mut tracked = tracked_puzzles(session).await;
+    let index = track(&mut tracked, puzzle);
+    let progress = &mut tracked[index];
+
+    if progress.is_finished() {
+        return Ok(RevealResult::Invalid(InvalidReveal::GameOver));
+    }
+    let allowance = hints.allowance(puzzle_difficulty(session, puzzle).await);
+    if progress.revealed.len() >= allowance {
+        return Ok(RevealResult::Invalid(InvalidReveal::NotAvailable));
+    }
+    // a reveal has to leave at least one guess to use it with
+    if progress.spent() + hints.cost >= config().max_guesses {
+        return Ok(RevealResult::Invalid(InvalidReveal::TooCostly));
+    }
+
+    let player = player_id(session).await?;
+    let today = schedule::today();
+    if stats::hints_used(player, today) >= hints.daily_budget {
+        return Ok(RevealResult::Invalid(InvalidReveal::DailyBudgetSpent));
+    }
+
+    let answer: Vec<char> = get_answer(puzzle)
+        .await
+        .map_err(|err| anyhow::anyhow!(err))?
+        .chars()
+        .collect();
+    let known = |position: usize| {
+        progress.revealed.contains(&position)
+            || progress.guesses.iter().any(|recorded| {
+                recorded.word.chars().count() == answer.len()
+                    && recorded.feedback.result.get(position) == Some(&LetterFeedback::Correct)
+            })
+    };
+    let Some(position) = (0..answer.len()).find(|&position| !known(position)) else {
+        return Ok(RevealResult::Invalid(InvalidReveal::NothingToReveal));
+    };
+
+    progress.revealed.push(position);
+    let reveal = Reveal {
+        position,
+        letter: answer[position],
+        reveals_remaining: allowance - progress.revealed.len(),
+        guesses_remaining: config().max_guesses.saturating_sub(progress.spent()),
+    };
+    session.insert(GUESSES_KEY, &tracked).await?;
+    stats::record_hint(player, today)?;
+
+    Ok(RevealResult::Valid(reveal))
+}
+
+// each call shows one more hint; they're free to take but marked on the share
+pub async fn genedle_hint(
+    session: Session,
+    Query(query): Query<StateQuery>,
+) -> Result<Json<HintResult>, ApiError> {
+    let puzzle = requested_puzzle(&session, query.puzzle).await;
+    Ok(Json(_genedle_hint(&session, puzzle).await?))
+}
+
+async fn _genedle_hint(session: &Session, puzzle: u64) -> Result<HintResult, anyhow::Error> {
+    let mut tracked = tracked_puzzles(session).await;
+    let index = track(&mut tracked, puzzle);
+    let progress = &mut tracked[index];
+
+    if progress.is_finished() {
+        return Ok(HintResult::Invalid(InvalidHint::GameOver));
+    }
+
+    let hints = puzzle_hints(puzzle)
+        .await
+        .map_err(|err| anyhow::anyhow!(err))?;
+    if progress.hint_level >= hints.len() {
+        return Ok(HintResult::Invalid(InvalidHint::NoMoreHints));
+    }
+
+    progress.hint_level += 1;
+    let result = HintProgress {
+        hints_remaining: hints.len() - progress.hint_level,
+        hints: hints.into_iter().take(progress.hint_level).collect(),
+    };
+    session.insert(GUESSES_KEY, &tracked).await?;
+
+    Ok(HintResult::Valid(result))
+}
+
+async fn remember_answer(session: &Session, puzzle: u64) -> Result<(), anyhow::Error> {
+    let answer = get_answer(puzzle)
+        .await
+        .map_err(|err| anyhow::anyhow!(err))?;
+    stats::record_answer(player_id(session).await?, &answer)
+}
+
+// None when nothing fitting turned up; an unfiltered puzzle would be labelled with a tier it isn't
+async fn draw_practice_puzzle(session: &Session, options: &PracticeOptions) -> Option<u64> {
+    const MAX_DRAWS: usize = 100;
+
+    let draw = || rand::random_range(schedule::PRACTICE_KEY_MIN..=u64::MAX);
+    if !options.no_repeats && options.difficulty.is_none() {
+        return Some(draw());
+    }
+
+    let history = match player_id(session).await {
+        Ok(player) if options.no_repeats => stats::answer_history(player),
+        _ => BTreeSet::new(),
+    };
+    let tier = options.difficulty.map(Difficulty::tier);
+    let corpus = corpus::corpus().await.ok();
+
+    for _ in 0..MAX_DRAWS {
+        let puzzle = draw();
+        let Ok(answer) = get_answer(puzzle).await else {
+            continue;
+        };
+
+        let fits = tier.is_none_or(|tier| {
+            corpus
+                .and_then(|corpus| corpus.gene(&answer))
+                .is_some_and(|gene| tier.allows(gene))
+        });
+        if fits && !history.contains(&answer) {
+            return Some(puzzle);
+        }
+    }
+
+    None
+}
+
+fn share_code_key(code: &str) -> String {
+    format!("genedle.share.{code}")
+}
+
+pub(crate) fn random_code() -> String {
+    (0..SHARE_CODE_LENGTH)
+        .map(|_| SHARE_CODE_ALPHABET[rand::random_range(0..SHARE_CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+async fn start_practice(
+    session: &Session,
+    puzzle: u64,
+    difficulty: Option<Difficulty>,
+) -> Result<PracticeGame, anyhow::Error> {
+    let code = loop {
+        let code = random_code();
+        if storage()
+            .get::<SharedPractice>(&share_code_key(&code))
+            .is_none()
+        {
+            break code;
+        }
+    };
+
+    let shared = match difficulty {
+        Some(difficulty) => SharedPractice::Tiered { puzzle, difficulty },
+        None => SharedPractice::Puzzle(puzzle),
+    };
+    storage().insert_with_ttl(
+        &share_code_key(&code),
+        &shared,
+        chrono::Duration::hours(SHARE_CODE_TTL_HOURS),
+    )?;
+
+    let game = PracticeGame::new(puzzle, code, difficulty);
+    session.insert(PRACTICE_KEY, &game).await?;
+    let _ = remember_answer(session, puzzle).await;
+
+    Ok(game)
+}
+
+pub async fn genedle_practice(
+    session: Session,
+    Query(options): Query<PracticeOptions>,
+) -> Json<Option<PracticeGame>> {
+    let Some(puzzle) = draw_practice_puzzle(&session, &options).await else {
+        return Json(None);
+    };
+    Json(
+        start_practice(&session, puzzle, options.difficulty)
+            .await
+            .ok(),
+    )
+}
+
+pub async fn redeem_practice(
+    session: Session,
+    Path(code): Path<String>,
+) -> Json<Option<PracticeGame>> {
+    let code = code.to_ascii_uppercase();
+
+    let shared = storage().get::<SharedPractice>(&share_code_key(&code));
+    let (puzzle, difficulty) = match shared {
+        Some(SharedPractice::Puzzle(puzzle)) => (puzzle, None),
+        Some(SharedPractice::Tiered { puzzle, difficulty }) => (puzzle, Some(difficulty)),
+        None => return Json(None),
+    };
+
+    let game = PracticeGame::new(puzzle, code, difficulty);
+    let _ = remember_answer(&session, puzzle).await;
+    Json(session.insert(PRACTICE_KEY, &game).await.ok().map(|_| game))
+}
+
+async fn init_weekly(session: &Session) -> Result<WeeklyGame, anyhow::Error> {
+    let week = schedule::this_week();
+
+    match session.get::<WeeklyGame>(WEEKLY_KEY).await? {
+        Some(game) if game.week == week => Ok(game),
+        _ => {
+            let game = WeeklyGame {
+                week,
+                started_at: chrono::Utc::now().timestamp(),
+                ..Default::default()
+            };
+            session.insert(WEEKLY_KEY, &game).await?;
+            stats::record_participation("genedle-weekly", week)?;
+
+            Ok(game)
+        }
+    }
+}
+
+pub async fn genedle_weekly(session: Session) -> Json<String> {
+    match init_weekly(&session).await {
+        Ok(game) => Json(game.week.to_string()),
         Err(err) => Json(format!("Error initializing word: {err}")),
     }
 }
+
+pub async fn weekly_guess(
+    session: Session,
+    StrictJson(guess): StrictJson<WeeklyGuess>,
+) -> Result<Json<GuessResult>, ApiError> {
+    respond(_weekly_guess(&session, guess).await?)
+}
+
+async fn _weekly_guess(
+    session: &Session,
+    mut guess: WeeklyGuess,
+) -> Result<GuessResult, anyhow::Error> {
+    guess.word = normalize_word(&guess.word);
+    let mut game = init_weekly(session).await?;
+    if game.finished {
+        return Ok(GuessResult::Invalid(InvalidGuess::GameOver));
+    }
+
+    let word = get_weekly_word(game.week)
+        .await
+        .map_err(|err| anyhow::anyhow!(err))?
+        .chars()
+        .collect::<Vec<_>>();
+
+    // the weekly challenge is always played in hard mode; a game started before its words were
+    // kept can't be checked against them
+    if let Some(reason) = check_length(&guess.word, word.len()) {
+        return Ok(GuessResult::Invalid(reason));
+    }
+    if game.words.len() == game.guesses.len() {
+        let previous: Vec<Vec<char>> = game
+            .words
+            .iter()
+            .map(|word| word.chars().collect())
+            .collect();
+        let hints = previous
+            .iter()
+            .map(Vec::as_slice)
+            .zip(game.guesses.iter().map(|scored| scored.result.as_slice()));
+        if let Some(reason) = check_hints(&guess.word, hints) {
+            return Ok(GuessResult::Invalid(reason));
+        }
+    }
+    if !in_corpus(guess.word.iter().collect())
+        .await
+        .map_err(|err| anyhow::anyhow!(err))?
+    {
+        return Ok(GuessResult::Invalid(InvalidGuess::NotInCorpus));
+    }
+
+    let scored = score_guess(&guess.word, &word);
+    game.guesses.push(scored.clone());
+    game.words.push(guess.word.iter().collect());
+    game.finished = scored.is_correct || game.guesses.len() >= WEEKLY_MAX_GUESSES;
+    if game.finished {
+        game.finished_at = Some(chrono::Utc::now().timestamp());
+    }
+    session.insert(WEEKLY_KEY, &game).await?;
+
+    if game.finished {
+        let player = player_id(session).await?;
+        let guesses = scored.is_correct.then_some(game.guesses.len());
+        stats::record_weekly(player, game.week, guesses)?;
+
+        // the weekly challenge has always been ranked, so solves go on the board straight away
+        if let Some(guesses) = guesses {
+            let seconds = game.finished_at.unwrap_or_default() - game.started_at;
+            // an existing entry just means the solve was already ranked
+            let _ = leaderboard::submit(Board::Weekly, game.week, player, guesses, seconds)?;
+        }
+    }
+
+    Ok(GuessResult::Valid(scored))
+}
+
+pub(crate) async fn finished_weekly(
+    session: &Session,
+) -> Result<Option<FinishedGame>, anyhow::Error> {
+    let game = init_weekly(session).await?;
+
+    Ok(game.finished_at.map(|finished_at| FinishedGame {
+        puzzle: game.week,
+        guesses: game
+            .guesses
+            .last()
+            .is_some_and(|guess| guess.is_correct)
+            .then_some(game.guesses.len()),
+        seconds: finished_at - game.started_at,
+    }))
+}
+
+pub(crate) async fn weekly_progress(session: &Session) -> GameProgress {
+    session
+        .get::<WeeklyGame>(WEEKLY_KEY)
+        .await
+        .ok()
+        .flatten()
+        .filter(|game| game.week == schedule::this_week())
+        .map(|game| GameProgress {
+            started: !game.guesses.is_empty(),
+            finished: game.finished,
+        })
+        .unwrap_or_default()
+}
+
+pub async fn weekly_stats(session: Session) -> Json<Option<WeeklyStats>> {
+    Json(player_id(&session).await.ok().map(|player| {
+        let mut stats = stats::weekly_stats(player);
+        stats.streak = stats.streak.as_of(schedule::this_week());
+        stats
+    }))
+}
+
+fn push_grid<'a>(share: &mut String, guesses: impl IntoIterator<Item = &'a ValidGuess>) {
+    for guess in guesses {
+        share.push('\n');
+        share.extend(guess.result.iter().map(|feedback| feedback.emoji()));
+    }
+}
+
+// defaults to the session's daily puzzle, like the state endpoint; unfinished games share nothing
+pub async fn genedle_share(session: Session, Query(query): Query<StateQuery>) -> Json<String> {
+    let puzzle = requested_puzzle(&session, query.puzzle).await;
+    let Some(progress) = tracked_puzzles(&session)
+        .await
+        .into_iter()
+        .find(|progress| progress.puzzle == puzzle && progress.is_finished())
+    else {
+        return Json(String::new());
+    };
+
+    let score = if progress.solved {
+        progress.spent().to_string()
+    } else {
+        "X".to_string()
+    };
+    let title = if schedule::is_daily(puzzle) {
+        schedule::numbered("Genedle", schedule::puzzle_number(puzzle))
+    } else {
+        "Genedle Practice".to_string()
+    };
+
+    let mut share = format!("{title} {score}/{}", config().max_guesses);
+    if progress.hint_level > 0 {
+        share.push_str(&format!(" 💡{}", progress.hint_level));
+    }
+    share.push('\n');
+    push_grid(
+        &mut share,
+        progress.guesses.iter().map(|recorded| &recorded.feedback),
+    );
+
+    Json(share)
+}
+
+pub async fn weekly_share(session: Session) -> Json<String> {
+    let game = match init_weekly(&session).await {
+        Ok(game) if game.finished => game,
+        Ok(_) => return Json(String::new()),
+        Err(err) => return Json(format!("Error loading game: {err}")),
+    };
+
+    let solved = game.guesses.last().is_some_and(|guess| guess.is_correct);
+    let score = if solved {
+        game.guesses.len().to_string()
+    } else {
+        "X".to_string()
+    };
+
+    let title = schedule::numbered("Genedle Weekly", schedule::week_number(game.week));
+    let mut share = format!("{title} {score}/{WEEKLY_MAX_GUESSES}*\n");
+    push_grid(&mut share, &game.guesses);
+
+    Json(share)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::corpus::Gene;
+    use crate::games::genedle::{Difficulty, SharedPractice, TRACKED_PUZZLES, track};
+    use crate::schedule;
+
+    fn gene(hgnc_id: u32, symbol: &str) -> Gene {
+        Gene {
+            hgnc_id: Some(hgnc_id),
+            symbol: symbol.to_string(),
+            location: String::new(),
+            aliases: Vec::new(),
+            previous_symbols: Vec::new(),
+            status: "Approved".to_string(),
+            locus_group: "protein-coding gene".to_string(),
+            locus_type: "gene with protein product".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_difficulty_tier() {
+        let easy = Difficulty::Easy.tier();
+        assert!(easy.allows(&gene(3236, "EGFR")));
+        assert!(!easy.allows(&gene(11998, "TP53")));
+        assert!(!easy.allows(&gene(4000, "TP53")));
+
+        let medium = Difficulty::Medium.tier();
+        assert!(medium.allows(&gene(11998, "TP53")));
+        assert!(!medium.allows(&gene(52000, "TP53")));
+        assert!(!medium.allows(&gene(7553, "MYC")));
+    }
+
+    #[test]
+    fn test_shared_practice() {
+        assert_eq!(
+            serde_json::from_str::<SharedPractice>("4294967296").unwrap(),
+            SharedPractice::Puzzle(4294967296)
+        );
+        assert_eq!(
+            serde_json::from_str::<SharedPractice>(
+                r#"{"puzzle": 4294967296, "difficulty": "hard"}"#
+            )
+            .unwrap(),
+            SharedPractice::Tiered {
+                puzzle: 4294967296,
+                difficulty: Difficulty::Hard
+            }
+        );
+    }
+
+    #[test]
+    fn test_track() {
+        let today = schedule::today();
+        let mut tracked = Vec::new();
+        track(&mut tracked, today);
+        for round in 0..TRACKED_PUZZLES as u64 * 2 {
+            track(&mut tracked, schedule::PRACTICE_KEY_MIN + round);
+        }
+
+        assert_eq!(tracked.len(), TRACKED_PUZZLES);
+        assert_eq!(tracked[0].puzzle, today);
+        assert_eq!(track(&mut tracked, today), 0);
+        // the newest practice rounds survive, the oldest were evicted
+        let puzzles: Vec<u64> = tracked.iter().map(|p| p.puzzle).collect();
+        assert_eq!(
+            puzzles[1],
+            schedule::PRACTICE_KEY_MIN + TRACKED_PUZZLES as u64 + 1
+        );
+
+        // a board full of archive days, with today's the oldest, still keeps today's guesses
+        let mut tracked = Vec::new();
+        track(&mut tracked, today);
+        tracked[0].started_at = 1;
+        for day in 1..=TRACKED_PUZZLES as u64 {
+            track(&mut tracked, today - day);
+        }
+        assert_eq!(tracked.len(), TRACKED_PUZZLES);
+        assert_eq!(tracked[0].puzzle, today);
+        assert_eq!(tracked[0].started_at, 1);
+        // the oldest archive day made room instead
+        assert_eq!(tracked[1].puzzle, today - 2);
+    }
+}
@@ -1,3 +1,127 @@
+use crate::schedule;
+use crate::stats::{
+    self, ArchiveRecord, CrossoverRecord, GenectionsRecord, GenedleRecord, SpellingGeneRecord,
+    WeeklyStats,
+};
+use axum::Json;
+use serde::Serialize;
+use tower_sessions::Session;
+
+pub mod crossover;
 pub mod genections;
 pub mod genedle;
+pub mod packs;
+pub mod race;
 pub mod spelling_gene;
+pub mod tutorial;
+
+pub(crate) const PLAYER_KEY: &str = "player.id";
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct PlayerStats {
+    pub genedle: GenedleRecord,
+    pub genedle_archive: ArchiveRecord,
+    pub genedle_weekly: WeeklyStats,
+    pub genections: GenectionsRecord,
+    pub spelling_gene: SpellingGeneRecord,
+    pub crossover: CrossoverRecord,
+}
+
+// started means a move has been made, not just that the page was opened
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct GameProgress {
+    pub started: bool,
+    pub finished: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct DailyProgress {
+    pub day: u64,
+    pub genedle: GameProgress,
+    pub genedle_weekly: GameProgress,
+    pub genections: GameProgress,
+    pub spelling_gene: GameProgress,
+    pub crossover: GameProgress,
+}
+
+pub(crate) async fn player_id(session: &Session) -> Result<u64, anyhow::Error> {
+    match session.get::<u64>(PLAYER_KEY).await? {
+        Some(player) => Ok(player),
+        None => {
+            let player = rand::random();
+            session.insert(PLAYER_KEY, player).await?;
+
+            Ok(player)
+        }
+    }
+}
+
+// signing in on a new device swaps in the player id the account's stats are kept under
+pub(crate) async fn adopt_player(session: &Session, player: u64) -> Result<(), anyhow::Error> {
+    session.insert(PLAYER_KEY, player).await?;
+
+    Ok(())
+}
+
+pub(crate) fn stats_of(player: u64) -> PlayerStats {
+    let mut genedle = stats::genedle_record(player);
+    genedle.streak = genedle.streak_as_of(schedule::today());
+
+    let genedle_archive = stats::archive_record(player);
+
+    let mut genedle_weekly = stats::weekly_stats(player);
+    genedle_weekly.streak = genedle_weekly.streak.as_of(schedule::this_week());
+
+    let mut genections = stats::genections_record(player);
+    genections.streak = genections.streak.as_of(schedule::today());
+
+    let mut spelling_gene = stats::spelling_gene_record(player);
+    spelling_gene.streak = spelling_gene.streak.as_of(spelling_gene::daily_seed());
+
+    PlayerStats {
+        genedle,
+        genedle_archive,
+        genedle_weekly,
+        genections,
+        spelling_gene,
+        crossover: stats::crossover_record(player),
+    }
+}
+
+pub async fn player_stats(session: Session) -> Json<Option<PlayerStats>> {
+    Json(player_id(&session).await.ok().map(stats_of))
+}
+
+// drawn once and then cached or stored, so the first player of the day doesn't wait on genenames.org
+async fn warm(day: u64) {
+    if let Err(err) = crate::api::genedle::get_word(day).await {
+        tracing::error!("Failed to pre-generate Genedle for day {day}: {err}");
+    }
+    if let Err(err) = genections::load_board(day).await {
+        tracing::error!("Failed to pre-generate Genections for day {day}: {err}");
+    }
+    let seed = spelling_gene::daily_seed();
+    if let Err(err) = spelling_gene::load_game(seed).await {
+        tracing::error!("Failed to pre-generate Spelling Gene {seed}: {err}");
+    }
+}
+
+// runs at startup and again each time the day rolls over in GENEDLE_TIMEZONE
+pub async fn pregenerate() {
+    loop {
+        warm(schedule::today()).await;
+        tokio::time::sleep(schedule::until_rollover()).await;
+    }
+}
+
+// read-only, so checking progress never starts a game or counts as participation
+pub async fn daily_progress(session: Session) -> Json<DailyProgress> {
+    Json(DailyProgress {
+        day: schedule::today(),
+        genedle: genedle::daily_progress(&session).await,
+        genedle_weekly: genedle::weekly_progress(&session).await,
+        genections: genections::daily_progress(&session).await,
+        spelling_gene: spelling_gene::daily_progress(&session).await,
+        crossover: crossover::daily_progress(&session).await,
+    })
+}
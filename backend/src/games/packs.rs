@@ -0,0 +1,140 @@
+use crate::api::genedle::{GuessResult, InvalidGuess, check_length, get_pack_word, score_guess};
+use crate::extract::StrictJson;
+use crate::games::player_id;
+use crate::stats;
+use crate::storage::storage;
+use axum::Json;
+use axum::extract::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tower_sessions::Session;
+
+const PUZZLES_PER_PACK: u64 = 10;
+const CHROMOSOMES: [&str; 24] = [
+    "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "13", "14", "15", "16", "17",
+    "18", "19", "20", "21", "22", "X", "Y",
+];
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Unlock {
+    Free,
+    QueenGenes(usize),
+    WeeklyWins(usize),
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct Pack {
+    pub chromosome: &'static str,
+    pub name: String,
+    pub puzzles: u64,
+    pub unlock: Unlock,
+    pub unlocked: bool,
+    pub solved: BTreeSet<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PackGuess {
+    pub word: Vec<char>,
+}
+
+fn unlock_requirement(chromosome: &str) -> Unlock {
+    match chromosome {
+        "X" => Unlock::QueenGenes(1),
+        "Y" => Unlock::WeeklyWins(3),
+        _ => Unlock::Free,
+    }
+}
+
+fn is_unlocked(player: u64, unlock: Unlock) -> bool {
+    match unlock {
+        Unlock::Free => true,
+        Unlock::QueenGenes(count) => stats::spelling_gene_record(player).queen_genes >= count,
+        Unlock::WeeklyWins(count) => stats::weekly_stats(player).won >= count,
+    }
+}
+
+fn progress_key(player: u64) -> String {
+    format!("packs.genedle.{player:016x}")
+}
+
+fn progress(player: u64) -> BTreeMap<String, BTreeSet<u64>> {
+    storage().get(&progress_key(player)).unwrap_or_default()
+}
+
+pub async fn packs(session: Session) -> Json<Vec<Pack>> {
+    let Ok(player) = player_id(&session).await else {
+        return Json(Vec::new());
+    };
+    let mut progress = progress(player);
+
+    Json(
+        CHROMOSOMES
+            .iter()
+            .map(|&chromosome| {
+                let unlock = unlock_requirement(chromosome);
+
+                Pack {
+                    chromosome,
+                    name: format!("Chromosome {chromosome} pack"),
+                    puzzles: PUZZLES_PER_PACK,
+                    unlock,
+                    unlocked: is_unlocked(player, unlock),
+                    solved: progress.remove(chromosome).unwrap_or_default(),
+                }
+            })
+            .collect(),
+    )
+}
+
+pub async fn guess(
+    session: Session,
+    Path((chromosome, index)): Path<(String, u64)>,
+    StrictJson(guess): StrictJson<PackGuess>,
+) -> Json<GuessResult> {
+    match _guess(&session, chromosome, index, guess).await {
+        Ok(result) => Json(result),
+        Err(err) => Json(GuessResult::Invalid(InvalidGuess::InternalError(
+            err.to_string(),
+        ))),
+    }
+}
+
+async fn _guess(
+    session: &Session,
+    chromosome: String,
+    index: u64,
+    guess: PackGuess,
+) -> Result<GuessResult, anyhow::Error> {
+    let player = player_id(session).await?;
+
+    if !CHROMOSOMES.contains(&chromosome.as_str()) || index >= PUZZLES_PER_PACK {
+        anyhow::bail!("Unknown puzzle pack");
+    }
+    if !is_unlocked(player, unlock_requirement(&chromosome)) {
+        anyhow::bail!("Puzzle pack is locked");
+    }
+
+    let word = get_pack_word(chromosome.clone(), index)
+        .await
+        .map_err(|err| anyhow::anyhow!(err))?
+        .chars()
+        .collect::<Vec<_>>();
+
+    if let Some(reason) = check_length(&guess.word, word.len()) {
+        return Ok(GuessResult::Invalid(reason));
+    }
+
+    let scored = score_guess(&guess.word, &word);
+    if scored.is_correct {
+        storage().update(
+            &progress_key(player),
+            |progress: &mut BTreeMap<String, BTreeSet<u64>>| {
+                progress.entry(chromosome).or_default().insert(index);
+            },
+        )?;
+    }
+
+    Ok(GuessResult::Valid(scored))
+}
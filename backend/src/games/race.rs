@@ -0,0 +1,447 @@
+use crate::api::genedle::{
+    GuessResult, InvalidGuess, check_hints, check_length, get_word, in_corpus, score_guess,
+};
+use crate::error::ApiError;
+use crate::games::genedle::random_code;
+use crate::schedule;
+use axum::Json;
+use axum::extract::Path;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::{IntoResponse, Response};
+use genedle_core::feedback::LetterFeedback;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use tokio::sync::broadcast;
+
+const ROOM_CAPACITY: usize = 2;
+const RACE_MAX_GUESSES: usize = 6;
+const RECENT_EMOTES: usize = 8;
+const IDLE_ROOM_TTL_SECONDS: i64 = 60 * 60;
+
+// a fixed set keeps the channel free of anything that would need moderating
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Emote {
+    Wave,
+    ThumbsUp,
+    Laugh,
+    Wow,
+    Sweat,
+    Clap,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct EmoteEvent {
+    pub player: usize,
+    pub emote: Emote,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+enum ClientMessage {
+    Guess { word: Vec<char> },
+    Emote(Emote),
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum RaceEvent {
+    Joined {
+        player: usize,
+        num_letters: usize,
+        emotes: Vec<EmoteEvent>,
+        spectators: usize,
+    },
+    Spectating {
+        players: Vec<PlayerProgress>,
+        emotes: Vec<EmoteEvent>,
+        spectators: usize,
+    },
+    Full,
+    // the code is wrong, or the room emptied and was cleared away
+    NotFound,
+    Guess(GuessResult),
+    Progress(PlayerProgress),
+    Emote(EmoteEvent),
+    Spectators {
+        count: usize,
+    },
+}
+
+// spectators only ever see colours, never the letters behind them
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct PlayerProgress {
+    pub player: usize,
+    pub grid: Vec<Vec<LetterFeedback>>,
+    pub finished: bool,
+    pub elapsed_ms: i64,
+}
+
+// only the code goes out; a puzzle token would let a racer play the answer out of the session game
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct RaceRoom {
+    pub code: String,
+}
+
+#[derive(Debug, Default)]
+struct PlayerState {
+    grid: Vec<Vec<LetterFeedback>>,
+    // kept server-side only; the other player just sees the grid
+    words: Vec<Vec<char>>,
+    finished: bool,
+    elapsed_ms: i64,
+}
+
+impl PlayerState {
+    fn progress(&self, player: usize) -> PlayerProgress {
+        PlayerProgress {
+            player,
+            grid: self.grid.clone(),
+            finished: self.finished,
+            elapsed_ms: self.elapsed_ms,
+        }
+    }
+}
+
+struct Room {
+    puzzle: u64,
+    created_at_ms: i64,
+    started_at_ms: Option<i64>,
+    players: Vec<PlayerState>,
+    connections: usize,
+    spectators: usize,
+    emotes: VecDeque<EmoteEvent>,
+    events: broadcast::Sender<RaceEvent>,
+}
+
+static ROOMS: LazyLock<Mutex<HashMap<String, Room>>> = LazyLock::new(Default::default);
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+pub async fn create_room() -> Json<RaceRoom> {
+    let mut rooms = ROOMS.lock().unwrap();
+    rooms.retain(|_, room| {
+        room.connections > 0 || now_ms() - room.created_at_ms < IDLE_ROOM_TTL_SECONDS * 1000
+    });
+
+    let code = loop {
+        let code = random_code();
+        if !rooms.contains_key(&code) {
+            break code;
+        }
+    };
+    let puzzle = rand::random_range(schedule::PRACTICE_KEY_MIN..=u64::MAX);
+
+    rooms.insert(
+        code.clone(),
+        Room {
+            puzzle,
+            created_at_ms: now_ms(),
+            started_at_ms: None,
+            players: Vec::with_capacity(ROOM_CAPACITY),
+            connections: 0,
+            spectators: 0,
+            emotes: VecDeque::with_capacity(RECENT_EMOTES),
+            events: broadcast::channel(32).0,
+        },
+    );
+
+    Json(RaceRoom { code })
+}
+
+fn room_exists(code: &str) -> bool {
+    ROOMS.lock().unwrap().contains_key(code)
+}
+
+fn no_such_room() -> Response {
+    ApiError::NotFound("No such race room".to_string()).into_response()
+}
+
+pub async fn join_room(Path(code): Path<String>, upgrade: WebSocketUpgrade) -> Response {
+    let code = code.to_ascii_uppercase();
+    if !room_exists(&code) {
+        return no_such_room();
+    }
+    upgrade.on_upgrade(move |socket| play(socket, code))
+}
+
+pub async fn spectate_room(Path(code): Path<String>, upgrade: WebSocketUpgrade) -> Response {
+    let code = code.to_ascii_uppercase();
+    if !room_exists(&code) {
+        return no_such_room();
+    }
+    upgrade.on_upgrade(move |socket| spectate(socket, code))
+}
+
+async fn send(socket: &mut WebSocket, event: &RaceEvent) -> bool {
+    match serde_json::to_string(event) {
+        Ok(json) => socket.send(Message::Text(json.into())).await.is_ok(),
+        Err(_) => false,
+    }
+}
+
+async fn play(mut socket: WebSocket, code: String) {
+    // the room can still go between the upgrade being accepted and the socket opening
+    let joined = {
+        let mut rooms = ROOMS.lock().unwrap();
+        rooms.get_mut(&code).map(|room| {
+            (room.players.len() < ROOM_CAPACITY).then(|| {
+                room.players.push(PlayerState::default());
+                room.connections += 1;
+                if room.players.len() == ROOM_CAPACITY {
+                    room.started_at_ms = Some(now_ms());
+                }
+                (
+                    room.players.len() - 1,
+                    room.puzzle,
+                    room.emotes.iter().copied().collect::<Vec<_>>(),
+                    room.spectators,
+                    room.events.subscribe(),
+                )
+            })
+        })
+    };
+    let (player, puzzle, emotes, spectators, mut events) = match joined {
+        Some(Some(joined)) => joined,
+        Some(None) => {
+            send(&mut socket, &RaceEvent::Full).await;
+            return;
+        }
+        None => {
+            send(&mut socket, &RaceEvent::NotFound).await;
+            return;
+        }
+    };
+
+    if let Ok(word) = get_word(puzzle).await {
+        let word: Vec<char> = word.chars().collect();
+        let joined = RaceEvent::Joined {
+            player,
+            num_letters: word.len(),
+            emotes,
+            spectators,
+        };
+
+        if send(&mut socket, &joined).await {
+            play_connected(&mut socket, &code, player, &word, &mut events).await;
+        }
+    }
+
+    leave(&code, false);
+}
+
+async fn spectate(mut socket: WebSocket, code: String) {
+    let joined = {
+        let mut rooms = ROOMS.lock().unwrap();
+        rooms.get_mut(&code).map(|room| {
+            room.connections += 1;
+            room.spectators += 1;
+            let _ = room.events.send(RaceEvent::Spectators {
+                count: room.spectators,
+            });
+
+            let spectating = RaceEvent::Spectating {
+                players: room
+                    .players
+                    .iter()
+                    .enumerate()
+                    .map(|(player, state)| state.progress(player))
+                    .collect(),
+                emotes: room.emotes.iter().copied().collect(),
+                spectators: room.spectators,
+            };
+            (spectating, room.events.subscribe())
+        })
+    };
+    let Some((spectating, mut events)) = joined else {
+        send(&mut socket, &RaceEvent::NotFound).await;
+        return;
+    };
+
+    if send(&mut socket, &spectating).await {
+        loop {
+            tokio::select! {
+                // spectators can't guess or emote, so incoming messages only matter when closing
+                message = socket.recv() => {
+                    if !matches!(message, Some(Ok(message)) if !matches!(message, Message::Close(_))) {
+                        break;
+                    }
+                }
+                event = events.recv() => {
+                    match event {
+                        Ok(event) => {
+                            if !send(&mut socket, &event).await {
+                                break;
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            }
+        }
+    }
+
+    leave(&code, true);
+}
+
+fn leave(code: &str, spectator: bool) {
+    let mut rooms = ROOMS.lock().unwrap();
+    if let Some(room) = rooms.get_mut(code) {
+        room.connections -= 1;
+        if spectator {
+            room.spectators -= 1;
+            let _ = room.events.send(RaceEvent::Spectators {
+                count: room.spectators,
+            });
+        }
+
+        if room.connections == 0 {
+            rooms.remove(code);
+        }
+    }
+}
+
+async fn play_connected(
+    socket: &mut WebSocket,
+    code: &str,
+    player: usize,
+    word: &[char],
+    events: &mut broadcast::Receiver<RaceEvent>,
+) {
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let Some(Ok(message)) = message else {
+                    return;
+                };
+                let Message::Text(text) = message else {
+                    continue;
+                };
+                let Ok(message) = serde_json::from_str::<ClientMessage>(&text) else {
+                    continue;
+                };
+
+                match message {
+                    ClientMessage::Guess { word: guess } => {
+                        let result = race_guess(code, player, &guess, word).await;
+                        if !send(socket, &RaceEvent::Guess(result)).await {
+                            return;
+                        }
+                    }
+                    ClientMessage::Emote(emote) => relay_emote(code, player, emote),
+                }
+            }
+            event = events.recv() => {
+                match event {
+                    Ok(event) => {
+                        if !send(socket, &event).await {
+                            return;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        }
+    }
+}
+
+fn relay_emote(code: &str, player: usize, emote: Emote) {
+    let mut rooms = ROOMS.lock().unwrap();
+    if let Some(room) = rooms.get_mut(code) {
+        let event = EmoteEvent { player, emote };
+        if room.emotes.len() == RECENT_EMOTES {
+            room.emotes.pop_front();
+        }
+        room.emotes.push_back(event);
+        let _ = room.events.send(RaceEvent::Emote(event));
+    }
+}
+
+async fn race_guess(code: &str, player: usize, guess: &[char], word: &[char]) -> GuessResult {
+    // races are played in hard mode so neither player can brute force the grid
+    let rejected = match ROOMS.lock().unwrap().get(code) {
+        // the clock only starts with both players in, so an early solve would post no time at all
+        Some(room) if room.started_at_ms.is_none() => Some(InvalidGuess::NotStarted),
+        Some(room) if !room.players[player].finished => {
+            let state = &room.players[player];
+            let previous = state
+                .words
+                .iter()
+                .map(Vec::as_slice)
+                .zip(state.grid.iter().map(Vec::as_slice));
+            check_length(guess, word.len()).or_else(|| check_hints(guess, previous))
+        }
+        _ => Some(InvalidGuess::GameOver),
+    };
+    if let Some(reason) = rejected {
+        return GuessResult::Invalid(reason);
+    }
+    match in_corpus(guess.iter().collect()).await {
+        Ok(true) => (),
+        Ok(false) => return GuessResult::Invalid(InvalidGuess::NotInCorpus),
+        Err(err) => return GuessResult::Invalid(InvalidGuess::InternalError(err)),
+    }
+
+    let scored = score_guess(guess, word);
+
+    let mut rooms = ROOMS.lock().unwrap();
+    if let Some(room) = rooms.get_mut(code) {
+        let state = &mut room.players[player];
+        state.grid.push(scored.result.clone());
+        state.words.push(guess.to_vec());
+        state.finished = scored.is_correct || state.grid.len() >= RACE_MAX_GUESSES;
+        state.elapsed_ms = room
+            .started_at_ms
+            .map_or(0, |started_at| now_ms() - started_at);
+
+        let _ = room
+            .events
+            .send(RaceEvent::Progress(state.progress(player)));
+    }
+
+    GuessResult::Valid(scored)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::genedle::{GuessResult, InvalidGuess};
+    use crate::games::race::{PlayerState, ROOMS, RaceRoom, create_room, now_ms, race_guess};
+    use axum::Json;
+
+    #[tokio::test]
+    async fn test_race_not_started() {
+        let Json(RaceRoom { code }) = create_room().await;
+        ROOMS
+            .lock()
+            .unwrap()
+            .get_mut(&code)
+            .unwrap()
+            .players
+            .push(PlayerState::default());
+
+        let word: Vec<char> = "TP53".chars().collect();
+        assert_eq!(
+            race_guess(&code, 0, &word, &word).await,
+            GuessResult::Invalid(InvalidGuess::NotStarted)
+        );
+
+        // the same guess counts once the second player is in
+        crate::corpus::init_sample().await;
+        {
+            let mut rooms = ROOMS.lock().unwrap();
+            let room = rooms.get_mut(&code).unwrap();
+            room.players.push(PlayerState::default());
+            room.started_at_ms = Some(now_ms());
+        }
+        assert!(matches!(
+            race_guess(&code, 0, &word, &word).await,
+            GuessResult::Valid(guess) if guess.is_correct
+        ));
+        assert!(ROOMS.lock().unwrap()[&code].players[0].finished);
+    }
+}
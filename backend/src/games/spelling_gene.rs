@@ -1,6 +1,250 @@
+use crate::api::spelling_gene::{
+    SpellingGeneGame, SpellingGeneGuessResult, SpellingGeneMetadata, fixed_game, generate_game,
+    judge_guess,
+};
+use crate::config::config;
+use crate::curation::{self, SpecialInfo};
+use crate::error::{ApiError, respond};
+use crate::extract::StrictJson;
+use crate::games::{GameProgress, player_id};
+use crate::puzzle_ids::PuzzleId;
+use crate::schedule;
+use crate::stats::{self, SpellingGeneRecord};
 use axum::Json;
+use genedle_core::spelling_gene::{
+    LetterUsage, PrefixUsage, ScoringPreset, letter_usage, prefix_usage, total_score,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use tower_sessions::Session;
 
-pub async fn spelling_gene(_session: Session) -> Json<String> {
-    Json(String::new())
+pub(crate) const GAME_KEY: &str = "spelling_gene.game";
+pub(crate) const MIN_LENGTH: usize = 4;
+pub(crate) const MIN_WORDS: usize = 10;
+pub(crate) const NUM_LETTERS: u8 = 7;
+
+// the frontend seeds the daily puzzle with days since the unix epoch
+pub(crate) const UNIX_EPOCH_DAYS_FROM_CE: u64 = 719_163;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+struct SpellingGeneProgress {
+    seed: u64,
+    found: BTreeSet<String>,
+    #[serde(default)]
+    scoring: ScoringPreset,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct SpellingGeneSession {
+    pub seed: PuzzleId,
+    #[serde(flatten)]
+    pub metadata: SpellingGeneMetadata,
+    pub found: BTreeSet<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub special: Option<SpecialInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SpellingGeneGuess {
+    pub guess: String,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct SpellingGeneStats {
+    pub found: usize,
+    pub total: usize,
+    pub queen_gene: bool,
+    pub scoring: ScoringPreset,
+    pub score: u32,
+    pub max_score: u32,
+    // scores above already include it
+    pub multiplier: u32,
+    pub letters: Vec<LetterUsage>,
+    #[serde(flatten)]
+    pub record: SpellingGeneRecord,
+}
+
+// what's left to find, without naming any symbol, like the Bee's hints page
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct SpellingGeneHints {
+    pub letters: Vec<LetterUsage>,
+    pub two_letter_list: Vec<PrefixUsage>,
+}
+
+pub(crate) fn daily_seed() -> u64 {
+    schedule::today() - UNIX_EPOCH_DAYS_FROM_CE
+}
+
+async fn init_progress(session: &Session) -> Result<SpellingGeneProgress, anyhow::Error> {
+    let seed = daily_seed();
+
+    match session.get::<SpellingGeneProgress>(GAME_KEY).await? {
+        Some(progress) if progress.seed == seed => Ok(progress),
+        _ => {
+            let progress = SpellingGeneProgress {
+                seed,
+                scoring: config().spelling_gene_scoring,
+                ..Default::default()
+            };
+            session.insert(GAME_KEY, &progress).await?;
+            stats::record_participation("spelling-gene", seed)?;
+
+            Ok(progress)
+        }
+    }
+}
+
+// a special scheduled for the day replaces the generated puzzle
+pub(crate) async fn load_game(seed: u64) -> Result<SpellingGeneGame, anyhow::Error> {
+    match curation::special(seed + UNIX_EPOCH_DAYS_FROM_CE) {
+        Some(special) => {
+            fixed_game(&special.center_letter, &special.outer_letters, MIN_LENGTH).await
+        }
+        None => generate_game(MIN_LENGTH, MIN_WORDS, NUM_LETTERS, seed).await,
+    }
+}
+
+fn multiplier(seed: u64) -> u32 {
+    curation::special(seed + UNIX_EPOCH_DAYS_FROM_CE).map_or(1, |special| special.multiplier)
+}
+
+// there's no losing a spelling gene day, so it's finished once every symbol is found
+pub(crate) async fn daily_progress(session: &Session) -> GameProgress {
+    let Some(progress) = session
+        .get::<SpellingGeneProgress>(GAME_KEY)
+        .await
+        .ok()
+        .flatten()
+        .filter(|progress| progress.seed == daily_seed())
+    else {
+        return GameProgress::default();
+    };
+
+    GameProgress {
+        started: !progress.found.is_empty(),
+        finished: load_game(progress.seed)
+            .await
+            .is_ok_and(|game| progress.found.len() == game.valid_symbols.len()),
+    }
+}
+
+pub async fn spelling_gene(session: Session) -> Json<Option<SpellingGeneSession>> {
+    let Ok(progress) = init_progress(&session).await else {
+        return Json(None);
+    };
+
+    Json(
+        load_game(progress.seed)
+            .await
+            .ok()
+            .map(|game| SpellingGeneSession {
+                seed: PuzzleId(progress.seed),
+                metadata: game.metadata,
+                found: progress.found,
+                special: curation::special(progress.seed + UNIX_EPOCH_DAYS_FROM_CE)
+                    .map(|special| special.info()),
+            }),
+    )
+}
+
+pub async fn guess(
+    session: Session,
+    StrictJson(guess): StrictJson<SpellingGeneGuess>,
+) -> Result<Json<SpellingGeneGuessResult>, ApiError> {
+    respond(_guess(&session, guess).await?)
+}
+
+async fn _guess(
+    session: &Session,
+    guess: SpellingGeneGuess,
+) -> Result<SpellingGeneGuessResult, anyhow::Error> {
+    let mut progress = init_progress(session).await?;
+    let game = load_game(progress.seed).await?;
+
+    let result = judge_guess(&game, MIN_LENGTH, &guess.guess).await;
+    let SpellingGeneGuessResult::Valid { symbol } = &result else {
+        return Ok(result);
+    };
+
+    progress.found.insert(symbol.clone());
+    session.insert(GAME_KEY, &progress).await?;
+
+    let complete = progress.found.len() == game.valid_symbols.len();
+    stats::record_spelling_gene(
+        player_id(session).await?,
+        progress.seed,
+        complete,
+        progress.scoring,
+        total_score(
+            progress.scoring,
+            &progress.found,
+            &game.valid_symbols,
+            &game.aliases,
+        ) * multiplier(progress.seed),
+    )?;
+
+    Ok(result)
+}
+
+fn puzzle_letters(game: &SpellingGeneGame) -> Vec<&'static str> {
+    std::iter::once(game.metadata.center_letter)
+        .chain(game.metadata.outer_letters.iter().copied())
+        .collect()
+}
+
+// counts come from the session's found symbols, so they always match the player's own progress
+pub async fn hints(session: Session) -> Json<Option<SpellingGeneHints>> {
+    let Ok(progress) = init_progress(&session).await else {
+        return Json(None);
+    };
+
+    Json(
+        load_game(progress.seed)
+            .await
+            .ok()
+            .map(|game| SpellingGeneHints {
+                letters: letter_usage(&puzzle_letters(&game), &game.valid_symbols, &progress.found),
+                two_letter_list: prefix_usage(&game.valid_symbols, &progress.found),
+            }),
+    )
+}
+
+pub async fn progress(session: Session) -> Json<Option<SpellingGeneStats>> {
+    let Ok(progress) = init_progress(&session).await else {
+        return Json(None);
+    };
+    let Ok(player) = player_id(&session).await else {
+        return Json(None);
+    };
+
+    Json(load_game(progress.seed).await.ok().map(|game| {
+        let letters = puzzle_letters(&game);
+        let multiplier = multiplier(progress.seed);
+
+        let mut record = stats::spelling_gene_record(player);
+        record.streak = record.streak.as_of(progress.seed);
+
+        SpellingGeneStats {
+            found: progress.found.len(),
+            total: game.valid_symbols.len(),
+            queen_gene: progress.found.len() == game.valid_symbols.len(),
+            scoring: progress.scoring,
+            score: total_score(
+                progress.scoring,
+                &progress.found,
+                &game.valid_symbols,
+                &game.aliases,
+            ) * multiplier,
+            max_score: total_score(
+                progress.scoring,
+                &game.valid_symbols,
+                &game.valid_symbols,
+                &game.aliases,
+            ) * multiplier,
+            multiplier,
+            letters: letter_usage(&letters, &game.valid_symbols, &progress.found),
+            record,
+        }
+    }))
 }
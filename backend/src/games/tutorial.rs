@@ -0,0 +1,234 @@
+use crate::api::genedle::{
+    GuessResult, InvalidGuess, check_length, in_corpus, normalize_word, score_guess,
+};
+use crate::extract::StrictJson;
+use crate::games::genedle::RecordedGuess;
+use axum::Json;
+use genedle_core::feedback::LetterFeedback;
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+
+// kept apart from every other game key, so nothing here reaches stats, streaks or the metrics
+const TUTORIAL_KEY: &str = "genedle.tutorial";
+const TUTORIAL_ANSWER: &str = "TP53";
+const CLUE_AFTER: usize = 3;
+const ANSWER_AFTER: usize = 5;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+struct TutorialGame {
+    guesses: Vec<RecordedGuess>,
+    solved: bool,
+}
+
+// one step per thing worth explaining, in the order a new player runs into them
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TutorialStep {
+    Welcome,
+    Correct,
+    Present,
+    Absent,
+    KeepGoing,
+    Clue,
+    Answer,
+    Solved,
+}
+
+impl TutorialStep {
+    pub fn message(self) -> &'static str {
+        match self {
+            TutorialStep::Welcome => {
+                "Every puzzle is a human gene symbol. This one has four characters, so try any four-character symbol you know."
+            }
+            TutorialStep::Correct => {
+                "Green means that character is in the answer and in the right place. Keep it where it is."
+            }
+            TutorialStep::Present => {
+                "Yellow means that character is in the answer, but somewhere else. Try moving it."
+            }
+            TutorialStep::Absent => {
+                "Grey means that character isn't in the answer at all, so there's no need to use it again."
+            }
+            TutorialStep::KeepGoing => "Use what the colours have told you so far and guess again.",
+            TutorialStep::Clue => {
+                "Here's a clue: the answer is the tumour suppressor known as the guardian of the genome."
+            }
+            TutorialStep::Answer => "The answer is TP53. Enter it to finish the tutorial.",
+            TutorialStep::Solved => {
+                "That's it! Daily puzzles work the same way, but without the guidance."
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct TutorialState {
+    pub length: usize,
+    pub guesses: Vec<RecordedGuess>,
+    pub solved: bool,
+    pub step: TutorialStep,
+    pub message: &'static str,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct TutorialResponse {
+    #[serde(flatten)]
+    pub result: GuessResult,
+    pub tutorial: TutorialState,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TutorialGuess {
+    pub word: Vec<char>,
+}
+
+// each colour is explained once, a guess at a time, in the order it first showed up; after that the
+// player is nudged, then given a clue, then simply told the answer, so the tutorial can't be lost
+fn step_for(guesses: &[RecordedGuess], solved: bool) -> TutorialStep {
+    if solved {
+        return TutorialStep::Solved;
+    }
+
+    let mut explained = Vec::new();
+    let mut step = TutorialStep::Welcome;
+    for made in 1..=guesses.len() {
+        let unexplained = [
+            (LetterFeedback::Correct, TutorialStep::Correct),
+            (LetterFeedback::Present, TutorialStep::Present),
+            (LetterFeedback::Absent, TutorialStep::Absent),
+        ]
+        .into_iter()
+        .find(|(feedback, _)| {
+            !explained.contains(feedback)
+                && guesses[..made]
+                    .iter()
+                    .any(|guess| guess.feedback.result.contains(feedback))
+        });
+
+        step = match unexplained {
+            Some((feedback, step)) => {
+                explained.push(feedback);
+                step
+            }
+            None if made >= ANSWER_AFTER => TutorialStep::Answer,
+            None if made >= CLUE_AFTER => TutorialStep::Clue,
+            None => TutorialStep::KeepGoing,
+        };
+    }
+
+    step
+}
+
+fn tutorial_state(game: TutorialGame) -> TutorialState {
+    let step = step_for(&game.guesses, game.solved);
+
+    TutorialState {
+        length: TUTORIAL_ANSWER.len(),
+        guesses: game.guesses,
+        solved: game.solved,
+        step,
+        message: step.message(),
+    }
+}
+
+async fn tutorial_game(session: &Session) -> TutorialGame {
+    session
+        .get(TUTORIAL_KEY)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+pub async fn tutorial(session: Session) -> Json<TutorialState> {
+    Json(tutorial_state(tutorial_game(&session).await))
+}
+
+pub async fn restart_tutorial(session: Session) -> Json<TutorialState> {
+    let _ = session.remove::<TutorialGame>(TUTORIAL_KEY).await;
+    Json(tutorial_state(TutorialGame::default()))
+}
+
+pub async fn guess(
+    session: Session,
+    StrictJson(guess): StrictJson<TutorialGuess>,
+) -> Json<TutorialResponse> {
+    let mut game = tutorial_game(&session).await;
+    let result = match _guess(&session, &mut game, guess).await {
+        Ok(result) => result,
+        Err(err) => GuessResult::Invalid(InvalidGuess::InternalError(err.to_string())),
+    };
+
+    Json(TutorialResponse {
+        result,
+        tutorial: tutorial_state(game),
+    })
+}
+
+async fn _guess(
+    session: &Session,
+    game: &mut TutorialGame,
+    guess: TutorialGuess,
+) -> Result<GuessResult, anyhow::Error> {
+    if game.solved {
+        return Ok(GuessResult::Invalid(InvalidGuess::GameOver));
+    }
+
+    let word = normalize_word(&guess.word);
+    let answer: Vec<char> = TUTORIAL_ANSWER.chars().collect();
+    if let Some(reason) = check_length(&word, answer.len()) {
+        return Ok(GuessResult::Invalid(reason));
+    }
+    if !in_corpus(word.iter().collect())
+        .await
+        .map_err(|err| anyhow::anyhow!(err))?
+    {
+        return Ok(GuessResult::Invalid(InvalidGuess::NotInCorpus));
+    }
+
+    let scored = score_guess(&word, &answer);
+    game.solved = scored.is_correct;
+    game.guesses.push(RecordedGuess {
+        word: word.iter().collect(),
+        feedback: scored.clone(),
+    });
+    session.insert(TUTORIAL_KEY, &*game).await?;
+
+    Ok(GuessResult::Valid(scored))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::genedle::score_guess;
+    use crate::games::genedle::RecordedGuess;
+    use crate::games::tutorial::{TutorialStep, step_for};
+
+    fn recorded(word: &str) -> RecordedGuess {
+        let word: Vec<char> = word.chars().collect();
+        RecordedGuess {
+            word: word.iter().collect(),
+            feedback: score_guess(&word, &['T', 'P', '5', '3']),
+        }
+    }
+
+    #[test]
+    fn test_step_for() {
+        assert_eq!(step_for(&[], false), TutorialStep::Welcome);
+
+        // TP63 shows green and grey, which are explained a guess at a time
+        let mut guesses = vec![recorded("TP63")];
+        assert_eq!(step_for(&guesses, false), TutorialStep::Correct);
+        guesses.push(recorded("TP63"));
+        assert_eq!(step_for(&guesses, false), TutorialStep::Absent);
+        guesses.push(recorded("PT35"));
+        assert_eq!(step_for(&guesses, false), TutorialStep::Present);
+        guesses.push(recorded("TP63"));
+        assert_eq!(step_for(&guesses, false), TutorialStep::Clue);
+        guesses.push(recorded("TP63"));
+        assert_eq!(step_for(&guesses, false), TutorialStep::Answer);
+
+        guesses.push(recorded("TP53"));
+        assert_eq!(step_for(&guesses, true), TutorialStep::Solved);
+    }
+}
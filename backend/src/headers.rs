@@ -0,0 +1,42 @@
+use crate::config::config;
+use crate::corpus;
+use axum::extract::Request;
+use axum::http::HeaderValue;
+use axum::http::header::{CONTENT_SECURITY_POLICY, REFERRER_POLICY, X_CONTENT_TYPE_OPTIONS};
+use axum::middleware::Next;
+use axum::response::Response;
+
+pub async fn security_headers(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+
+    let policy = format!(
+        "{}; frame-ancestors {}",
+        config().content_security_policy,
+        config().frame_ancestors
+    );
+
+    let headers = response.headers_mut();
+    if let Ok(policy) = HeaderValue::from_str(&policy) {
+        headers.entry(CONTENT_SECURITY_POLICY).or_insert(policy);
+    }
+    headers
+        .entry(X_CONTENT_TYPE_OPTIONS)
+        .or_insert(HeaderValue::from_static("nosniff"));
+    headers
+        .entry(REFERRER_POLICY)
+        .or_insert(HeaderValue::from_static("strict-origin-when-cross-origin"));
+
+    response
+}
+
+// lets clients say so when the games are running on a fallback gene list
+pub async fn degraded_mode(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    if corpus::is_degraded() {
+        response
+            .headers_mut()
+            .insert("x-genedle-degraded", HeaderValue::from_static("true"));
+    }
+
+    response
+}
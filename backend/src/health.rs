@@ -0,0 +1,74 @@
+use crate::corpus;
+use crate::sessions::TrackedStore;
+use crate::upstream::{self, Circuit};
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct Readiness {
+    pub ready: bool,
+    pub corpus_loaded: bool,
+    pub sessions_reachable: bool,
+    pub upstream: Circuit,
+    // running on a fallback gene list, so an unreachable HGNC doesn't hold anything up
+    pub degraded: bool,
+}
+
+impl Readiness {
+    fn check(
+        corpus_loaded: bool,
+        sessions_reachable: bool,
+        upstream: Circuit,
+        degraded: bool,
+    ) -> Self {
+        Readiness {
+            ready: corpus_loaded && sessions_reachable && (upstream != Circuit::Open || degraded),
+            corpus_loaded,
+            sessions_reachable,
+            upstream,
+            degraded,
+        }
+    }
+}
+
+// answers as long as the process is serving at all, so a restart is only for a wedged server
+pub async fn healthz() -> StatusCode {
+    StatusCode::OK
+}
+
+// whether to send players here; no gene list or session store means no game could be played
+pub async fn readyz(State(sessions): State<TrackedStore>) -> (StatusCode, Json<Readiness>) {
+    let readiness = Readiness::check(
+        corpus::is_loaded(),
+        sessions.ping().await.is_ok(),
+        upstream::circuit(),
+        corpus::is_degraded(),
+    );
+
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::health::Readiness;
+    use crate::upstream::Circuit;
+
+    #[test]
+    fn test_readiness() {
+        assert!(Readiness::check(true, true, Circuit::Closed, false).ready);
+        assert!(Readiness::check(true, true, Circuit::HalfOpen, false).ready);
+        assert!(!Readiness::check(false, true, Circuit::Closed, false).ready);
+        assert!(!Readiness::check(true, false, Circuit::Closed, false).ready);
+
+        // HGNC being down only matters when there's no fallback to play from
+        assert!(!Readiness::check(true, true, Circuit::Open, false).ready);
+        assert!(Readiness::check(true, true, Circuit::Open, true).ready);
+    }
+}
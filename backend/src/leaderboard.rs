@@ -0,0 +1,333 @@
+use crate::config::config;
+use crate::games::genedle::{self, FinishedGame};
+use crate::games::player_id;
+use crate::preferences::{self, player_code};
+use crate::schedule;
+use crate::stats::{self, FREEZE_EVERY, GenedleRecord, MAX_FREEZES};
+use crate::storage::storage;
+use axum::Json;
+use axum::extract::Path;
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+
+const STREAK_BOARD_SIZE: usize = 100;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Board {
+    Daily,
+    Weekly,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct LeaderboardEntry {
+    pub player: String,
+    pub guesses: usize,
+    pub seconds: i64,
+    // entries from before submissions were timestamped sort first among their ties
+    #[serde(default)]
+    pub submitted_at: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct RankedEntry {
+    pub rank: usize,
+    #[serde(flatten)]
+    pub entry: LeaderboardEntry,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum SubmitResult {
+    Invalid(InvalidSubmission),
+    Valid(Standing),
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidSubmission {
+    InternalError(String),
+    NotFinished,
+    NotSolved,
+    AlreadySubmitted,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Standing {
+    pub puzzle: u64,
+    pub rank: usize,
+    pub entries: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct StreakEntry {
+    pub rank: usize,
+    pub player: String,
+    pub streak: usize,
+    pub max: usize,
+}
+
+// sent with the board so clients can explain it without duplicating the rules
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct StreakRules {
+    pub timezone: &'static str,
+    pub freeze_every: usize,
+    pub max_freezes: usize,
+    pub counted: &'static str,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct StreakLeaderboard {
+    pub rules: StreakRules,
+    pub entries: Vec<StreakEntry>,
+}
+
+impl Board {
+    fn prefix(self) -> &'static str {
+        match self {
+            Board::Daily => "genedle",
+            Board::Weekly => "genedle-weekly",
+        }
+    }
+
+    fn current_puzzle(self) -> u64 {
+        match self {
+            Board::Daily => schedule::today(),
+            Board::Weekly => schedule::this_week(),
+        }
+    }
+}
+
+fn leaderboard_key(board: Board, puzzle: u64) -> String {
+    format!("leaderboard.{}.{puzzle}", board.prefix())
+}
+
+// fewer guesses win, then the faster solve, then whoever got there first
+fn sort_entries(entries: &mut [LeaderboardEntry]) {
+    entries.sort_by_key(|entry| (entry.guesses, entry.seconds, entry.submitted_at));
+}
+
+fn standing(entries: &[LeaderboardEntry], puzzle: u64, player: &str) -> Option<Standing> {
+    entries
+        .iter()
+        .position(|entry| entry.player == player)
+        .map(|index| Standing {
+            puzzle,
+            rank: index + 1,
+            entries: entries.len(),
+        })
+}
+
+pub fn entries(board: Board, puzzle: u64) -> Vec<LeaderboardEntry> {
+    storage()
+        .get(&leaderboard_key(board, puzzle))
+        .unwrap_or_default()
+}
+
+// a player gets one entry per puzzle; the first submission stands
+pub fn submit(
+    board: Board,
+    puzzle: u64,
+    player: u64,
+    guesses: usize,
+    seconds: i64,
+) -> Result<Result<Standing, InvalidSubmission>, anyhow::Error> {
+    let player = player_code(player);
+    let mut submitted = false;
+
+    let entries = storage().update(
+        &leaderboard_key(board, puzzle),
+        |entries: &mut Vec<LeaderboardEntry>| {
+            if entries.iter().any(|entry| entry.player == player) {
+                return;
+            }
+
+            submitted = true;
+            entries.push(LeaderboardEntry {
+                player: player.clone(),
+                guesses,
+                seconds,
+                submitted_at: chrono::Utc::now().timestamp(),
+            });
+            sort_entries(entries);
+        },
+    )?;
+
+    Ok(match standing(&entries, puzzle, &player) {
+        Some(standing) if submitted => Ok(standing),
+        _ => Err(InvalidSubmission::AlreadySubmitted),
+    })
+}
+
+pub fn rankings(board: Board, puzzle: u64, viewer: Option<&str>) -> Vec<RankedEntry> {
+    let ranked = entries(board, puzzle)
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| RankedEntry {
+            rank: index + 1,
+            entry,
+        })
+        .collect();
+
+    preferences::visible_entries(ranked, viewer)
+}
+
+// ties share a rank; only streaks still alive today are ranked, so a lapsed one drops off
+fn rank_streaks(records: Vec<(String, GenedleRecord)>, today: u64) -> Vec<StreakEntry> {
+    let mut streaks: Vec<(String, usize, usize)> = records
+        .into_iter()
+        .map(|(player, record)| {
+            let streak = record.streak_as_of(today);
+            (player, streak.current, streak.max)
+        })
+        .filter(|&(_, current, _)| current > 0)
+        .collect();
+    streaks.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut entries: Vec<StreakEntry> = Vec::with_capacity(streaks.len());
+    for (index, (player, streak, max)) in streaks.into_iter().enumerate() {
+        let rank = match entries.last() {
+            Some(previous) if previous.streak == streak => previous.rank,
+            _ => index + 1,
+        };
+        entries.push(StreakEntry {
+            rank,
+            player,
+            streak,
+            max,
+        });
+    }
+
+    entries
+}
+
+// built from the streaks the server recorded as daily games finished, never from client numbers
+pub async fn streak_leaderboard(session: Session) -> Json<StreakLeaderboard> {
+    let viewer = player_id(&session).await.ok().map(player_code);
+    let entries = rank_streaks(stats::genedle_records(), schedule::today())
+        .into_iter()
+        .filter_map(|mut entry| {
+            entry.player = preferences::visible_name(&entry.player, viewer.as_deref())?;
+            Some(entry)
+        })
+        .take(STREAK_BOARD_SIZE)
+        .collect();
+
+    Json(StreakLeaderboard {
+        rules: StreakRules {
+            timezone: config().timezone.name(),
+            freeze_every: FREEZE_EVERY,
+            max_freezes: MAX_FREEZES,
+            counted: "daily Genedle puzzles; practice and archive games never count",
+        },
+        entries,
+    })
+}
+
+pub async fn submit_game(session: Session, Path(board): Path<Board>) -> Json<SubmitResult> {
+    let result = async {
+        let finished = match board {
+            Board::Daily => genedle::finished_daily(&session).await,
+            Board::Weekly => genedle::finished_weekly(&session).await,
+        }?;
+        let Some(FinishedGame {
+            puzzle,
+            guesses,
+            seconds,
+        }) = finished
+        else {
+            return Ok(Err(InvalidSubmission::NotFinished));
+        };
+        let Some(guesses) = guesses else {
+            return Ok(Err(InvalidSubmission::NotSolved));
+        };
+
+        submit(board, puzzle, player_id(&session).await?, guesses, seconds)
+    };
+
+    Json(match result.await {
+        Ok(Ok(standing)) => SubmitResult::Valid(standing),
+        Ok(Err(reason)) => SubmitResult::Invalid(reason),
+        Err(err) => SubmitResult::Invalid(InvalidSubmission::InternalError(err.to_string())),
+    })
+}
+
+pub async fn leaderboard(
+    session: Session,
+    Path((board, puzzle)): Path<(Board, u64)>,
+) -> Json<Vec<RankedEntry>> {
+    let viewer = player_id(&session).await.ok().map(player_code);
+    Json(rankings(board, puzzle, viewer.as_deref()))
+}
+
+// kept for clients from before daily rankings existed
+pub async fn weekly_leaderboard(session: Session, Path(week): Path<u64>) -> Json<Vec<RankedEntry>> {
+    leaderboard(session, Path((Board::Weekly, week))).await
+}
+
+pub async fn current_leaderboard(
+    session: Session,
+    Path(board): Path<Board>,
+) -> Json<Vec<RankedEntry>> {
+    leaderboard(session, Path((board, board.current_puzzle()))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::leaderboard::{LeaderboardEntry, rank_streaks, sort_entries, standing};
+    use crate::stats::{GenedleRecord, Streak};
+
+    fn entry(player: &str, guesses: usize, seconds: i64, submitted_at: i64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            player: player.to_string(),
+            guesses,
+            seconds,
+            submitted_at,
+        }
+    }
+
+    #[test]
+    fn test_sort_entries() {
+        let mut entries = vec![
+            entry("slow", 3, 300, 1),
+            entry("late", 3, 120, 9),
+            entry("early", 3, 120, 5),
+            entry("lucky", 2, 600, 7),
+        ];
+        sort_entries(&mut entries);
+
+        let order: Vec<&str> = entries.iter().map(|entry| entry.player.as_str()).collect();
+        assert_eq!(order, ["lucky", "early", "late", "slow"]);
+
+        let late = standing(&entries, 739000, "late").unwrap();
+        assert_eq!((late.rank, late.entries), (3, 4));
+        assert_eq!(standing(&entries, 739000, "absent"), None);
+    }
+
+    #[test]
+    fn test_rank_streaks() {
+        let record = |current, last_period| GenedleRecord {
+            streak: Streak {
+                current,
+                max: 10,
+                last_period: Some(last_period),
+            },
+            ..Default::default()
+        };
+        let records = vec![
+            ("c".to_string(), record(4, 500)),
+            ("a".to_string(), record(6, 499)),
+            ("b".to_string(), record(4, 500)),
+            ("lapsed".to_string(), record(9, 497)),
+            ("lost".to_string(), record(0, 500)),
+        ];
+
+        let ranked = rank_streaks(records, 500);
+        let ranked: Vec<(usize, &str, usize)> = ranked
+            .iter()
+            .map(|entry| (entry.rank, entry.player.as_str(), entry.streak))
+            .collect();
+        // the lapsed streak missed two days without a freeze banked
+        assert_eq!(ranked, [(1, "a", 6), (2, "b", 4), (2, "c", 4)]);
+    }
+}
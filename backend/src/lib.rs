@@ -0,0 +1,312 @@
+pub mod admin;
+pub mod announcements;
+pub mod api;
+pub mod auth;
+pub mod backup;
+pub mod cache;
+pub mod changelog;
+pub mod config;
+pub mod corpus;
+pub mod curation;
+pub mod error;
+pub mod extract;
+pub mod games;
+pub mod headers;
+pub mod health;
+pub mod leaderboard;
+pub mod logging;
+pub mod notifications;
+pub mod preferences;
+pub mod puzzle_ids;
+pub mod ratelimit;
+pub mod redact;
+pub mod render;
+pub mod schedule;
+pub mod sessions;
+pub mod slo;
+pub mod state;
+pub mod stats;
+pub mod storage;
+pub mod survey;
+pub mod themes;
+pub mod upstream;
+
+use axum::Router;
+use axum::extract::DefaultBodyLimit;
+use axum::middleware;
+use axum::routing::{delete, get, post, put};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::services::{ServeDir, ServeFile};
+use tower_sessions::cookie::SameSite;
+use tower_sessions::cookie::time::Duration;
+use tower_sessions::{Expiry, SessionManagerLayer};
+
+// the whole app minus its background tasks, so tests can serve it exactly as main does
+pub fn router(state: state::AppState) -> Router {
+    let base_path = &config::config().base_path;
+    let session_layer = SessionManagerLayer::new(state.sessions.clone())
+        .with_path(if base_path.is_empty() { "/" } else { base_path })
+        .with_expiry(Expiry::OnInactivity(Duration::days(1)))
+        // OAuth callbacks arrive as cross-site navigations and still need the login state
+        .with_same_site(SameSite::Lax);
+
+    let static_files = ServeDir::new("../frontend/build/client")
+        .not_found_service(ServeFile::new("../frontend/build/client/index.html"));
+
+    let admin = Router::new()
+        .route("/genedle-report/{day}", get(admin::genedle_report))
+        .route("/genedle-answer/{day}", put(admin::override_genedle_answer))
+        .route("/genedle-answer-pool", get(admin::genedle_answer_pool))
+        .route("/overview", get(admin::overview))
+        .route("/gene-groups", get(api::gene_group::groups))
+        .route("/gene-groups/{id}/members", get(api::gene_group::members))
+        .route("/sessions", get(admin::session_metrics))
+        .route("/sessions/{id}", get(admin::inspect_session))
+        .route("/audit/session-inspections", get(admin::inspection_audit))
+        .route("/upstream", get(admin::upstream_drift))
+        .route("/announcements", post(admin::publish_announcement))
+        .route("/announcements/{id}", delete(admin::retract_announcement))
+        .route("/config/reload", post(admin::reload_config))
+        .route("/slos", get(admin::slos))
+        .route("/metrics", get(admin::prometheus_metrics))
+        .route(
+            "/notifications",
+            get(admin::notification_deliveries).post(admin::send_notification),
+        )
+        .route(
+            "/notifications/{player}",
+            get(admin::player_notification_deliveries),
+        )
+        .route(
+            "/genections-board/{day}",
+            get(admin::genections_board_history).put(admin::publish_genections_board),
+        )
+        .route(
+            "/genections-board/{day}/rollback/{version}",
+            post(admin::rollback_genections_board),
+        )
+        .route(
+            "/spelling-gene-specials",
+            get(admin::spelling_gene_specials),
+        )
+        .route(
+            "/spelling-gene-specials/{day}",
+            put(admin::schedule_spelling_gene_special),
+        )
+        .route_layer(middleware::from_fn(admin::require_admin))
+        .layer(DefaultBodyLimit::max(extract::ADMIN_BODY_LIMIT));
+
+    // embeds are served cookie-free and kept apart from the session-carrying game API
+    let public = Router::new()
+        .route("/puzzles", get(api::public::puzzles))
+        .route("/puzzles/{game}/{puzzle}", get(api::public::puzzle))
+        .route(
+            "/spelling-gene/heatmap",
+            get(api::public::spelling_gene_heatmap),
+        )
+        .layer(middleware::from_fn(ratelimit::limit_public));
+
+    let app = Router::new()
+        .fallback_service(static_files)
+        .route("/games/genedle", get(games::genedle::genedle))
+        .route(
+            "/games/genedle/archive/{day}",
+            post(games::genedle::start_archive),
+        )
+        .route(
+            "/games/genedle-practice",
+            post(games::genedle::genedle_practice),
+        )
+        .route(
+            "/games/genedle-practice/{code}",
+            get(games::genedle::redeem_practice),
+        )
+        .route("/games/genedle-packs", get(games::packs::packs))
+        .route(
+            "/games/genedle-packs/{chromosome}/{index}/guess",
+            post(games::packs::guess),
+        )
+        .route("/games/genedle-race", post(games::race::create_room))
+        .route("/games/genedle-race/{code}", get(games::race::join_room))
+        .route(
+            "/games/genedle-race/{code}/spectate",
+            get(games::race::spectate_room),
+        )
+        .route("/games/genedle-weekly", get(games::genedle::genedle_weekly))
+        .route(
+            "/games/genedle-weekly/guess",
+            post(games::genedle::weekly_guess),
+        )
+        .route(
+            "/games/genedle-weekly/stats",
+            get(games::genedle::weekly_stats),
+        )
+        .route(
+            "/games/genedle-weekly/share",
+            get(games::genedle::weekly_share),
+        )
+        .route(
+            "/games/genedle-tutorial",
+            get(games::tutorial::tutorial).delete(games::tutorial::restart_tutorial),
+        )
+        .route(
+            "/games/genedle-tutorial/guess",
+            post(games::tutorial::guess),
+        )
+        .route("/games/crossover", get(games::crossover::crossover))
+        .route("/games/crossover/guess", post(games::crossover::guess))
+        .route("/games/stats", get(games::player_stats))
+        .route("/api/v1/daily-progress", get(games::daily_progress))
+        .route(
+            "/games/preferences",
+            get(preferences::get_preferences).put(preferences::put_preferences),
+        )
+        .route(
+            "/games/notifications",
+            get(notifications::get_notification_preferences)
+                .put(notifications::put_notification_preferences),
+        )
+        .route("/games/genections", get(games::genections::genections))
+        .route("/games/genections/guess", post(games::genections::guess))
+        .route("/games/genections/text", get(games::genections::text_board))
+        .route(
+            "/games/genections/text/guess",
+            post(games::genections::text_guess),
+        )
+        .route(
+            "/games/genections/submissions",
+            get(games::genections::submissions),
+        )
+        .route(
+            "/games/genections/mistakes",
+            get(games::genections::mistakes),
+        )
+        .route(
+            "/games/genections/hint",
+            post(games::genections::grouping_hint),
+        )
+        .route("/games/genections/share", get(games::genections::share))
+        .route(
+            "/games/genections/snapshot/{code}",
+            get(games::genections::snapshot),
+        )
+        .route(
+            "/games/spelling-gene",
+            get(games::spelling_gene::spelling_gene),
+        )
+        .route(
+            "/games/spelling-gene/guess",
+            post(games::spelling_gene::guess),
+        )
+        .route(
+            "/games/spelling-gene/progress",
+            get(games::spelling_gene::progress),
+        )
+        .route(
+            "/games/spelling-gene/hints",
+            get(games::spelling_gene::hints),
+        )
+        // these need to know who's asking, so they sit behind the session layer
+        .route("/api/v1/genedle-guess", post(games::genedle::guess))
+        .route("/api/v1/genedle-state", get(games::genedle::genedle_state))
+        .route("/api/v1/genedle-share", get(games::genedle::genedle_share))
+        .route("/api/v1/genedle-hint", get(games::genedle::genedle_hint))
+        .route(
+            "/api/v1/genedle-answer",
+            get(games::genedle::genedle_answer),
+        )
+        .route("/api/v1/genedle-themes", get(themes::genedle_themes))
+        .route(
+            "/api/v1/genedle-reveal",
+            post(games::genedle::reveal_letter),
+        )
+        .route("/api/v1/genedle-stats", get(games::genedle::genedle_stats))
+        .route(
+            "/api/v1/genedle-archive",
+            get(games::genedle::genedle_archive),
+        )
+        .route(
+            "/api/v1/genedle-weekly-leaderboard/{week}",
+            get(leaderboard::weekly_leaderboard),
+        )
+        .route(
+            "/api/v1/streak-leaderboard",
+            get(leaderboard::streak_leaderboard),
+        )
+        .route(
+            "/api/v1/leaderboard/{board}",
+            get(leaderboard::current_leaderboard),
+        )
+        .route(
+            "/api/v1/leaderboard/{board}/submit",
+            post(leaderboard::submit_game),
+        )
+        .route(
+            "/api/v1/leaderboard/{board}/{puzzle}",
+            get(leaderboard::leaderboard),
+        )
+        .route("/auth/login/{provider}", get(auth::login))
+        .route("/auth/callback/{provider}", get(auth::callback))
+        .route("/auth/me", get(auth::me))
+        .route("/auth/logout", post(auth::logout))
+        .route("/api/v1/announcements", get(announcements::list))
+        .route("/api/v1/announcements/read", post(announcements::mark_read))
+        .layer(session_layer)
+        .route(
+            "/api/v1/spelling-gene-guess/{seed}/{min_length}/{min_words}/{num_letters}/{guess}",
+            get(api::spelling_gene::check_guess),
+        )
+        .route(
+            "/api/v1/spelling-gene/{seed}/{min_length}/{min_words}/{num_letters}",
+            get(api::spelling_gene::get_letters),
+        )
+        .route("/api/v2/spelling-gene", post(api::spelling_gene::puzzle))
+        .route(
+            "/api/v2/spelling-gene/guess",
+            post(api::spelling_gene::guess),
+        )
+        .route(
+            "/api/v1/genedle-letters/{id}",
+            get(api::genedle::num_letters),
+        )
+        .route("/api/v1/genedle-metadata/{id}", get(api::genedle::metadata))
+        .route("/api/v1/genedle-corpus", get(api::genedle::corpus))
+        .route(
+            "/api/v1/genedle-guidelines/{id}",
+            get(api::genedle::guidelines),
+        )
+        .route("/api/v1/gene/{symbol}", get(api::gene::gene))
+        .route("/api/changelog", get(changelog::changelog))
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route(
+            "/api/v1/daily-summary/{game}",
+            get(api::public::daily_summary),
+        )
+        .route(
+            "/api/v1/puzzle-number/{game}",
+            get(api::public::today_number),
+        )
+        .layer(DefaultBodyLimit::max(extract::GAME_BODY_LIMIT))
+        .nest("/api/admin", admin)
+        .nest("/api/public", public)
+        .layer(middleware::from_fn(slo::track))
+        .layer(middleware::from_fn(changelog::deprecation_headers))
+        // checked per request, so a config reload can change the allowed origins
+        .layer(
+            CorsLayer::permissive().allow_origin(AllowOrigin::predicate(|origin, _| {
+                config::config().allows_origin(origin)
+            })),
+        )
+        .with_state(state);
+
+    if base_path.is_empty() {
+        app
+    } else {
+        Router::new().nest(base_path, app)
+    }
+    .layer(middleware::from_fn(headers::security_headers))
+    .layer(middleware::from_fn(headers::degraded_mode))
+    // outermost, so the latency and status logged are what the client saw
+    .layer(middleware::from_fn(logging::trace))
+}
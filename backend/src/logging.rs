@@ -0,0 +1,166 @@
+use crate::config::config;
+use axum::extract::Request;
+use axum::http::header::COOKIE;
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::str::FromStr;
+use std::time::Instant;
+use tracing::Instrument;
+use tracing_subscriber::EnvFilter;
+
+pub const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+// the tower-sessions default
+const SESSION_COOKIE: &str = "id";
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LogFormat {
+    Text,
+    // one object per line, for log shippers
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format {s}")),
+        }
+    }
+}
+
+tokio::task_local! {
+    static CURRENT_REQUEST: String;
+}
+
+// to stderr, where everything went before
+pub fn init() {
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(&config().log_filter))
+        .with_writer(std::io::stderr);
+    match config().log_format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.json().init(),
+    }
+}
+
+// the id of the request being handled, so outbound calls made for it can carry it on
+pub fn request_id() -> Option<String> {
+    CURRENT_REQUEST.try_with(Clone::clone).ok()
+}
+
+// one a proxy in front already assigned is kept, so its logs and ours line up
+fn incoming_request_id(headers: &HeaderMap) -> Option<String> {
+    let id = headers.get(&REQUEST_ID)?.to_str().ok()?;
+    (!id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+    .then(|| id.to_string())
+}
+
+fn generate_request_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+// enough to follow one player's requests through the logs without the cookie that would let
+// someone reading them take over the session
+fn session_hash(headers: &HeaderMap) -> Option<String> {
+    let session = headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .find_map(|cookie| {
+            cookie
+                .trim()
+                .strip_prefix(SESSION_COOKIE)?
+                .strip_prefix('=')
+        })?;
+
+    let digest = Sha256::digest(session.as_bytes());
+    Some(digest[..8].iter().fold(String::new(), |mut hash, byte| {
+        let _ = write!(hash, "{byte:02x}");
+        hash
+    }))
+}
+
+pub async fn trace(request: Request, next: Next) -> Response {
+    let id = incoming_request_id(request.headers()).unwrap_or_else(generate_request_id);
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %request.method(),
+        path = request.uri().path(),
+        session = session_hash(request.headers()),
+    );
+
+    let started = Instant::now();
+    let mut response = CURRENT_REQUEST
+        .scope(id.clone(), next.run(request))
+        .instrument(span.clone())
+        .await;
+    span.in_scope(|| {
+        tracing::info!(
+            status = response.status().as_u16(),
+            latency_ms = started.elapsed().as_millis() as u64,
+            "finished"
+        )
+    });
+
+    if let Ok(id) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID, id);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logging::{
+        LogFormat, REQUEST_ID, generate_request_id, incoming_request_id, session_hash,
+    };
+    use axum::http::header::COOKIE;
+    use axum::http::{HeaderMap, HeaderValue};
+
+    #[test]
+    fn test_request_id() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(incoming_request_id(&headers), None);
+        headers.insert(REQUEST_ID, HeaderValue::from_static("fly-01J9Z.abc_1"));
+        assert_eq!(
+            incoming_request_id(&headers).as_deref(),
+            Some("fly-01J9Z.abc_1")
+        );
+        headers.insert(REQUEST_ID, HeaderValue::from_static("id with spaces"));
+        assert_eq!(incoming_request_id(&headers), None);
+
+        assert_eq!(generate_request_id().len(), 32);
+        assert_ne!(generate_request_id(), generate_request_id());
+        assert_eq!("json".parse(), Ok(LogFormat::Json));
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_session_hash() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(session_hash(&headers), None);
+        headers.insert(
+            COOKIE,
+            HeaderValue::from_static("theme=dark; id=Lz9YvPq3; other=1"),
+        );
+        let hash = session_hash(&headers).unwrap();
+        assert_eq!(hash.len(), 16);
+        assert!(!hash.contains("Lz9YvPq3"));
+
+        // a cookie that only starts with the same letters isn't the session
+        headers.insert(COOKIE, HeaderValue::from_static("idle=1"));
+        assert_eq!(session_hash(&headers), None);
+    }
+}
@@ -1,50 +1,80 @@
-mod api;
-mod games;
-
-use axum::Router;
-use axum::routing::{get, post};
+use genedle::{
+    backup, cache, config, corpus, games, logging, notifications, ratelimit, router, sessions,
+    state, storage,
+};
+use std::net::SocketAddr;
 use tokio::net::TcpListener;
-use tower_http::cors::CorsLayer;
-use tower_http::services::{ServeDir, ServeFile};
-use tower_sessions::cookie::time::Duration;
-use tower_sessions::{Expiry, MemoryStore, SessionManagerLayer};
 
 #[tokio::main]
 async fn main() {
-    let session_store = MemoryStore::default();
-    let session_layer = SessionManagerLayer::new(session_store)
-        .with_expiry(Expiry::OnInactivity(Duration::days(1)));
-
-    let static_files = ServeDir::new("../frontend/build/client")
-        .not_found_service(ServeFile::new("../frontend/build/client/index.html"));
-
-    let app = Router::new()
-        .fallback_service(static_files)
-        .route("/games/genedle", get(games::genedle::genedle))
-        .route("/games/genections", get(games::genections::genections))
-        .route(
-            "/games/spelling-gene",
-            get(games::spelling_gene::spelling_gene),
-        )
-        .layer(session_layer)
-        .route(
-            "/api/v1/spelling-gene-guess/{seed}/{min_length}/{min_words}/{num_letters}/{guess}",
-            get(api::spelling_gene::check_guess),
-        )
-        .route(
-            "/api/v1/spelling-gene/{seed}/{min_length}/{min_words}/{num_letters}",
-            get(api::spelling_gene::get_letters),
-        )
-        .route("/api/v1/genedle-guess", post(api::genedle::guess))
-        .route(
-            "/api/v1/genedle-letters/{id}",
-            get(api::genedle::num_letters),
-        )
-        .layer(CorsLayer::permissive());
+    logging::init();
+
+    let session_store = sessions::TrackedStore::open(&config::config().session_store).unwrap();
+    let state = state::AppState::new(session_store.clone()).unwrap();
+
+    let client = state.client.clone();
+    tokio::spawn(async move {
+        match corpus::init(&client).await {
+            Ok(_) => games::pregenerate().await,
+            Err(err) => tracing::error!("Failed to load HGNC corpus: {err}"),
+        }
+    });
+
+    tokio::spawn(
+        session_store
+            .clone()
+            .collect_garbage(tokio::time::Duration::from_secs(
+                config::config().session_gc_seconds,
+            )),
+    );
+
+    tokio::spawn(backup::nightly(session_store.clone()));
+    tokio::spawn(notifications::scheduler(state.client.clone()));
+    tokio::spawn(ratelimit::sweeper());
+
+    let app = router(state);
 
     let host = std::env::var("GENEDLE_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
     let port = std::env::var("GENEDLE_PORT").unwrap_or_else(|_| "3000".to_string());
 
+    cache::restore().await;
+
     let listener = TcpListener::bind(format!("{host}:{port}")).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    tracing::info!("Listening on {host}:{port}");
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+    .unwrap();
+
+    if let Err(err) = cache::persist().await {
+        tracing::error!("Failed to persist caches: {err}");
+    }
+    if let Err(err) = storage::storage().sync() {
+        tracing::error!("Failed to write storage: {err}");
+    }
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.ok();
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        if let Ok(mut signal) =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        {
+            signal.recv().await;
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
 }
@@ -0,0 +1,523 @@
+use crate::config::config;
+use crate::extract::StrictJson;
+use crate::games::player_id;
+use crate::preferences::player_code;
+use crate::storage::storage;
+use axum::Json;
+use axum::http::StatusCode;
+use chrono::{DateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use reqwest::{Client, Url, redirect};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::{IpAddr, SocketAddr};
+use tower_sessions::Session;
+
+const LAST_ID_KEY: &str = "notifications.last_id";
+const DELIVERY_PREFIX: &str = "notifications.delivery.";
+const MAX_ATTEMPTS: u32 = 5;
+const RETRY_BASE_SECONDS: i64 = 60;
+const MAX_RETRY_SECONDS: i64 = 3600;
+const DELIVERY_RETENTION_DAYS: i64 = 14;
+const SEND_TIMEOUT_SECONDS: u64 = 10;
+const MAX_MUTED: usize = 50;
+const MAX_EMAIL_LENGTH: usize = 254;
+const MAX_LISTED: usize = 500;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    WebPush,
+    Email,
+    Webhook,
+}
+
+// whole hours in GENEDLE_TIMEZONE, the same clock the daily puzzles run on; a start after the end
+// wraps past midnight
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct QuietHours {
+    pub start: u32,
+    pub end: u32,
+}
+
+// each enabled channel maps to where it delivers: a push subscription endpoint, an email address
+// or a webhook URL
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationPreferences {
+    #[serde(default)]
+    pub channels: BTreeMap<Channel, String>,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    // kinds the player never wants, whichever game sends them
+    #[serde(default)]
+    pub muted: BTreeSet<String>,
+}
+
+// kinds are namespaced by whoever sends them, e.g. "genedle.daily" or "announcement"
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Notification {
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Notify {
+    pub players: Vec<String>,
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+// one notification on one channel, kept for a while after it settles so failures can be traced
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Delivery {
+    pub id: u64,
+    pub player: String,
+    pub channel: Channel,
+    pub notification: Notification,
+    pub created_at: i64,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<i64>,
+}
+
+// everything due for one player on one channel goes out together
+#[derive(Serialize, Debug)]
+struct Digest<'a> {
+    channel: Channel,
+    to: &'a str,
+    player: &'a str,
+    notifications: Vec<&'a Notification>,
+}
+
+impl QuietHours {
+    fn contains(&self, hour: u32) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&hour)
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+
+    // the first moment from now on that isn't quiet, stepping through the hours as they read locally
+    fn resumes_at(&self, now: DateTime<Utc>, timezone: Tz) -> DateTime<Utc> {
+        let now = now.with_timezone(&timezone);
+        let mut at = now
+            .with_minute(0)
+            .and_then(|at| at.with_second(0))
+            .and_then(|at| at.with_nanosecond(0))
+            .unwrap_or(now);
+        while self.contains(at.hour()) {
+            at += chrono::Duration::hours(1);
+        }
+        at.max(now).with_timezone(&Utc)
+    }
+}
+
+fn preferences_key(code: &str) -> String {
+    format!("notifications.preferences.{code}")
+}
+
+fn delivery_key(id: u64) -> String {
+    format!("{DELIVERY_PREFIX}{id:016}")
+}
+
+pub fn notification_preferences(code: &str) -> NotificationPreferences {
+    storage().get(&preferences_key(code)).unwrap_or_default()
+}
+
+// webhooks are fetched by the server, so they're held to https hosts by name; where the name
+// points is checked again at send time
+fn is_valid_address(channel: Channel, address: &str) -> bool {
+    match channel {
+        Channel::Email => {
+            address.len() <= MAX_EMAIL_LENGTH
+                && !address.contains(char::is_whitespace)
+                && address
+                    .split_once('@')
+                    .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'))
+        }
+        Channel::WebPush | Channel::Webhook => Url::parse(address).is_ok_and(|url| {
+            url.scheme() == "https" && url.domain().is_some_and(|host| host != "localhost")
+        }),
+    }
+}
+
+fn is_valid(preferences: &NotificationPreferences) -> bool {
+    preferences
+        .channels
+        .iter()
+        .all(|(&channel, address)| is_valid_address(channel, address))
+        && preferences
+            .quiet_hours
+            .is_none_or(|quiet| quiet.start < 24 && quiet.end < 24)
+        && preferences.muted.len() <= MAX_MUTED
+}
+
+fn retry_delay(attempts: u32) -> i64 {
+    (RETRY_BASE_SECONDS << attempts.saturating_sub(1).min(16)).min(MAX_RETRY_SECONDS)
+}
+
+fn save(delivery: &Delivery) -> Result<(), anyhow::Error> {
+    storage().insert_with_ttl(
+        &delivery_key(delivery.id),
+        delivery,
+        chrono::Duration::days(DELIVERY_RETENTION_DAYS),
+    )
+}
+
+// one delivery per enabled channel, skipping players who muted the kind; when it goes out is up to
+// the scheduler
+pub fn enqueue(
+    players: &[String],
+    notification: &Notification,
+) -> Result<Vec<Delivery>, anyhow::Error> {
+    let now = Utc::now().timestamp();
+    let mut deliveries = Vec::new();
+    for player in players {
+        let preferences = notification_preferences(player);
+        if preferences.muted.contains(&notification.kind) {
+            continue;
+        }
+
+        for &channel in preferences.channels.keys() {
+            let id = storage().update(LAST_ID_KEY, |id: &mut u64| *id += 1)?;
+            let delivery = Delivery {
+                id,
+                player: player.clone(),
+                channel,
+                notification: notification.clone(),
+                created_at: now,
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                next_attempt_at: now,
+                last_error: None,
+                delivered_at: None,
+            };
+            save(&delivery)?;
+            deliveries.push(delivery);
+        }
+    }
+
+    Ok(deliveries)
+}
+
+// newest first
+pub fn deliveries(player: Option<&str>) -> Vec<Delivery> {
+    let mut deliveries: Vec<Delivery> = storage()
+        .keys(DELIVERY_PREFIX)
+        .iter()
+        .filter_map(|key| storage().get::<Delivery>(key))
+        .filter(|delivery| player.is_none_or(|player| delivery.player == player))
+        .collect();
+    deliveries.sort_by_key(|delivery| std::cmp::Reverse(delivery.id));
+    deliveries.truncate(MAX_LISTED);
+    deliveries
+}
+
+// email and web push go through a relay, which owns the mail server and push service keys
+fn relay(channel: Channel) -> Option<&'static str> {
+    let relay = match channel {
+        Channel::Email => &config().email_relay_url,
+        Channel::WebPush => &config().push_relay_url,
+        Channel::Webhook => return None,
+    };
+    (!relay.is_empty()).then_some(relay.as_str())
+}
+
+// anything a player's webhook could use to reach the server's own network
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // carrier-grade NAT, 100.64.0.0/10
+                || (first == 100 && second & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+// the host is resolved once and the client pinned to what was checked, so it can't be re-pointed
+// in between; redirects aren't followed, since they could lead anywhere
+async fn webhook_client(url: &str) -> Result<Client, String> {
+    let url = Url::parse(url).map_err(|err| err.to_string())?;
+    let (Some(host), Some(port)) = (url.domain(), url.port_or_known_default()) else {
+        return Err("Webhook needs a host name".to_string());
+    };
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|err| err.to_string())?
+        .collect();
+    if addrs.is_empty() || !addrs.iter().all(|addr| is_public(addr.ip())) {
+        return Err(format!("Webhook host {host} isn't a public address"));
+    }
+
+    Client::builder()
+        .user_agent(concat!("genedle/", env!("CARGO_PKG_VERSION")))
+        .redirect(redirect::Policy::none())
+        .resolve_to_addrs(host, &addrs)
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+async fn send(client: &Client, digest: &Digest<'_>) -> Result<(), String> {
+    let (client, url) = match digest.channel {
+        Channel::Webhook => (webhook_client(digest.to).await?, digest.to),
+        channel => (
+            client.clone(),
+            relay(channel).ok_or_else(|| format!("No relay configured for {channel:?}"))?,
+        ),
+    };
+
+    client
+        .post(url)
+        .timeout(std::time::Duration::from_secs(SEND_TIMEOUT_SECONDS))
+        .json(digest)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+fn settle(batch: Vec<Delivery>, outcome: impl Fn(&mut Delivery)) {
+    for mut delivery in batch {
+        outcome(&mut delivery);
+        if let Err(err) = save(&delivery) {
+            tracing::error!("Failed to record notification {}: {err}", delivery.id);
+        }
+    }
+}
+
+// preferences are read again at send time, so turning a channel off or moving quiet hours applies
+// to notifications already queued
+pub async fn deliver_due(client: &Client, now: DateTime<Utc>) {
+    let mut due: BTreeMap<(String, Channel), Vec<Delivery>> = BTreeMap::new();
+    for delivery in deliveries_due(now.timestamp()) {
+        due.entry((delivery.player.clone(), delivery.channel))
+            .or_default()
+            .push(delivery);
+    }
+
+    for ((player, channel), batch) in due {
+        let preferences = notification_preferences(&player);
+        let Some(address) = preferences.channels.get(&channel) else {
+            settle(batch, |delivery| {
+                delivery.status = DeliveryStatus::Failed;
+                delivery.last_error = Some("Channel turned off".to_string());
+            });
+            continue;
+        };
+        let timezone = config().timezone;
+        if let Some(quiet) = preferences.quiet_hours
+            && quiet.contains(now.with_timezone(&timezone).hour())
+        {
+            let resumes_at = quiet.resumes_at(now, timezone).timestamp();
+            settle(batch, |delivery| delivery.next_attempt_at = resumes_at);
+            continue;
+        }
+
+        let digest = Digest {
+            channel,
+            to: address,
+            player: &player,
+            notifications: batch
+                .iter()
+                .map(|delivery| &delivery.notification)
+                .collect(),
+        };
+        let result = send(client, &digest).await;
+        if let Err(err) = &result {
+            tracing::warn!("Failed to notify {player} by {channel:?}: {err}");
+        }
+
+        settle(batch, |delivery| {
+            delivery.attempts += 1;
+            match &result {
+                Ok(()) => {
+                    delivery.status = DeliveryStatus::Delivered;
+                    delivery.delivered_at = Some(now.timestamp());
+                    delivery.last_error = None;
+                }
+                Err(err) => {
+                    delivery.last_error = Some(err.clone());
+                    if delivery.attempts >= MAX_ATTEMPTS {
+                        delivery.status = DeliveryStatus::Failed;
+                    } else {
+                        delivery.next_attempt_at = now.timestamp() + retry_delay(delivery.attempts);
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn deliveries_due(now: i64) -> Vec<Delivery> {
+    storage()
+        .keys(DELIVERY_PREFIX)
+        .iter()
+        .filter_map(|key| storage().get::<Delivery>(key))
+        .filter(|delivery| {
+            delivery.status == DeliveryStatus::Pending && delivery.next_attempt_at <= now
+        })
+        .collect()
+}
+
+pub async fn scheduler(client: Client) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+    loop {
+        interval.tick().await;
+        deliver_due(&client, Utc::now()).await;
+    }
+}
+
+pub async fn get_notification_preferences(
+    session: Session,
+) -> Json<Option<NotificationPreferences>> {
+    Json(
+        player_id(&session)
+            .await
+            .ok()
+            .map(|player| notification_preferences(&player_code(player))),
+    )
+}
+
+pub async fn put_notification_preferences(
+    session: Session,
+    StrictJson(preferences): StrictJson<NotificationPreferences>,
+) -> Result<Json<NotificationPreferences>, StatusCode> {
+    if !is_valid(&preferences) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let player = player_code(
+        player_id(&session)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    );
+    storage()
+        .insert(&preferences_key(&player), &preferences)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(preferences))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::notifications::{
+        Channel, QuietHours, is_public, is_valid_address, retry_delay, webhook_client,
+    };
+    use chrono::{TimeZone, Timelike, Utc};
+    use chrono_tz::Tz;
+
+    #[test]
+    fn test_quiet_hours() {
+        let overnight = QuietHours { start: 22, end: 7 };
+        assert!(overnight.contains(23));
+        assert!(overnight.contains(0));
+        assert!(!overnight.contains(7));
+        assert!(!overnight.contains(12));
+
+        let now = Utc.with_ymd_and_hms(2025, 3, 1, 23, 30, 0).unwrap();
+        assert_eq!(
+            overnight.resumes_at(now, Tz::UTC),
+            Utc.with_ymd_and_hms(2025, 3, 2, 7, 0, 0).unwrap()
+        );
+        let now = Utc.with_ymd_and_hms(2025, 3, 1, 12, 30, 0).unwrap();
+        assert_eq!(overnight.resumes_at(now, Tz::UTC), now);
+
+        // a zero-length window is never quiet
+        let never = QuietHours { start: 9, end: 9 };
+        assert!((0..24).all(|hour| !never.contains(hour)));
+        assert_eq!(never.resumes_at(now, Tz::UTC).hour(), 12);
+
+        // 14:30 UTC is 23:30 in Tokyo, so the same window is quiet until 07:00 there
+        let now = Utc.with_ymd_and_hms(2025, 3, 1, 14, 30, 0).unwrap();
+        assert_eq!(
+            overnight.resumes_at(now, Tz::Asia__Tokyo),
+            Utc.with_ymd_and_hms(2025, 3, 1, 22, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), 60);
+        assert_eq!(retry_delay(2), 120);
+        assert_eq!(retry_delay(4), 480);
+        assert_eq!(retry_delay(30), 3600);
+    }
+
+    #[test]
+    fn test_is_valid_address() {
+        assert!(is_valid_address(Channel::Email, "ada@example.org"));
+        assert!(!is_valid_address(Channel::Email, "ada@localhost"));
+        assert!(!is_valid_address(
+            Channel::Email,
+            "ada lovelace@example.org"
+        ));
+        assert!(is_valid_address(
+            Channel::Webhook,
+            "https://hooks.example.org/genedle"
+        ));
+        assert!(!is_valid_address(
+            Channel::Webhook,
+            "http://hooks.example.org/genedle"
+        ));
+        assert!(!is_valid_address(Channel::Webhook, "https://127.0.0.1/"));
+        assert!(!is_valid_address(
+            Channel::WebPush,
+            "https://localhost/push"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_client() {
+        for ip in [
+            "10.0.0.8",
+            "169.254.169.254",
+            "100.64.1.1",
+            "::1",
+            "fd00::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+        assert!(is_public("93.184.216.34".parse().unwrap()));
+        assert!(is_public("2606:4700::1111".parse().unwrap()));
+
+        // a name that passes the address check but resolves somewhere internal
+        assert!(webhook_client("https://localhost/hook").await.is_err());
+        assert!(webhook_client("https://127.0.0.1/hook").await.is_err());
+    }
+}
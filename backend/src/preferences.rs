@@ -0,0 +1,138 @@
+use crate::extract::StrictJson;
+use crate::games::player_id;
+use crate::leaderboard::RankedEntry;
+use crate::storage::storage;
+use axum::Json;
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use tower_sessions::Session;
+
+const MAX_PSEUDONYM_LENGTH: usize = 20;
+const MAX_FRIENDS: usize = 100;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardVisibility {
+    #[default]
+    Public,
+    FriendsOnly,
+    Hidden,
+}
+
+// friends are player codes; listing someone lets them see you on friends-only leaderboards
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Preferences {
+    #[serde(default)]
+    pub leaderboard: LeaderboardVisibility,
+    #[serde(default)]
+    pub pseudonym: Option<String>,
+    #[serde(default)]
+    pub friends: BTreeSet<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct PlayerPreferences {
+    pub player: String,
+    #[serde(flatten)]
+    pub preferences: Preferences,
+}
+
+pub fn player_code(player: u64) -> String {
+    format!("{player:016x}")
+}
+
+fn preferences_key(code: &str) -> String {
+    format!("preferences.{code}")
+}
+
+pub fn preferences(code: &str) -> Preferences {
+    storage().get(&preferences_key(code)).unwrap_or_default()
+}
+
+fn is_valid_pseudonym(pseudonym: &str) -> bool {
+    let length = pseudonym.chars().count();
+    (1..=MAX_PSEUDONYM_LENGTH).contains(&length)
+        && pseudonym.trim() == pseudonym
+        && pseudonym
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+}
+
+// every leaderboard goes through here, so a hidden player never shows up anywhere; None when they've
+// asked not to be shown to the viewer
+pub fn visible_name(player: &str, viewer: Option<&str>) -> Option<String> {
+    let preferences = preferences(player);
+    let visible = match preferences.leaderboard {
+        LeaderboardVisibility::Public => true,
+        LeaderboardVisibility::FriendsOnly => {
+            viewer.is_some_and(|viewer| viewer == player || preferences.friends.contains(viewer))
+        }
+        LeaderboardVisibility::Hidden => false,
+    };
+
+    visible.then(|| preferences.pseudonym.unwrap_or_else(|| player.to_string()))
+}
+
+pub fn visible_entries(entries: Vec<RankedEntry>, viewer: Option<&str>) -> Vec<RankedEntry> {
+    entries
+        .into_iter()
+        .filter_map(|RankedEntry { rank, mut entry }| {
+            entry.player = visible_name(&entry.player, viewer)?;
+            Some(RankedEntry { rank, entry })
+        })
+        .collect()
+}
+
+pub async fn get_preferences(session: Session) -> Json<Option<PlayerPreferences>> {
+    Json(player_id(&session).await.ok().map(|player| {
+        let player = player_code(player);
+        PlayerPreferences {
+            preferences: preferences(&player),
+            player,
+        }
+    }))
+}
+
+pub async fn put_preferences(
+    session: Session,
+    StrictJson(preferences): StrictJson<Preferences>,
+) -> Result<Json<PlayerPreferences>, StatusCode> {
+    if preferences
+        .pseudonym
+        .as_deref()
+        .is_some_and(|pseudonym| !is_valid_pseudonym(pseudonym))
+        || preferences.friends.len() > MAX_FRIENDS
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let player = player_code(
+        player_id(&session)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    );
+    storage()
+        .insert(&preferences_key(&player), &preferences)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(PlayerPreferences {
+        player,
+        preferences,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::preferences::is_valid_pseudonym;
+
+    #[test]
+    fn test_is_valid_pseudonym() {
+        assert!(is_valid_pseudonym("Gene Hunter_42"));
+        assert!(!is_valid_pseudonym(""));
+        assert!(!is_valid_pseudonym(" padded"));
+        assert!(!is_valid_pseudonym("<script>"));
+        assert!(!is_valid_pseudonym("a name well past twenty characters"));
+    }
+}
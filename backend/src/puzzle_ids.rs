@@ -0,0 +1,143 @@
+use crate::config::config;
+use crate::schedule;
+use crate::storage::storage;
+use hmac::{Hmac, Mac};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::Sha256;
+use std::sync::LazyLock;
+
+const SECRET_KEY: &str = "puzzle_ids.secret";
+const SECRET_BYTES: usize = 32;
+const DAY_BYTES: usize = 4;
+const TAG_BYTES: usize = 12;
+const SEED_BYTES: usize = 8;
+
+// GENEDLE_PUZZLE_SECRET if it's set, otherwise one made on first use and kept in storage, so tokens
+// and keyed draws survive restarts
+static SECRET: LazyLock<Vec<u8>> = LazyLock::new(|| {
+    if !config().puzzle_secret.is_empty() {
+        return config().puzzle_secret.as_bytes().to_vec();
+    }
+    if let Some(secret) = storage().get::<String>(SECRET_KEY) {
+        return secret.into_bytes();
+    }
+
+    let secret: String = (0..SECRET_BYTES)
+        .map(|_| format!("{:02x}", rand::random::<u8>()))
+        .collect();
+    if let Err(err) = storage().insert(SECRET_KEY, &secret) {
+        tracing::error!("Failed to save puzzle id secret: {err}");
+    }
+    secret.into_bytes()
+});
+
+// a puzzle key as clients see it: a token only this server can mint or read
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct PuzzleId(pub u64);
+
+fn hmac(domain: &[u8], parts: &[&[u8]]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(&SECRET).expect("HMAC takes keys of any length");
+    mac.update(domain);
+    for part in parts {
+        mac.update(part);
+    }
+    mac
+}
+
+fn digest(domain: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    hmac(domain, parts).finalize().into_bytes().into()
+}
+
+// what generation actually seeds its RNG with, so knowing a date-derived seed isn't enough to replay
+// a puzzle offline
+pub fn keyed(seed: u64) -> u64 {
+    let digest = digest(b"draw", &[&seed.to_be_bytes()]);
+    u64::from_be_bytes(digest[..SEED_BYTES].try_into().unwrap())
+}
+
+pub fn seal(seed: u64) -> String {
+    seal_on(seed, schedule::today() as u32)
+}
+
+// day, then a tag over the day and seed, then the seed masked with a pad derived from the tag; the
+// tag authenticates the token and the pad hides the seed
+fn seal_on(seed: u64, day: u32) -> String {
+    let day = day.to_be_bytes();
+    let seed = seed.to_be_bytes();
+    let tag = digest(b"tag", &[&day, &seed]);
+    let tag = &tag[..TAG_BYTES];
+    let pad = digest(b"pad", &[tag]);
+
+    day.iter()
+        .chain(tag)
+        .copied()
+        .chain(seed.iter().zip(pad).map(|(byte, pad)| byte ^ pad))
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+pub fn open(token: &str) -> Option<u64> {
+    if token.len() != 2 * (DAY_BYTES + TAG_BYTES + SEED_BYTES) || !token.is_ascii() {
+        return None;
+    }
+    let bytes: Vec<u8> = (0..token.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&token[index..index + 2], 16).ok())
+        .collect::<Option<_>>()?;
+
+    let (day, rest) = bytes.split_at(DAY_BYTES);
+    let (tag, masked) = rest.split_at(TAG_BYTES);
+    let pad = digest(b"pad", &[tag]);
+    let seed: Vec<u8> = masked
+        .iter()
+        .zip(pad)
+        .map(|(byte, pad)| byte ^ pad)
+        .collect();
+
+    hmac(b"tag", &[day, &seed])
+        .verify_truncated_left(tag)
+        .ok()?;
+    Some(u64::from_be_bytes(seed.try_into().ok()?))
+}
+
+impl Serialize for PuzzleId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&seal(self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for PuzzleId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let token = String::deserialize(deserializer)?;
+        open(&token)
+            .map(PuzzleId)
+            .ok_or_else(|| D::Error::custom("unknown puzzle id"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::puzzle_ids::{keyed, open, seal_on};
+
+    #[test]
+    fn test_seal() {
+        let token = seal_on(1_234_567_890, 739_000);
+        assert_eq!(token.len(), 48);
+        assert!(!token.contains(&format!("{:x}", 1_234_567_890)));
+        assert_eq!(open(&token), Some(1_234_567_890));
+
+        // the same seed reads differently from one day to the next
+        assert_ne!(seal_on(1_234_567_890, 739_001), token);
+        assert_eq!(open(&seal_on(u64::MAX, 739_001)), Some(u64::MAX));
+
+        let mut tampered = token.into_bytes();
+        tampered[47] = if tampered[47] == b'0' { b'1' } else { b'0' };
+        assert_eq!(open(&String::from_utf8(tampered).unwrap()), None);
+        assert_eq!(open("739000"), None);
+        assert_eq!(open(&"é".repeat(24)), None);
+
+        assert_ne!(keyed(739_000), 739_000);
+        assert_eq!(keyed(739_000), keyed(739_000));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::config::config;
+use crate::error::ApiError;
+use axum::extract::{ConnectInfo, Request};
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use governor::clock::{Clock, DefaultClock};
+use governor::{DefaultDirectRateLimiter, DefaultKeyedRateLimiter, Quota, RateLimiter};
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+// burst size and refill seconds the limiter was built with
+type Limits = (u32, u64);
+
+// rebuilt when a config reload changes the limits, which starts every client's bucket over
+static PUBLIC_LIMITER: Mutex<Option<(Limits, Arc<DefaultKeyedRateLimiter<IpAddr>>)>> =
+    Mutex::new(None);
+
+fn public_limiter() -> Arc<DefaultKeyedRateLimiter<IpAddr>> {
+    let config = config();
+    let limits = (config.public_api_burst, config.public_api_refill_seconds);
+
+    let mut current = PUBLIC_LIMITER.lock().unwrap();
+    if let Some((built, limiter)) = current.as_ref()
+        && *built == limits
+    {
+        return limiter.clone();
+    }
+
+    // both are validated as non-zero when the config is loaded
+    let quota = Quota::with_period(Duration::from_secs(limits.1))
+        .unwrap()
+        .allow_burst(NonZeroU32::new(limits.0).unwrap());
+    let limiter = Arc::new(RateLimiter::keyed(quota));
+    *current = Some((limits, limiter.clone()));
+    limiter
+}
+
+// concurrent requests and requests per second the upstream throttle was built with
+type UpstreamLimits = (usize, u32);
+
+#[derive(Debug)]
+struct UpstreamThrottle {
+    limits: UpstreamLimits,
+    permits: Arc<Semaphore>,
+    rate: Arc<DefaultDirectRateLimiter>,
+}
+
+// shared by every outbound HGNC request in the process, however many players trigger them
+static UPSTREAM_THROTTLE: Mutex<Option<UpstreamThrottle>> = Mutex::new(None);
+
+fn upstream_throttle() -> (Arc<Semaphore>, Arc<DefaultDirectRateLimiter>) {
+    let config = config();
+    let limits = (
+        config.upstream_concurrency,
+        config.upstream_requests_per_second,
+    );
+
+    let mut current = UPSTREAM_THROTTLE.lock().unwrap();
+    if let Some(throttle) = current.as_ref()
+        && throttle.limits == limits
+    {
+        return (throttle.permits.clone(), throttle.rate.clone());
+    }
+
+    // requests already holding a permit from the old throttle finish under it
+    let throttle = UpstreamThrottle {
+        limits,
+        permits: Arc::new(Semaphore::new(limits.0)),
+        rate: Arc::new(RateLimiter::direct(Quota::per_second(
+            NonZeroU32::new(limits.1).unwrap(),
+        ))),
+    };
+    let built = (throttle.permits.clone(), throttle.rate.clone());
+    *current = Some(throttle);
+    built
+}
+
+// waits for room under both limits; the permit is held until the response has come back
+pub async fn upstream_permit() -> OwnedSemaphorePermit {
+    let (permits, rate) = upstream_throttle();
+    let permit = permits
+        .acquire_owned()
+        .await
+        .expect("the upstream semaphore is never closed");
+    rate.until_ready().await;
+    permit
+}
+
+// drops buckets that have refilled, so one-off clients don't pile up
+fn retain_recent() {
+    if let Some((_, limiter)) = PUBLIC_LIMITER.lock().unwrap().as_ref() {
+        limiter.retain_recent();
+    }
+}
+
+pub async fn sweeper() {
+    let mut interval = tokio::time::interval(Duration::from_secs(60));
+    loop {
+        interval.tick().await;
+        retain_recent();
+    }
+}
+
+pub async fn limit_public(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match public_limiter().check_key(&addr.ip()) {
+        Ok(()) => next.run(request).await,
+        Err(not_until) => {
+            let wait = not_until
+                .wait_time_from(DefaultClock::default().now())
+                .as_secs();
+            let mut response = ApiError::RateLimited {
+                message: format!("Too Many Requests! Wait for {wait}s"),
+                retry_after_secs: Some(wait),
+            }
+            .into_response();
+            response
+                .headers_mut()
+                .insert("x-ratelimit-after", HeaderValue::from(wait));
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::config;
+    use crate::ratelimit::upstream_permit;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_upstream_throttle() {
+        let mut permits = Vec::new();
+        for _ in 0..config().upstream_concurrency {
+            permits.push(upstream_permit().await);
+        }
+        let waiting = tokio::time::timeout(Duration::from_millis(50), upstream_permit()).await;
+        assert!(waiting.is_err());
+
+        permits.pop();
+        let freed = tokio::time::timeout(Duration::from_secs(1), upstream_permit()).await;
+        assert!(freed.is_ok());
+    }
+}
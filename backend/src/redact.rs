@@ -0,0 +1,73 @@
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::LazyLock;
+
+// answers and guesses never reach the logs in plaintext; a salted hash still lets the same symbol
+// be correlated across lines, and the length is kept since it's what most bugs hinge on
+static SALT: LazyLock<String> =
+    LazyLock::new(|| std::env::var("GENEDLE_LOG_SALT").unwrap_or_default());
+
+const UPSTREAM_PATHS: [&str; 3] = ["/search/symbol/", "/fetch/symbol/", "/search/location/"];
+
+pub struct Redacted<'a>(&'a str);
+
+pub fn redact(symbol: &str) -> Redacted<'_> {
+    Redacted(symbol)
+}
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hasher = DefaultHasher::new();
+        SALT.hash(&mut hasher);
+        self.0.hash(&mut hasher);
+
+        write!(
+            f,
+            "<{} chars #{:08x}>",
+            self.0.chars().count(),
+            hasher.finish() as u32
+        )
+    }
+}
+
+// upstream errors quote the request url, which carries the symbol being looked up
+pub fn scrub(message: &str) -> String {
+    let mut scrubbed = message.to_string();
+    for path in UPSTREAM_PATHS {
+        let mut from = 0;
+        while let Some(start) = scrubbed[from..].find(path).map(|i| from + i + path.len()) {
+            let end = scrubbed[start..]
+                .find(|c: char| c == ')' || c == '?' || c.is_whitespace())
+                .map_or(scrubbed.len(), |i| start + i);
+            let redacted = redact(&scrubbed[start..end]).to_string();
+            scrubbed.replace_range(start..end, &redacted);
+            from = start + redacted.len();
+        }
+    }
+
+    scrubbed
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::redact::{redact, scrub};
+
+    #[test]
+    fn test_redact() {
+        let redacted = redact("BRCA1").to_string();
+        assert!(redacted.starts_with("<5 chars #"));
+        assert!(!redacted.contains("BRCA1"));
+        assert_eq!(redacted, redact("BRCA1").to_string());
+        assert_ne!(redacted, redact("BRCA2").to_string());
+
+        let message =
+            "error sending request for url (https://rest.genenames.org/search/symbol/TP53)";
+        let scrubbed = scrub(message);
+        assert!(!scrubbed.contains("TP53"));
+        assert!(scrubbed.starts_with(
+            "error sending request for url (https://rest.genenames.org/search/symbol/<4 chars #"
+        ));
+        assert!(scrubbed.ends_with(">)"));
+        assert_eq!(scrub("timed out"), "timed out");
+    }
+}
@@ -0,0 +1,102 @@
+use crate::config::config;
+use chrono::{DateTime, Datelike, Utc};
+use chrono_tz::Tz;
+
+// days turn over at midnight in GENEDLE_TIMEZONE
+pub fn today() -> u64 {
+    chrono::Utc::now()
+        .with_timezone(&config().timezone)
+        .num_days_from_ce() as u64
+}
+
+// the next midnight in the given timezone; a midnight skipped by a clock change rolls over at the
+// first moment of the new day instead
+pub fn next_rollover(now: DateTime<Utc>, timezone: Tz) -> DateTime<Utc> {
+    let tomorrow = now.with_timezone(&timezone).date_naive() + chrono::Days::new(1);
+    (0..24)
+        .find_map(|hour| {
+            tomorrow
+                .and_hms_opt(hour, 0, 0)?
+                .and_local_timezone(timezone)
+                .earliest()
+        })
+        .map_or(now + chrono::Duration::hours(1), |midnight| {
+            midnight.with_timezone(&Utc)
+        })
+}
+
+pub fn until_rollover() -> std::time::Duration {
+    let now = Utc::now();
+    (next_rollover(now, config().timezone) - now)
+        .to_std()
+        .unwrap_or_default()
+}
+
+pub fn date(day: u64) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::from_num_days_from_ce_opt(i32::try_from(day).ok()?)
+}
+
+// day 1 of the common era was a Monday, so weeks roll over on Mondays
+pub fn this_week() -> u64 {
+    (today() - 1) / 7
+}
+
+fn epoch() -> u64 {
+    config().puzzle_epoch.num_days_from_ce() as u64
+}
+
+// the number players see for a day's puzzle, counting the epoch as #1; nothing before it has one
+pub fn puzzle_number(day: u64) -> Option<u64> {
+    day.checked_sub(epoch()).map(|days| days + 1)
+}
+
+// weeks are numbered the same way, from the week the epoch falls in
+pub fn week_number(week: u64) -> Option<u64> {
+    week.checked_sub((epoch() - 1) / 7).map(|weeks| weeks + 1)
+}
+
+// "Genedle #12", or just the name for a puzzle from before numbering began
+pub fn numbered(name: &str, number: Option<u64>) -> String {
+    match number {
+        Some(number) => format!("{name} #{number}"),
+        None => name.to_string(),
+    }
+}
+
+pub const PRACTICE_KEY_MIN: u64 = u32::MAX as u64;
+
+// practice puzzles are minted far above any day number, so any key up to today is a daily puzzle
+pub fn is_daily(key: u64) -> bool {
+    key <= today()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schedule::next_rollover;
+    use chrono::{TimeZone, Utc};
+    use chrono_tz::Tz;
+
+    #[test]
+    fn test_next_rollover() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 8, 20, 30, 0).unwrap();
+        assert_eq!(
+            next_rollover(now, Tz::UTC),
+            Utc.with_ymd_and_hms(2025, 3, 9, 0, 0, 0).unwrap()
+        );
+        // already the 9th in Tokyo
+        assert_eq!(
+            next_rollover(now, Tz::Asia__Tokyo),
+            Utc.with_ymd_and_hms(2025, 3, 9, 15, 0, 0).unwrap()
+        );
+        assert_eq!(
+            next_rollover(now, Tz::America__New_York),
+            Utc.with_ymd_and_hms(2025, 3, 9, 5, 0, 0).unwrap()
+        );
+
+        // Havana springs forward at midnight, so the 9th starts at 1am
+        assert_eq!(
+            next_rollover(now, Tz::America__Havana),
+            Utc.with_ymd_and_hms(2025, 3, 9, 5, 0, 0).unwrap()
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use crate::games::{genections, genedle, spelling_gene};
+use crate::schedule;
+use async_trait::async_trait;
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tower_sessions::SessionStore;
+use tower_sessions::cookie::time::OffsetDateTime;
+use tower_sessions::session::{Id, Record};
+use tower_sessions::session_store::{self, ExpiredDeletion};
+
+struct TrackedGame {
+    name: &'static str,
+    key: &'static str,
+    period: fn(&Value) -> Option<u64>,
+    current: fn() -> u64,
+}
+
+const TRACKED_GAMES: [TrackedGame; 4] = [
+    TrackedGame {
+        name: "genedle",
+        key: genedle::WORD_KEY,
+        period: Value::as_u64,
+        current: schedule::today,
+    },
+    TrackedGame {
+        name: "genedle_weekly",
+        key: genedle::WEEKLY_KEY,
+        period: |value| value.get("week").and_then(Value::as_u64),
+        current: schedule::this_week,
+    },
+    TrackedGame {
+        name: "genections",
+        key: genections::GAME_KEY,
+        period: |value| value.get("day").and_then(Value::as_u64),
+        current: schedule::today,
+    },
+    TrackedGame {
+        name: "spelling_gene",
+        key: spelling_gene::GAME_KEY,
+        period: |value| value.get("seed").and_then(Value::as_u64),
+        current: spelling_gene::daily_seed,
+    },
+];
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct SessionMetrics {
+    pub active_sessions: usize,
+    pub in_progress: BTreeMap<&'static str, usize>,
+}
+
+// the in-memory store forgets every session on restart, so it's only meant for development
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SessionBackend {
+    Memory,
+    Sqlite(String),
+}
+
+impl FromStr for SessionBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            _ if s == "memory" => Ok(SessionBackend::Memory),
+            Some(("sqlite", path)) if !path.is_empty() => {
+                Ok(SessionBackend::Sqlite(path.to_string()))
+            }
+            _ => Err(format!("Unknown session store {s}")),
+        }
+    }
+}
+
+// sessions stay in memory for metrics and fast lookups; a database, when configured, is written
+// through on every change and read back on startup
+#[derive(Clone, Debug, Default)]
+pub struct TrackedStore {
+    records: Arc<Mutex<HashMap<Id, Record>>>,
+    database: Option<Arc<std::sync::Mutex<Connection>>>,
+}
+
+fn is_active(expiry_date: OffsetDateTime) -> bool {
+    expiry_date > OffsetDateTime::now_utc()
+}
+
+fn backend_error(err: impl ToString) -> session_store::Error {
+    session_store::Error::Backend(err.to_string())
+}
+
+impl TrackedStore {
+    pub fn open(backend: &SessionBackend) -> Result<Self, anyhow::Error> {
+        let SessionBackend::Sqlite(path) = backend else {
+            return Ok(TrackedStore::default());
+        };
+
+        let connection = Connection::open(path)?;
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS sessions (
+                id TEXT PRIMARY KEY,
+                expiry INTEGER NOT NULL,
+                record TEXT NOT NULL
+            )",
+            (),
+        )?;
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let records = connection
+            .prepare("SELECT record FROM sessions WHERE expiry > ?1")?
+            .query_map([now], |row| row.get::<_, String>(0))?
+            .filter_map(|record| serde_json::from_str::<Record>(&record.ok()?).ok())
+            .map(|record| (record.id, record))
+            .collect();
+
+        Ok(TrackedStore {
+            records: Arc::new(Mutex::new(records)),
+            database: Some(Arc::new(std::sync::Mutex::new(connection))),
+        })
+    }
+
+    async fn with_database<F>(&self, f: F) -> session_store::Result<()>
+    where
+        F: FnOnce(&Connection) -> rusqlite::Result<()> + Send + 'static,
+    {
+        let Some(database) = self.database.clone() else {
+            return Ok(());
+        };
+
+        tokio::task::spawn_blocking(move || f(&database.lock().unwrap()))
+            .await
+            .map_err(backend_error)?
+            .map_err(backend_error)
+    }
+
+    async fn persist(&self, records: Vec<Record>) -> session_store::Result<()> {
+        let rows = records
+            .iter()
+            .map(|record| {
+                serde_json::to_string(record).map(|json| {
+                    (
+                        record.id.to_string(),
+                        record.expiry_date.unix_timestamp(),
+                        json,
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| session_store::Error::Encode(err.to_string()))?;
+
+        self.with_database(move |connection| {
+            let mut statement = connection.prepare_cached(
+                "INSERT OR REPLACE INTO sessions (id, expiry, record) VALUES (?1, ?2, ?3)",
+            )?;
+            for row in rows {
+                statement.execute(row)?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    // a memory store is as reachable as the process; a database has to answer a query
+    pub async fn ping(&self) -> session_store::Result<()> {
+        self.with_database(|connection| {
+            connection.query_row("SELECT 1", (), |_| Ok(()))?;
+            Ok(())
+        })
+        .await
+    }
+
+    // VACUUM INTO writes a compacted copy without blocking writers for long; memory stores have
+    // nothing to back up
+    pub async fn backup(&self, path: &std::path::Path) -> session_store::Result<bool> {
+        if self.database.is_none() {
+            return Ok(false);
+        }
+
+        let path = path.to_string_lossy().into_owned();
+        self.with_database(move |connection| {
+            connection.execute("VACUUM INTO ?1", [path])?;
+            Ok(())
+        })
+        .await?;
+
+        Ok(true)
+    }
+
+    pub async fn metrics(&self) -> SessionMetrics {
+        let records = self.records.lock().await;
+
+        let mut metrics = SessionMetrics::default();
+        for record in records
+            .values()
+            .filter(|record| is_active(record.expiry_date))
+        {
+            metrics.active_sessions += 1;
+
+            for game in &TRACKED_GAMES {
+                let current = record
+                    .data
+                    .get(game.key)
+                    .and_then(game.period)
+                    .is_some_and(|period| period == (game.current)());
+
+                *metrics.in_progress.entry(game.name).or_default() += current as usize;
+            }
+        }
+
+        metrics
+    }
+
+    pub async fn delete_orphaned_games(&self) -> session_store::Result<()> {
+        let mut changed = Vec::new();
+        let mut records = self.records.lock().await;
+
+        for record in records.values_mut() {
+            let before = record.data.len();
+            for game in &TRACKED_GAMES {
+                let stale = record
+                    .data
+                    .get(game.key)
+                    .and_then(game.period)
+                    .is_some_and(|period| period < (game.current)());
+
+                if stale {
+                    record.data.remove(game.key);
+                }
+            }
+
+            if record.data.len() != before {
+                changed.push(record.clone());
+            }
+        }
+        drop(records);
+
+        self.persist(changed).await
+    }
+
+    pub async fn collect_garbage(self, period: tokio::time::Duration) {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+
+            let _ = self.delete_expired().await;
+            let _ = self.delete_orphaned_games().await;
+        }
+    }
+}
+
+#[async_trait]
+impl SessionStore for TrackedStore {
+    async fn create(&self, record: &mut Record) -> session_store::Result<()> {
+        let mut records = self.records.lock().await;
+        while records.contains_key(&record.id) {
+            record.id = Id::default();
+        }
+        records.insert(record.id, record.clone());
+        drop(records);
+
+        self.persist(vec![record.clone()]).await
+    }
+
+    async fn save(&self, record: &Record) -> session_store::Result<()> {
+        self.records.lock().await.insert(record.id, record.clone());
+        self.persist(vec![record.clone()]).await
+    }
+
+    async fn load(&self, session_id: &Id) -> session_store::Result<Option<Record>> {
+        Ok(self
+            .records
+            .lock()
+            .await
+            .get(session_id)
+            .filter(|record| is_active(record.expiry_date))
+            .cloned())
+    }
+
+    async fn delete(&self, session_id: &Id) -> session_store::Result<()> {
+        self.records.lock().await.remove(session_id);
+
+        let id = session_id.to_string();
+        self.with_database(move |connection| {
+            connection.execute("DELETE FROM sessions WHERE id = ?1", [id])?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl ExpiredDeletion for TrackedStore {
+    async fn delete_expired(&self) -> session_store::Result<()> {
+        self.records
+            .lock()
+            .await
+            .retain(|_, record| is_active(record.expiry_date));
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        self.with_database(move |connection| {
+            connection.execute("DELETE FROM sessions WHERE expiry <= ?1", [now])?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sessions::{SessionBackend, TrackedStore};
+    use tower_sessions::SessionStore;
+    use tower_sessions::cookie::time::{Duration, OffsetDateTime};
+    use tower_sessions::session::{Id, Record};
+
+    #[test]
+    fn test_session_backend() {
+        assert_eq!("memory".parse(), Ok(SessionBackend::Memory));
+        assert_eq!(
+            "sqlite:sessions.db".parse(),
+            Ok(SessionBackend::Sqlite("sessions.db".to_string()))
+        );
+        assert!("sqlite:".parse::<SessionBackend>().is_err());
+        assert!("redis://localhost".parse::<SessionBackend>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_survives_restart() {
+        let path =
+            std::env::temp_dir().join(format!("genedle-sessions-{}.db", rand::random::<u64>()));
+        let backend = SessionBackend::Sqlite(path.to_string_lossy().into_owned());
+
+        let mut record = Record {
+            id: Id::default(),
+            data: [("genedle.word".to_string(), 20000.into())].into(),
+            expiry_date: OffsetDateTime::now_utc() + Duration::days(1),
+        };
+        let store = TrackedStore::open(&backend).unwrap();
+        store.create(&mut record).await.unwrap();
+        drop(store);
+
+        let restarted = TrackedStore::open(&backend).unwrap();
+        assert_eq!(
+            restarted.load(&record.id).await.unwrap(),
+            Some(record.clone())
+        );
+
+        restarted.delete(&record.id).await.unwrap();
+        let restarted = TrackedStore::open(&backend).unwrap();
+        assert_eq!(restarted.load(&record.id).await.unwrap(), None);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
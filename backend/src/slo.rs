@@ -0,0 +1,290 @@
+use crate::config::config;
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::str::FromStr;
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+
+// objectives are kept in hundredths of a percent, so 9950 is 99.5%
+const BASIS: u32 = 10_000;
+
+// a route's objectives, written "route:latency_ms:latency_target:availability_target", e.g.
+// "/games/genedle:1000:99:99.9"; a request is slow over latency_ms and an error if it's a 5xx
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Slo {
+    pub route: String,
+    pub latency_ms: u64,
+    pub latency_target: u32,
+    pub availability_target: u32,
+}
+
+fn parse_target(target: &str) -> Option<u32> {
+    let percent: f64 = target.parse().ok()?;
+    (percent > 0.0 && percent < 100.0).then(|| (percent * f64::from(BASIS) / 100.0).round() as u32)
+}
+
+impl FromStr for Slo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid SLO {s}");
+        let [route, latency_ms, latency_target, availability_target] =
+            s.split(':').collect::<Vec<_>>()[..]
+        else {
+            return Err(invalid());
+        };
+        if !route.starts_with('/') {
+            return Err(invalid());
+        }
+
+        Ok(Slo {
+            route: route.to_string(),
+            latency_ms: latency_ms.parse().map_err(|_| invalid())?,
+            latency_target: parse_target(latency_target).ok_or_else(invalid)?,
+            availability_target: parse_target(availability_target).ok_or_else(invalid)?,
+        })
+    }
+}
+
+// one minute of a route's requests, judged against the objectives in force at the time
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+struct Bucket {
+    minute: i64,
+    requests: u64,
+    slow: u64,
+    errors: u64,
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct SloReport {
+    pub route: String,
+    pub latency_ms: u64,
+    pub latency_target: f64,
+    pub availability_target: f64,
+    pub requests: u64,
+    pub slow: u64,
+    pub errors: u64,
+    pub latency_compliance: f64,
+    pub availability_compliance: f64,
+    // how fast the error budget is going; 1 spends exactly the budget over the window
+    pub latency_burn_rate: f64,
+    pub error_burn_rate: f64,
+}
+
+static WINDOWS: LazyLock<Mutex<BTreeMap<String, VecDeque<Bucket>>>> =
+    LazyLock::new(Default::default);
+
+fn current_minute() -> i64 {
+    chrono::Utc::now().timestamp() / 60
+}
+
+fn prune(buckets: &mut VecDeque<Bucket>, minute: i64) {
+    let window = config().slo_window_minutes as i64;
+    while buckets
+        .front()
+        .is_some_and(|bucket| bucket.minute <= minute - window)
+    {
+        buckets.pop_front();
+    }
+}
+
+fn record(slo: &Slo, elapsed_ms: u64, error: bool) {
+    let minute = current_minute();
+    let mut windows = WINDOWS.lock().unwrap();
+    let buckets = windows.entry(slo.route.clone()).or_default();
+    prune(buckets, minute);
+
+    if buckets.back().is_none_or(|bucket| bucket.minute != minute) {
+        buckets.push_back(Bucket {
+            minute,
+            ..Default::default()
+        });
+    }
+    let bucket = buckets.back_mut().unwrap();
+    bucket.requests += 1;
+    bucket.slow += u64::from(elapsed_ms > slo.latency_ms);
+    bucket.errors += u64::from(error);
+}
+
+// routes without an objective aren't tracked, so the windows stay as small as the config
+pub async fn track(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let started = Instant::now();
+    let response = next.run(request).await;
+
+    let Some(route) = route else {
+        return response;
+    };
+    let route = route
+        .strip_prefix(config().base_path.as_str())
+        .unwrap_or(&route);
+    if let Some(slo) = config().slos.iter().find(|slo| slo.route == route) {
+        record(
+            slo,
+            started.elapsed().as_millis() as u64,
+            response.status().is_server_error(),
+        );
+    }
+
+    response
+}
+
+fn percent(basis: u32) -> f64 {
+    f64::from(basis) * 100.0 / f64::from(BASIS)
+}
+
+// the share of bad requests over the share the objective allows
+fn burn_rate(bad: u64, requests: u64, target: u32) -> f64 {
+    if requests == 0 {
+        return 0.0;
+    }
+    let budget = f64::from(BASIS - target) / f64::from(BASIS);
+    bad as f64 / requests as f64 / budget
+}
+
+fn compliance(bad: u64, requests: u64) -> f64 {
+    if requests == 0 {
+        return 100.0;
+    }
+    (requests - bad) as f64 * 100.0 / requests as f64
+}
+
+fn report(slo: &Slo, buckets: &VecDeque<Bucket>) -> SloReport {
+    let totals = buckets
+        .iter()
+        .fold(Bucket::default(), |total, bucket| Bucket {
+            minute: total.minute,
+            requests: total.requests + bucket.requests,
+            slow: total.slow + bucket.slow,
+            errors: total.errors + bucket.errors,
+        });
+
+    SloReport {
+        route: slo.route.clone(),
+        latency_ms: slo.latency_ms,
+        latency_target: percent(slo.latency_target),
+        availability_target: percent(slo.availability_target),
+        requests: totals.requests,
+        slow: totals.slow,
+        errors: totals.errors,
+        latency_compliance: compliance(totals.slow, totals.requests),
+        availability_compliance: compliance(totals.errors, totals.requests),
+        latency_burn_rate: burn_rate(totals.slow, totals.requests, slo.latency_target),
+        error_burn_rate: burn_rate(totals.errors, totals.requests, slo.availability_target),
+    }
+}
+
+// over the last GENEDLE_SLO_WINDOW_MINUTES, for every configured route whether or not it's been hit
+pub fn reports() -> Vec<SloReport> {
+    let minute = current_minute();
+    let mut windows = WINDOWS.lock().unwrap();
+
+    config()
+        .slos
+        .iter()
+        .map(|slo| {
+            let buckets = windows.entry(slo.route.clone()).or_default();
+            prune(buckets, minute);
+            report(slo, buckets)
+        })
+        .collect()
+}
+
+// a gauge's latency and availability values for one route
+type Objectives = fn(&SloReport) -> [f64; 2];
+
+// the Prometheus text format, for scraping alongside the JSON view
+pub fn prometheus(reports: &[SloReport]) -> String {
+    let gauges: [(&str, &str, Objectives); 2] = [
+        (
+            "genedle_slo_burn_rate",
+            "Error budget burn rate over the SLO window",
+            |report| [report.latency_burn_rate, report.error_burn_rate],
+        ),
+        (
+            "genedle_slo_compliance_percent",
+            "Share of requests meeting the objective over the SLO window",
+            |report| [report.latency_compliance, report.availability_compliance],
+        ),
+    ];
+
+    let mut text = String::new();
+    for (name, help, value) in gauges {
+        let _ = writeln!(text, "# HELP {name} {help}");
+        let _ = writeln!(text, "# TYPE {name} gauge");
+        for report in reports {
+            for (objective, value) in ["latency", "availability"].into_iter().zip(value(report)) {
+                let _ = writeln!(
+                    text,
+                    "{name}{{route=\"{}\",objective=\"{objective}\"}} {value}",
+                    report.route
+                );
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::slo::{Bucket, Slo, burn_rate, prometheus, report};
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_parse_slo() {
+        assert_eq!(
+            "/games/genedle:1000:99:99.95".parse(),
+            Ok(Slo {
+                route: "/games/genedle".to_string(),
+                latency_ms: 1000,
+                latency_target: 9900,
+                availability_target: 9995,
+            })
+        );
+        assert!("/games/genedle:1000:99".parse::<Slo>().is_err());
+        assert!("games/genedle:1000:99:99.9".parse::<Slo>().is_err());
+        assert!("/games/genedle:1000:100:99.9".parse::<Slo>().is_err());
+        assert!("/games/genedle:fast:99:99.9".parse::<Slo>().is_err());
+    }
+
+    #[test]
+    fn test_report() {
+        assert_eq!(burn_rate(0, 0, 9900), 0.0);
+        // 2% slow against a 1% budget burns it twice as fast as allowed
+        assert!((burn_rate(2, 100, 9900) - 2.0).abs() < 1e-9);
+
+        let slo: Slo = "/games/spelling-gene:2000:99:99.9".parse().unwrap();
+        let buckets = VecDeque::from([
+            Bucket {
+                minute: 1,
+                requests: 600,
+                slow: 3,
+                errors: 0,
+            },
+            Bucket {
+                minute: 2,
+                requests: 400,
+                slow: 2,
+                errors: 1,
+            },
+        ]);
+        let report = report(&slo, &buckets);
+        assert_eq!(report.requests, 1000);
+        assert!((report.latency_compliance - 99.5).abs() < 1e-9);
+        assert!((report.latency_burn_rate - 0.5).abs() < 1e-9);
+        assert!((report.error_burn_rate - 1.0).abs() < 1e-9);
+
+        let text = prometheus(&[report]);
+        assert!(text.contains("# TYPE genedle_slo_burn_rate gauge"));
+        assert!(text.contains(
+            "genedle_slo_burn_rate{route=\"/games/spelling-gene\",objective=\"latency\"} 0.5"
+        ));
+    }
+}
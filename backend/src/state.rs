@@ -0,0 +1,37 @@
+use crate::config::config;
+use crate::sessions::TrackedStore;
+use axum::extract::FromRef;
+use reqwest::Client;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct AppState {
+    pub client: Client,
+    pub sessions: TrackedStore,
+}
+
+impl AppState {
+    // one pooled client so every outbound call shares connections and configuration; the read
+    // timeout is per read, so a large download that keeps streaming isn't cut off
+    pub fn new(sessions: TrackedStore) -> Result<Self, reqwest::Error> {
+        let client = Client::builder()
+            .user_agent(concat!("genedle/", env!("CARGO_PKG_VERSION")))
+            .connect_timeout(Duration::from_millis(config().upstream_connect_timeout_ms))
+            .read_timeout(Duration::from_millis(config().upstream_read_timeout_ms))
+            .build()?;
+
+        Ok(AppState { client, sessions })
+    }
+}
+
+impl FromRef<AppState> for Client {
+    fn from_ref(state: &AppState) -> Self {
+        state.client.clone()
+    }
+}
+
+impl FromRef<AppState> for TrackedStore {
+    fn from_ref(state: &AppState) -> Self {
+        state.sessions.clone()
+    }
+}
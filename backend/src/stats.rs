@@ -0,0 +1,528 @@
+use crate::storage::storage;
+use crate::themes::Theme;
+use genedle_core::spelling_gene::ScoringPreset;
+pub use genedle_core::streak::Streak;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct WeeklyStats {
+    pub played: usize,
+    pub won: usize,
+    pub streak: Streak,
+}
+
+// a freeze is earned every FREEZE_EVERY daily wins in a row and bridges one missed day, never a
+// lost game
+pub const FREEZE_EVERY: usize = 7;
+pub const MAX_FREEZES: usize = 2;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct GenedleRecord {
+    pub played: usize,
+    pub won: usize,
+    // guesses taken on each win
+    pub distribution: BTreeMap<usize, usize>,
+    pub streak: Streak,
+    #[serde(default)]
+    pub freezes: usize,
+}
+
+// archive plays have no streak to break, so replays are caught by remembering each puzzle
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ArchiveRecord {
+    pub played: usize,
+    pub won: usize,
+    pub distribution: BTreeMap<usize, usize>,
+    pub puzzles: BTreeSet<u64>,
+}
+
+// the bonus puzzle for finishing both daily games; it's only ever counted once a day
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct CrossoverRecord {
+    pub played: usize,
+    pub solved: usize,
+    pub xp: u64,
+    pub last_day: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct SpellingGeneRecord {
+    pub streak: Streak,
+    pub queen_genes: usize,
+    pub last_queen_gene: Option<u64>,
+    // scores from different presets aren't comparable, so bests are kept per preset
+    #[serde(default)]
+    pub best_scores: BTreeMap<ScoringPreset, u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct GenectionsRecord {
+    pub played: usize,
+    pub won: usize,
+    pub perfect_games: usize,
+    pub streak: Streak,
+    // mistakes made on each solved day
+    pub calendar: BTreeMap<u64, usize>,
+}
+
+// themes are copied in at solve time, so retagging an answer later doesn't rewrite collections
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ThemeCollection {
+    pub answers: BTreeMap<String, BTreeSet<Theme>>,
+}
+
+// only pair counts are kept, never who made them
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct GenectionsMistakes {
+    pub games: u64,
+    pub pairs: BTreeMap<String, u64>,
+}
+
+// shared by every player of a puzzle, so it only ever holds totals
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct PuzzleOutcomes {
+    pub finished: u64,
+    pub solved: u64,
+    // summed over solved games only
+    pub guesses: u64,
+}
+
+impl GenedleRecord {
+    // returns whether the puzzle counted; each daily puzzle only counts once
+    fn record(&mut self, puzzle: u64, guesses: Option<usize>) -> bool {
+        if self.streak.last_period == Some(puzzle) {
+            return false;
+        }
+
+        self.played += 1;
+        if let Some(guesses) = guesses {
+            self.won += 1;
+            *self.distribution.entry(guesses).or_default() += 1;
+        }
+
+        let won = guesses.is_some();
+        if won
+            && self.streak.current > 0
+            && self.freezes > 0
+            && self
+                .streak
+                .last_period
+                .is_some_and(|last| last + 2 == puzzle)
+        {
+            self.freezes -= 1;
+            self.streak.last_period = Some(puzzle - 1);
+        }
+        // daily puzzle keys are days from the common era, so a gap in keys is a missed day
+        self.streak.record(puzzle, won);
+        if won && self.streak.current.is_multiple_of(FREEZE_EVERY) {
+            self.freezes = (self.freezes + 1).min(MAX_FREEZES);
+        }
+
+        true
+    }
+
+    // a streak stays alive through today if the last win was yesterday, or the day before with a
+    // freeze banked to cover the gap
+    pub fn streak_as_of(&self, day: u64) -> Streak {
+        match self.streak.last_period {
+            Some(last) if self.freezes > 0 && last + 2 == day => self.streak,
+            _ => self.streak.as_of(day),
+        }
+    }
+}
+
+fn weekly_stats_key(player: u64) -> String {
+    format!("stats.genedle-weekly.{player:016x}")
+}
+
+fn participation_key(game: &str, puzzle: u64) -> String {
+    format!("participation.{game}.{puzzle}")
+}
+
+fn completions_key(game: &str, puzzle: u64) -> String {
+    format!("completions.{game}.{puzzle}")
+}
+
+fn outcomes_key(game: &str, puzzle: u64) -> String {
+    format!("outcomes.{game}.{puzzle}")
+}
+
+fn answer_history_key(player: u64) -> String {
+    format!("history.genedle.{player:016x}")
+}
+
+fn genedle_key(player: u64) -> String {
+    format!("stats.genedle.{player:016x}")
+}
+
+fn genedle_archive_key(player: u64) -> String {
+    format!("stats.genedle-archive.{player:016x}")
+}
+
+fn themes_key(player: u64) -> String {
+    format!("stats.genedle-themes.{player:016x}")
+}
+
+fn hints_key(player: u64, day: u64) -> String {
+    format!("hints.genedle.{player:016x}.{day}")
+}
+
+fn genections_key(player: u64) -> String {
+    format!("stats.genections.{player:016x}")
+}
+
+fn genections_mistakes_key(day: u64) -> String {
+    format!("stats.genections-mistakes.{day}")
+}
+
+fn spelling_gene_key(player: u64) -> String {
+    format!("stats.spelling-gene.{player:016x}")
+}
+
+fn crossover_key(player: u64) -> String {
+    format!("stats.crossover.{player:016x}")
+}
+
+pub fn participation(game: &str, puzzle: u64) -> u64 {
+    storage()
+        .get(&participation_key(game, puzzle))
+        .unwrap_or_default()
+}
+
+pub fn record_participation(game: &str, puzzle: u64) -> Result<u64, anyhow::Error> {
+    storage().update(&participation_key(game, puzzle), |count: &mut u64| {
+        *count += 1
+    })
+}
+
+// for games that can't be lost, how many players saw a puzzle all the way through
+pub fn completions(game: &str, puzzle: u64) -> u64 {
+    storage()
+        .get(&completions_key(game, puzzle))
+        .unwrap_or_default()
+}
+
+fn record_completion(game: &str, puzzle: u64) -> Result<u64, anyhow::Error> {
+    storage().update(&completions_key(game, puzzle), |count: &mut u64| {
+        *count += 1
+    })
+}
+
+pub fn outcomes(game: &str, puzzle: u64) -> PuzzleOutcomes {
+    storage()
+        .get(&outcomes_key(game, puzzle))
+        .unwrap_or_default()
+}
+
+// callers dedupe per player first, so a replayed puzzle isn't counted twice
+fn record_outcome(
+    game: &str,
+    puzzle: u64,
+    guesses: Option<usize>,
+) -> Result<PuzzleOutcomes, anyhow::Error> {
+    storage().update(
+        &outcomes_key(game, puzzle),
+        |outcomes: &mut PuzzleOutcomes| {
+            outcomes.finished += 1;
+            if let Some(guesses) = guesses {
+                outcomes.solved += 1;
+                outcomes.guesses += guesses as u64;
+            }
+        },
+    )
+}
+
+pub fn spelling_gene_record(player: u64) -> SpellingGeneRecord {
+    storage()
+        .get::<SpellingGeneRecord>(&spelling_gene_key(player))
+        .unwrap_or_default()
+}
+
+pub fn record_spelling_gene(
+    player: u64,
+    day: u64,
+    complete: bool,
+    scoring: ScoringPreset,
+    score: u32,
+) -> Result<SpellingGeneRecord, anyhow::Error> {
+    let mut completed = false;
+    let record = storage().update(
+        &spelling_gene_key(player),
+        |record: &mut SpellingGeneRecord| {
+            record.streak.record(day, true);
+
+            if complete && record.last_queen_gene != Some(day) {
+                record.queen_genes += 1;
+                record.last_queen_gene = Some(day);
+                completed = true;
+            }
+
+            let best = record.best_scores.entry(scoring).or_default();
+            *best = (*best).max(score);
+        },
+    )?;
+
+    if completed {
+        record_completion("spelling-gene", day)?;
+    }
+
+    Ok(record)
+}
+
+pub fn answer_history(player: u64) -> BTreeSet<String> {
+    storage()
+        .get(&answer_history_key(player))
+        .unwrap_or_default()
+}
+
+pub fn record_answer(player: u64, answer: &str) -> Result<(), anyhow::Error> {
+    storage().update(
+        &answer_history_key(player),
+        |history: &mut BTreeSet<String>| {
+            history.insert(answer.to_string());
+        },
+    )?;
+
+    Ok(())
+}
+
+pub fn genedle_record(player: u64) -> GenedleRecord {
+    storage()
+        .get::<GenedleRecord>(&genedle_key(player))
+        .unwrap_or_default()
+}
+
+pub fn record_genedle(
+    player: u64,
+    puzzle: u64,
+    guesses: Option<usize>,
+) -> Result<GenedleRecord, anyhow::Error> {
+    let mut counted = false;
+    let record = storage().update(&genedle_key(player), |record: &mut GenedleRecord| {
+        counted = record.record(puzzle, guesses);
+    })?;
+
+    if counted {
+        record_outcome("genedle", puzzle, guesses)?;
+    }
+
+    Ok(record)
+}
+
+// every player's daily record, keyed by player code
+pub fn genedle_records() -> Vec<(String, GenedleRecord)> {
+    storage()
+        .keys("stats.genedle.")
+        .into_iter()
+        .filter_map(|key| {
+            let record = storage().get(&key)?;
+            Some((key.strip_prefix("stats.genedle.")?.to_string(), record))
+        })
+        .collect()
+}
+
+pub fn archive_record(player: u64) -> ArchiveRecord {
+    storage()
+        .get::<ArchiveRecord>(&genedle_archive_key(player))
+        .unwrap_or_default()
+}
+
+pub fn record_archive(
+    player: u64,
+    puzzle: u64,
+    guesses: Option<usize>,
+) -> Result<ArchiveRecord, anyhow::Error> {
+    storage().update(
+        &genedle_archive_key(player),
+        |record: &mut ArchiveRecord| {
+            if !record.puzzles.insert(puzzle) {
+                return;
+            }
+
+            record.played += 1;
+            if let Some(guesses) = guesses {
+                record.won += 1;
+                *record.distribution.entry(guesses).or_default() += 1;
+            }
+        },
+    )
+}
+
+pub fn theme_collection(player: u64) -> ThemeCollection {
+    storage()
+        .get::<ThemeCollection>(&themes_key(player))
+        .unwrap_or_default()
+}
+
+pub fn record_themes(
+    player: u64,
+    answer: &str,
+    themes: &[Theme],
+) -> Result<ThemeCollection, anyhow::Error> {
+    // untagged answers aren't part of the collection, so they don't create a record either
+    if themes.is_empty() {
+        return Ok(theme_collection(player));
+    }
+
+    storage().update(&themes_key(player), |collection: &mut ThemeCollection| {
+        collection
+            .answers
+            .entry(answer.to_string())
+            .or_default()
+            .extend(themes.iter().cloned());
+    })
+}
+
+pub fn hints_used(player: u64, day: u64) -> usize {
+    storage().get(&hints_key(player, day)).unwrap_or_default()
+}
+
+// budgets reset daily, so the counters expire rather than waiting on the nightly prune
+pub fn record_hint(player: u64, day: u64) -> Result<usize, anyhow::Error> {
+    let used = hints_used(player, day) + 1;
+    storage().insert_with_ttl(&hints_key(player, day), &used, chrono::Duration::days(2))?;
+
+    Ok(used)
+}
+
+pub fn crossover_record(player: u64) -> CrossoverRecord {
+    storage()
+        .get::<CrossoverRecord>(&crossover_key(player))
+        .unwrap_or_default()
+}
+
+pub fn record_crossover(
+    player: u64,
+    day: u64,
+    solved: bool,
+    xp: u64,
+) -> Result<CrossoverRecord, anyhow::Error> {
+    storage().update(&crossover_key(player), |record: &mut CrossoverRecord| {
+        if record.last_day == Some(day) {
+            return;
+        }
+
+        record.last_day = Some(day);
+        record.played += 1;
+        record.solved += solved as usize;
+        record.xp += xp;
+    })
+}
+
+pub fn genections_record(player: u64) -> GenectionsRecord {
+    storage()
+        .get::<GenectionsRecord>(&genections_key(player))
+        .unwrap_or_default()
+}
+
+pub fn record_genections(
+    player: u64,
+    day: u64,
+    won: bool,
+    mistakes: usize,
+    guesses: usize,
+) -> Result<GenectionsRecord, anyhow::Error> {
+    let mut counted = false;
+    let record = storage().update(&genections_key(player), |record: &mut GenectionsRecord| {
+        if record.streak.last_period == Some(day) {
+            return;
+        }
+
+        counted = true;
+        record.played += 1;
+        if won {
+            record.won += 1;
+            record.perfect_games += (mistakes == 0) as usize;
+            record.calendar.insert(day, mistakes);
+        }
+        record.streak.record(day, won);
+    })?;
+
+    if counted {
+        record_outcome("genections", day, won.then_some(guesses))?;
+    }
+
+    Ok(record)
+}
+
+pub fn genections_mistakes(day: u64) -> GenectionsMistakes {
+    storage()
+        .get(&genections_mistakes_key(day))
+        .unwrap_or_default()
+}
+
+pub fn record_genections_mistakes(
+    day: u64,
+    pairs: &BTreeSet<String>,
+) -> Result<GenectionsMistakes, anyhow::Error> {
+    storage().update(
+        &genections_mistakes_key(day),
+        |mistakes: &mut GenectionsMistakes| {
+            mistakes.games += 1;
+            for pair in pairs {
+                *mistakes.pairs.entry(pair.clone()).or_default() += 1;
+            }
+        },
+    )
+}
+
+pub fn weekly_stats(player: u64) -> WeeklyStats {
+    storage()
+        .get::<WeeklyStats>(&weekly_stats_key(player))
+        .unwrap_or_default()
+}
+
+pub fn record_weekly(
+    player: u64,
+    week: u64,
+    guesses: Option<usize>,
+) -> Result<WeeklyStats, anyhow::Error> {
+    let mut counted = false;
+    let stats = storage().update(&weekly_stats_key(player), |stats: &mut WeeklyStats| {
+        if stats.streak.last_period != Some(week) {
+            counted = true;
+            stats.played += 1;
+            stats.won += guesses.is_some() as usize;
+            stats.streak.record(week, guesses.is_some());
+        }
+    })?;
+
+    if counted {
+        record_outcome("genedle-weekly", week, guesses)?;
+    }
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stats::{FREEZE_EVERY, GenedleRecord};
+
+    #[test]
+    fn test_streak_freezes() {
+        let mut record = GenedleRecord::default();
+        for day in 0..FREEZE_EVERY as u64 {
+            assert!(record.record(100 + day, Some(3)));
+        }
+        assert!(!record.record(106, Some(3)));
+        assert_eq!((record.streak.current, record.freezes), (7, 1));
+
+        // day 107 is missed, and the freeze keeps the streak alive through day 108
+        assert_eq!(record.streak_as_of(108).current, 7);
+        assert_eq!(record.streak_as_of(109).current, 0);
+        record.record(108, Some(4));
+        assert_eq!((record.streak.current, record.freezes), (8, 0));
+
+        // with no freeze left, the next gap breaks it
+        assert_eq!(record.streak_as_of(109).current, 8);
+        assert_eq!(record.streak_as_of(110).current, 0);
+        record.record(110, Some(2));
+        assert_eq!(record.streak.current, 1);
+
+        // freezes never cover a loss
+        record.record(111, None);
+        assert_eq!(record.streak.current, 0);
+        assert_eq!(record.streak.max, 8);
+    }
+}
@@ -0,0 +1,262 @@
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, LazyLock, Mutex, Weak};
+use std::time::Duration;
+
+// how often an idle flusher checks whether its store is still around
+const FLUSH_POLL: Duration = Duration::from_secs(1);
+// backs off a failing disk instead of spinning on it
+const FLUSH_RETRY: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Entry {
+    value: serde_json::Value,
+    expires_at: Option<i64>,
+}
+
+impl Entry {
+    fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+struct Shared {
+    entries: Mutex<HashMap<String, Entry>>,
+    // set by every change, cleared once a snapshot including it is taken
+    dirty: Mutex<bool>,
+    changed: Condvar,
+    // one writer at a time, so a flush and a sync don't trample the same temp file
+    writing: Mutex<()>,
+}
+
+pub struct Storage {
+    path: Option<PathBuf>,
+    shared: Arc<Shared>,
+}
+
+static STORAGE: LazyLock<Storage> = LazyLock::new(|| {
+    let path = std::env::var("GENEDLE_STORAGE_PATH").unwrap_or_else(|_| "genedle.json".to_string());
+    Storage::open(path)
+});
+
+pub fn storage() -> &'static Storage {
+    &STORAGE
+}
+
+impl Storage {
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+
+        let shared = Arc::new(Shared {
+            entries: Mutex::new(entries),
+            dirty: Mutex::new(false),
+            changed: Condvar::new(),
+            writing: Mutex::new(()),
+        });
+        spawn_flusher(path.clone(), Arc::downgrade(&shared));
+
+        Storage {
+            path: Some(path),
+            shared,
+        }
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let entries = self.shared.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(Utc::now().timestamp()))
+            .and_then(|entry| serde_json::from_value(entry.value.clone()).ok())
+    }
+
+    pub fn insert<T: Serialize>(&self, key: &str, value: &T) -> Result<(), anyhow::Error> {
+        let value = serde_json::to_value(value)?;
+
+        let mut entries = self.shared.entries.lock().unwrap();
+        entries.insert(
+            key.to_string(),
+            Entry {
+                value,
+                expires_at: None,
+            },
+        );
+        self.shared.mark_dirty();
+
+        Ok(())
+    }
+
+    pub fn insert_with_ttl<T: Serialize>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: chrono::Duration,
+    ) -> Result<(), anyhow::Error> {
+        let value = serde_json::to_value(value)?;
+        let expires_at = Some((Utc::now() + ttl).timestamp());
+
+        let mut entries = self.shared.entries.lock().unwrap();
+        entries.insert(key.to_string(), Entry { value, expires_at });
+        self.shared.mark_dirty();
+
+        Ok(())
+    }
+
+    pub fn update<T, F>(&self, key: &str, f: F) -> Result<T, anyhow::Error>
+    where
+        T: Serialize + DeserializeOwned + Default,
+        F: FnOnce(&mut T),
+    {
+        let now = Utc::now().timestamp();
+
+        let mut entries = self.shared.entries.lock().unwrap();
+        let (mut value, expires_at) = match entries.get(key).filter(|entry| !entry.is_expired(now))
+        {
+            Some(entry) => (
+                serde_json::from_value(entry.value.clone())?,
+                entry.expires_at,
+            ),
+            None => (T::default(), None),
+        };
+
+        f(&mut value);
+
+        entries.insert(
+            key.to_string(),
+            Entry {
+                value: serde_json::to_value(&value)?,
+                expires_at,
+            },
+        );
+        self.shared.mark_dirty();
+
+        Ok(value)
+    }
+
+    pub fn keys(&self, prefix: &str) -> Vec<String> {
+        let now = Utc::now().timestamp();
+
+        let entries = self.shared.entries.lock().unwrap();
+        entries
+            .iter()
+            .filter(|(key, entry)| key.starts_with(prefix) && !entry.is_expired(now))
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    // drops expired entries along with any the caller no longer wants, returning how many went
+    pub fn compact(&self, retain: impl Fn(&str) -> bool) -> Result<usize, anyhow::Error> {
+        let now = Utc::now().timestamp();
+
+        let mut entries = self.shared.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|key, entry| !entry.is_expired(now) && retain(key));
+        let removed = before - entries.len();
+        self.shared.mark_dirty();
+
+        Ok(removed)
+    }
+
+    pub fn backup(&self, path: &Path) -> Result<(), anyhow::Error> {
+        let snapshot = self.shared.snapshot()?;
+        std::fs::write(path, snapshot)?;
+
+        Ok(())
+    }
+
+    // writes out anything the flusher hasn't got to yet, for shutdown
+    pub fn sync(&self) -> Result<(), anyhow::Error> {
+        match &self.path {
+            Some(path) => self.shared.flush(path),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Shared {
+    fn mark_dirty(&self) {
+        *self.dirty.lock().unwrap() = true;
+        self.changed.notify_one();
+    }
+
+    fn snapshot(&self) -> Result<Vec<u8>, anyhow::Error> {
+        let entries = self.entries.lock().unwrap();
+        Ok(serde_json::to_vec(&*entries)?)
+    }
+
+    // the disk write happens outside the entries lock, so handlers never wait on it
+    fn flush(&self, path: &Path) -> Result<(), anyhow::Error> {
+        let _writing = self.writing.lock().unwrap();
+        if !std::mem::take(&mut *self.dirty.lock().unwrap()) {
+            return Ok(());
+        }
+
+        let written = self.snapshot().and_then(|snapshot| {
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, snapshot)?;
+            Ok(std::fs::rename(tmp, path)?)
+        });
+        if written.is_err() {
+            self.mark_dirty();
+        }
+        written
+    }
+}
+
+// writes happen on their own thread, coalescing whatever changed while the last one ran; it
+// goes away with its store
+fn spawn_flusher(path: PathBuf, shared: Weak<Shared>) {
+    std::thread::spawn(move || {
+        while let Some(shared) = shared.upgrade() {
+            let dirty = shared.dirty.lock().unwrap();
+            let (dirty, _) = shared
+                .changed
+                .wait_timeout_while(dirty, FLUSH_POLL, |dirty| !*dirty)
+                .unwrap();
+            if !*dirty {
+                continue;
+            }
+            drop(dirty);
+
+            if let Err(err) = shared.flush(&path) {
+                tracing::error!("Failed to write {}: {err}", path.display());
+                std::thread::sleep(FLUSH_RETRY);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::Storage;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_flush() {
+        let dir = std::env::temp_dir().join(format!("genedle-storage-{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("storage.json");
+
+        let storage = Storage::open(&path);
+        storage.insert("a", &1).unwrap();
+        storage.update("b", |count: &mut u32| *count += 2).unwrap();
+        storage.sync().unwrap();
+        assert_eq!(Storage::open(&path).get::<u32>("b"), Some(2));
+
+        // the flusher gets there on its own too
+        storage.insert("c", &3).unwrap();
+        let started = Instant::now();
+        while Storage::open(&path).get::<u32>("c").is_none() {
+            assert!(started.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::storage::storage;
+pub use genedle_core::survey::{Rating, SurveyTally};
+use std::collections::BTreeSet;
+
+fn survey_key(game: &str, puzzle: u64) -> String {
+    format!("survey.{game}.{puzzle}")
+}
+
+fn voters_key(game: &str, puzzle: u64) -> String {
+    format!("survey-voters.{game}.{puzzle}")
+}
+
+// one rating per player and puzzle; None when the player had already rated it
+pub fn record(
+    game: &str,
+    puzzle: u64,
+    player: u64,
+    rating: Rating,
+) -> Result<Option<SurveyTally>, anyhow::Error> {
+    let mut first = false;
+    storage().update(&voters_key(game, puzzle), |voters: &mut BTreeSet<u64>| {
+        first = voters.insert(player)
+    })?;
+    if !first {
+        return Ok(None);
+    }
+
+    storage()
+        .update(
+            &survey_key(game, puzzle),
+            |tally: &mut SurveyTally| match rating {
+                Rating::TooEasy => tally.too_easy += 1,
+                Rating::JustRight => tally.just_right += 1,
+                Rating::TooHard => tally.too_hard += 1,
+            },
+        )
+        .map(Some)
+}
+
+pub fn tally(game: &str, puzzle: u64) -> SurveyTally {
+    storage().get(&survey_key(game, puzzle)).unwrap_or_default()
+}
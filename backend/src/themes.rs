@@ -0,0 +1,179 @@
+use crate::games::genedle;
+use crate::games::player_id;
+use crate::stats::{self, ThemeCollection};
+use axum::Json;
+use axum::extract::Query;
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::LazyLock;
+use tower_sessions::Session;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeKind {
+    Disease,
+    Pathway,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, PartialOrd, Ord)]
+pub struct Theme {
+    pub kind: ThemeKind,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct CollectedTheme {
+    #[serde(flatten)]
+    pub theme: Theme,
+    pub answers: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct ThemeReveal {
+    pub themes: Vec<Theme>,
+    // distinct themed answers solved, however many themes each carries
+    pub collected: usize,
+    pub collection: Vec<CollectedTheme>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Themes {
+    by_symbol: BTreeMap<String, Vec<Theme>>,
+}
+
+impl FromStr for ThemeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disease" => Ok(ThemeKind::Disease),
+            "pathway" => Ok(ThemeKind::Pathway),
+            _ => Err(format!("Unknown theme kind {s}")),
+        }
+    }
+}
+
+impl Themes {
+    // curated by hand, so a bad row is reported and skipped rather than losing every theme
+    pub fn parse(tsv: &str) -> Themes {
+        let mut themes = Themes::default();
+        for (number, line) in tsv.lines().enumerate().skip(1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+            let (symbol, kind, name) = match fields.as_slice() {
+                [symbol, kind, name] if !symbol.is_empty() && !name.is_empty() => {
+                    (*symbol, kind, *name)
+                }
+                _ => {
+                    tracing::warn!("Skipping malformed theme on line {}", number + 1);
+                    continue;
+                }
+            };
+            let kind = match kind.parse() {
+                Ok(kind) => kind,
+                Err(err) => {
+                    tracing::warn!("Skipping theme on line {}: {err}", number + 1);
+                    continue;
+                }
+            };
+
+            let tags = themes.by_symbol.entry(symbol.to_string()).or_default();
+            let theme = Theme {
+                kind,
+                name: name.to_string(),
+            };
+            if !tags.contains(&theme) {
+                tags.push(theme);
+            }
+        }
+
+        themes
+    }
+
+    pub fn of(&self, symbol: &str) -> &[Theme] {
+        self.by_symbol.get(symbol).map_or(&[], Vec::as_slice)
+    }
+}
+
+static THEMES: LazyLock<Themes> = LazyLock::new(|| {
+    let path =
+        std::env::var("GENEDLE_THEMES_PATH").unwrap_or_else(|_| "genedle-themes.tsv".to_string());
+
+    // themes are optional; without the file every answer is simply untagged
+    std::fs::read_to_string(path)
+        .map(|tsv| Themes::parse(&tsv))
+        .unwrap_or_default()
+});
+
+pub fn themes() -> &'static Themes {
+    &THEMES
+}
+
+fn collection_of(collection: &ThemeCollection) -> Vec<CollectedTheme> {
+    let mut counts: BTreeMap<&Theme, usize> = BTreeMap::new();
+    for theme in collection.answers.values().flatten() {
+        *counts.entry(theme).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .map(|(theme, answers)| CollectedTheme {
+            theme: theme.clone(),
+            answers,
+        })
+        .collect()
+}
+
+// tags give the answer away, so they're only shown once it has been solved
+pub async fn genedle_themes(
+    session: Session,
+    Query(query): Query<genedle::StateQuery>,
+) -> Result<Json<ThemeReveal>, StatusCode> {
+    let answer = genedle::solved_answer(&session, query.puzzle)
+        .await
+        .ok_or(StatusCode::FORBIDDEN)?;
+    let player = player_id(&session)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let collection = stats::theme_collection(player);
+    Ok(Json(ThemeReveal {
+        themes: themes().of(&answer).to_vec(),
+        collected: collection.answers.len(),
+        collection: collection_of(&collection),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::themes::{Theme, ThemeKind, Themes};
+
+    const SAMPLE: &str = include_str!("../fixtures/themes/sample.tsv");
+
+    #[test]
+    fn test_themes() {
+        let themes = Themes::parse(SAMPLE);
+        assert_eq!(
+            themes.of("BRCA1"),
+            [
+                Theme {
+                    kind: ThemeKind::Disease,
+                    name: "Breast cancer".to_string()
+                },
+                Theme {
+                    kind: ThemeKind::Pathway,
+                    name: "Homologous recombination".to_string()
+                },
+            ]
+        );
+        assert!(themes.of("A1BG").is_empty());
+
+        let themes = Themes::parse("symbol\tkind\tname\nTP53\tsyndrome\tLFS\nTP53\tdisease\n");
+        assert!(themes.of("TP53").is_empty());
+    }
+}
@@ -0,0 +1,321 @@
+use crate::logging::{REQUEST_ID, request_id};
+use crate::ratelimit::upstream_permit;
+use crate::redact::scrub;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const ATTEMPTS: u32 = 3;
+const BASE_DELAY_MS: u64 = 250;
+// consecutive failed attempts before requests stop going out at all
+const FAILURE_THRESHOLD: u32 = 5;
+const OPEN_FOR: Duration = Duration::from_secs(30);
+
+// worth another try: HGNC being briefly overloaded or restarting, not a request it rejected
+fn is_transient_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+fn is_transient(result: &reqwest::Result<Response>) -> bool {
+    match result {
+        Ok(response) => is_transient_status(response.status()),
+        Err(err) => err.is_connect() || err.is_timeout() || err.is_request(),
+    }
+}
+
+// full jitter, so clients that failed together don't all come back together
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_millis(rand::random_range(0..=BASE_DELAY_MS << attempt))
+}
+
+// a timeout is kept apart from other failures, so callers can report a slow upstream as one
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum UpstreamError {
+    Timeout,
+    // HGNC has been failing, so the request wasn't sent
+    CircuitOpen,
+    Failed(String),
+}
+
+impl Display for UpstreamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpstreamError::Timeout => write!(f, "Upstream request timed out"),
+            UpstreamError::CircuitOpen => write!(f, "Upstream is unavailable"),
+            UpstreamError::Failed(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for UpstreamError {}
+
+impl From<reqwest::Error> for UpstreamError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            UpstreamError::Timeout
+        } else {
+            UpstreamError::Failed(scrub(&err.to_string()))
+        }
+    }
+}
+
+// reading a body can time out too, after it's been wrapped up with the parse errors
+impl From<anyhow::Error> for UpstreamError {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast::<reqwest::Error>() {
+            Ok(err) => err.into(),
+            Err(err) => UpstreamError::Failed(err.to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum Circuit {
+    Closed,
+    Open,
+    // the cooldown's over, and the next request is let through to see whether HGNC is back
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    failures: u32,
+    open_until: Option<Instant>,
+    probing: bool,
+}
+
+impl Breaker {
+    fn circuit(&self, now: Instant) -> Circuit {
+        match self.open_until {
+            None => Circuit::Closed,
+            Some(until) if now < until => Circuit::Open,
+            Some(_) => Circuit::HalfOpen,
+        }
+    }
+
+    // half open lets one probe out at a time; everything else fails fast until it's answered
+    fn allow(&mut self, now: Instant) -> bool {
+        match self.circuit(now) {
+            Circuit::Closed => true,
+            Circuit::Open => false,
+            Circuit::HalfOpen => !std::mem::replace(&mut self.probing, true),
+        }
+    }
+
+    // a failed probe opens the circuit again straight away; the probe's slot is only given back by
+    // its guard, since a request let out before the circuit opened can finish while it's out
+    fn record(&mut self, ok: bool, now: Instant) {
+        if ok {
+            self.failures = 0;
+            self.open_until = None;
+            return;
+        }
+
+        self.failures += 1;
+        if self.open_until.is_some() || self.failures >= FAILURE_THRESHOLD {
+            self.open_until = Some(now + OPEN_FOR);
+        }
+    }
+}
+
+static BREAKER: Mutex<Breaker> = Mutex::new(Breaker {
+    failures: 0,
+    open_until: None,
+    probing: false,
+});
+
+pub fn circuit() -> Circuit {
+    BREAKER.lock().unwrap().circuit(Instant::now())
+}
+
+// a half-open probe gives its slot back however it ends, so one dropped by a disconnected client
+// doesn't keep the circuit from ever closing
+struct Probe<'a>(&'a Mutex<Breaker>);
+
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().probing = false;
+    }
+}
+
+fn admit(breaker: &Mutex<Breaker>) -> Result<Option<Probe<'_>>, UpstreamError> {
+    let now = Instant::now();
+    let mut state = breaker.lock().unwrap();
+    let probe = state.circuit(now) == Circuit::HalfOpen;
+    if !state.allow(now) {
+        return Err(UpstreamError::CircuitOpen);
+    }
+    // the guard takes the lock when it's dropped, so only one that's needed is ever made
+    drop(state);
+
+    Ok(probe.then(|| Probe(breaker)))
+}
+
+async fn through<T>(
+    breaker: &Mutex<Breaker>,
+    attempt: impl Future<Output = T>,
+    healthy: impl Fn(&T) -> bool,
+) -> Result<T, UpstreamError> {
+    let _probe = admit(breaker)?;
+    let result = attempt.await;
+    breaker
+        .lock()
+        .unwrap()
+        .record(healthy(&result), Instant::now());
+
+    Ok(result)
+}
+
+// the breaker only counts what says something about HGNC's health; a 404 is a healthy answer
+async fn send(mut request: RequestBuilder) -> Result<reqwest::Result<Response>, UpstreamError> {
+    // so a slow or failed call can be matched up with the player request that made it
+    if let Some(id) = request_id() {
+        request = request.header(REQUEST_ID, id);
+    }
+    // waiting for a permit isn't part of the probe, so a queued request can't hold one up
+    let _permit = upstream_permit().await;
+    through(&BREAKER, request.send(), |result| !is_transient(result)).await
+}
+
+pub(crate) trait RetryExt {
+    async fn send_with_retry(self) -> Result<Response, UpstreamError>;
+}
+
+impl RetryExt for RequestBuilder {
+    async fn send_with_retry(self) -> Result<Response, UpstreamError> {
+        let mut attempt = 1;
+        loop {
+            // a streamed body can't be replayed, so it only gets the one try
+            let Some(request) = self.try_clone() else {
+                return Ok(send(self).await??);
+            };
+
+            let result = send(request).await?;
+            if attempt >= ATTEMPTS || !is_transient(&result) {
+                return Ok(result?);
+            }
+
+            let reason = match &result {
+                Ok(response) => response.status().to_string(),
+                Err(err) => scrub(&err.to_string()),
+            };
+            tracing::warn!("Retrying upstream request after attempt {attempt} failed: {reason}");
+            tokio::time::sleep(backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::upstream::{
+        BASE_DELAY_MS, Breaker, Circuit, FAILURE_THRESHOLD, OPEN_FOR, UpstreamError, backoff,
+        is_transient_status, through,
+    };
+    use reqwest::StatusCode;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_retry_policy() {
+        assert!(is_transient_status(StatusCode::BAD_GATEWAY));
+        assert!(is_transient_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_transient_status(StatusCode::NOT_FOUND));
+        assert!(!is_transient_status(StatusCode::OK));
+
+        for attempt in 1..3 {
+            assert!(backoff(attempt) <= Duration::from_millis(BASE_DELAY_MS << attempt));
+        }
+    }
+
+    #[test]
+    fn test_breaker() {
+        let now = Instant::now();
+        let mut breaker = Breaker {
+            failures: 0,
+            open_until: None,
+            probing: false,
+        };
+        for _ in 1..FAILURE_THRESHOLD {
+            breaker.record(false, now);
+        }
+        assert_eq!(breaker.circuit(now), Circuit::Closed);
+        breaker.record(false, now);
+        assert_eq!(breaker.circuit(now), Circuit::Open);
+        assert!(!breaker.allow(now));
+
+        // one probe once the cooldown's over, and a failed one opens it again
+        let later = now + OPEN_FOR;
+        assert_eq!(breaker.circuit(later), Circuit::HalfOpen);
+        assert!(breaker.allow(later));
+        assert!(!breaker.allow(later));
+        breaker.record(false, later);
+        assert_eq!(breaker.circuit(later), Circuit::Open);
+        // as its guard does once it's answered
+        breaker.probing = false;
+
+        let later = later + OPEN_FOR;
+        assert!(breaker.allow(later));
+        breaker.record(true, later);
+        assert_eq!(breaker.circuit(later), Circuit::Closed);
+        assert!(breaker.allow(later));
+        breaker.probing = false;
+
+        // a straggler from before the circuit opened finishing doesn't let a second probe out
+        breaker.open_until = Some(later);
+        assert!(breaker.allow(later));
+        breaker.record(false, later);
+        let later = later + OPEN_FOR;
+        assert_eq!(breaker.circuit(later), Circuit::HalfOpen);
+        assert!(!breaker.allow(later));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_probe() {
+        let breaker = Mutex::new(Breaker {
+            failures: FAILURE_THRESHOLD,
+            open_until: Some(Instant::now()),
+            probing: false,
+        });
+
+        // the probe goes out and never comes back, like a player closing the tab mid-lookup
+        let mut probe = Box::pin(through(&breaker, std::future::pending::<()>(), |_| true));
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), &mut probe)
+                .await
+                .is_err()
+        );
+        let blocked = through(&breaker, async {}, |_| true).await;
+        assert_eq!(blocked, Err(UpstreamError::CircuitOpen));
+
+        drop(probe);
+        assert_eq!(through(&breaker, async {}, |_| true).await, Ok(()));
+        // with the circuit closed there's no probe, and nothing to give back
+        assert_eq!(through(&breaker, async {}, |_| true).await, Ok(()));
+        assert_eq!(
+            breaker.lock().unwrap().circuit(Instant::now()),
+            Circuit::Closed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        // the connection is accepted but never answered, like a hung genenames.org
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let client = reqwest::Client::builder()
+            .read_timeout(Duration::from_millis(50))
+            .build()
+            .unwrap();
+        let err = client.get(url).send().await.unwrap_err();
+        assert_eq!(UpstreamError::from(err), UpstreamError::Timeout);
+
+        assert_eq!(
+            UpstreamError::from(anyhow::anyhow!("schema drift")),
+            UpstreamError::Failed("schema drift".to_string())
+        );
+    }
+}
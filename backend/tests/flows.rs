@@ -0,0 +1,1245 @@
+use chrono::{Datelike, NaiveDate};
+use genedle::puzzle_ids::PuzzleId;
+use genedle::sessions::{SessionBackend, TrackedStore};
+use genedle::state::AppState;
+use genedle::{cache, notifications, router, schedule, storage};
+use reqwest::header::{COOKIE, SET_COOKIE};
+use serde_json::{Value, json};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tokio::net::TcpListener;
+use tokio::sync::OnceCell;
+use tower_sessions::SessionStore;
+use tower_sessions::session::Id;
+
+const ANSWER: &str = "BRCA1";
+const ADMIN_TOKEN: &str = "flows-admin";
+const UNIX_EPOCH_DAYS_FROM_CE: u64 = 719_163;
+
+static SETUP: OnceCell<()> = OnceCell::const_new();
+
+// config, storage and the corpus are process-wide, so every flow shares one scratch directory and
+// today's puzzles are pinned through the cache snapshot instead of being generated
+async fn setup() {
+    SETUP
+        .get_or_init(|| async {
+            let dir = scratch_dir();
+            std::fs::create_dir_all(&dir).unwrap();
+            let cache_path = dir.join("cache.json");
+            std::fs::write(dir.join("genedle.conf"), "").unwrap();
+
+            // SAFETY: runs once, before anything in the crate has read its configuration
+            unsafe {
+                std::env::set_var("GENEDLE_CORPUS_PATH", "fixtures/hgnc/sample.tsv");
+                std::env::set_var("GENEDLE_THEMES_PATH", "fixtures/themes/sample.tsv");
+                std::env::set_var("GENEDLE_STORAGE_PATH", dir.join("storage.json"));
+                std::env::set_var("GENEDLE_CACHE_PATH", &cache_path);
+                std::env::set_var("GENEDLE_SESSION_STORE", "memory");
+                std::env::set_var("GENEDLE_ADMIN_TOKEN", ADMIN_TOKEN);
+                std::env::set_var("GENEDLE_GENECTIONS_COOLDOWN_MS", "0");
+                std::env::set_var("GENEDLE_CONFIG_PATH", dir.join("genedle.conf"));
+            }
+
+            write_snapshot(&cache_path);
+            cache::restore().await;
+            storage::storage()
+                .insert(&format!("genedle.daily.{}", schedule::today()), &ANSWER)
+                .unwrap();
+        })
+        .await;
+}
+
+fn scratch_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("genedle-flows-{}", std::process::id()))
+}
+
+fn write_snapshot(path: &PathBuf) {
+    let board = json!({
+        "categories": (0..4).map(|group| json!({
+            "name": format!("Family {group}"),
+            "symbols": (0..4).map(|member| symbol(group, member)).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+        "layout": (0..16).map(|index| symbol(index % 4, index / 4)).collect::<Vec<_>>(),
+    });
+    let spelling_seed = schedule::today() - UNIX_EPOCH_DAYS_FROM_CE;
+    let spelling_game = json!({
+        "outer_letters": ["A", "B", "C", "E", "F", "G"],
+        "center_letter": "R",
+        "valid_symbols": ["BRCA1", "BRCA2", "EGFR"],
+    });
+
+    let snapshot = json!({
+        "genections.board": [[schedule::today(), board]],
+        "spelling_gene.game": [[[4, 10, 7, spelling_seed, false], spelling_game]],
+    });
+    std::fs::write(path, serde_json::to_vec(&snapshot).unwrap()).unwrap();
+}
+
+fn symbol(group: u64, member: u64) -> String {
+    format!("FAM{group}M{member}")
+}
+
+// one player talking to a freshly served router, carrying the session cookie by hand
+struct Player {
+    client: reqwest::Client,
+    base: String,
+    cookie: Option<String>,
+}
+
+impl Player {
+    async fn join(sessions: &TrackedStore) -> Player {
+        setup().await;
+
+        let app = router(AppState::new(sessions.clone()).unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .unwrap();
+        });
+
+        Player {
+            client: reqwest::Client::new(),
+            base: format!("http://{addr}"),
+            cookie: None,
+        }
+    }
+
+    async fn send(&mut self, request: reqwest::RequestBuilder) -> Value {
+        let request = match &self.cookie {
+            Some(cookie) => request.header(COOKIE, cookie),
+            None => request,
+        };
+        let response = request.send().await.unwrap();
+        assert!(response.status().is_success(), "{}", response.status());
+
+        if let Some(cookie) = response.headers().get(SET_COOKIE) {
+            let cookie = cookie.to_str().unwrap();
+            self.cookie = Some(cookie.split(';').next().unwrap().to_string());
+        }
+
+        response.json().await.unwrap()
+    }
+
+    async fn status(&self, path: &str) -> reqwest::StatusCode {
+        let request = self.client.get(format!("{}{path}", self.base));
+        let request = match &self.cookie {
+            Some(cookie) => request.header(COOKIE, cookie),
+            None => request,
+        };
+        request.send().await.unwrap().status()
+    }
+
+    async fn get(&mut self, path: &str) -> Value {
+        let request = self.client.get(format!("{}{path}", self.base));
+        self.send(request).await
+    }
+
+    async fn post(&mut self, path: &str, body: Value) -> Value {
+        let request = self.client.post(format!("{}{path}", self.base)).json(&body);
+        self.send(request).await
+    }
+
+    async fn put(&mut self, path: &str, body: Value) -> Value {
+        let request = self.client.put(format!("{}{path}", self.base)).json(&body);
+        self.send(request).await
+    }
+}
+
+async fn in_progress(sessions: &TrackedStore, game: &str) -> usize {
+    sessions.metrics().await.in_progress[game]
+}
+
+#[tokio::test]
+async fn test_genedle_game() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let mut player = Player::join(&sessions).await;
+
+    // the puzzle comes back as an opaque token rather than the seed it was drawn from
+    let puzzle = player.get("/games/genedle").await;
+    let day = schedule::today();
+    assert_eq!(puzzle.as_str().unwrap().len(), 48);
+    assert!(puzzle.as_str().unwrap().parse::<u64>().is_err());
+    let progress = player.get("/api/v1/daily-progress").await;
+    assert_eq!(
+        progress["genedle"],
+        json!({"started": false, "finished": false})
+    );
+
+    for gated in ["/api/v1/genedle-answer", "/api/v1/genedle-themes"] {
+        assert_eq!(player.status(gated).await, reqwest::StatusCode::FORBIDDEN);
+    }
+
+    let hint = player.get("/api/v1/genedle-hint").await;
+    assert_eq!(
+        hint["data"],
+        json!({
+            "hints": [{"kind": "family", "value": "Ring finger proteins"}],
+            "hints_remaining": 2
+        })
+    );
+
+    // a raw day number is no longer a puzzle anyone can name
+    let forged = player
+        .client
+        .post(format!("{}/api/v1/genedle-guess", player.base))
+        .json(&json!({"word": ["B", "R", "C", "A", "2"], "session": day, "mode": "normal"}))
+        .send()
+        .await
+        .unwrap();
+    assert!(forged.status().is_client_error(), "{}", forged.status());
+
+    // a guess no answer could match is a bad request rather than a move
+    let short = player
+        .client
+        .post(format!("{}/api/v1/genedle-guess", player.base))
+        .header(COOKIE, player.cookie.clone().unwrap())
+        .json(&json!({"word": ["B", "R"], "session": puzzle, "mode": "normal"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(short.status(), reqwest::StatusCode::BAD_REQUEST);
+    assert_eq!(short.json::<Value>().await.unwrap()["error"], "bad_request");
+
+    for (word, correct) in [("BRCA2", false), ("ABCA1", false), (ANSWER, true)] {
+        let result = player
+            .post(
+                "/api/v1/genedle-guess",
+                json!({"word": word.chars().collect::<Vec<_>>(), "session": puzzle, "mode": "normal", "assist": true, "rating": "too_easy"}),
+            )
+            .await;
+        assert_eq!(result["type"], "valid");
+        assert_eq!(result["data"]["is_correct"], correct);
+        // the sample corpus leaves only a handful of candidates, and none once it's over
+        assert_eq!(
+            result.get("temperature"),
+            (!correct).then(|| json!("hot")).as_ref()
+        );
+
+        // only the guess that ends the game describes the answer
+        assert_eq!(result.get("summary").is_some(), correct);
+        if correct {
+            assert_eq!(result["summary"]["symbol"], ANSWER);
+            assert_eq!(result["summary"]["hgnc_id"], "HGNC:1100");
+            assert_eq!(
+                result["summary"]["genecards_url"],
+                "https://www.genecards.org/cgi-bin/carddisp.pl?gene=BRCA1"
+            );
+        }
+    }
+
+    let replay = player
+        .post(
+            "/api/v1/genedle-guess",
+            json!({"word": ANSWER.chars().collect::<Vec<_>>(), "session": puzzle, "mode": "normal", "rating": "too_hard"}),
+        )
+        .await;
+    assert_eq!(
+        replay,
+        json!({"type": "invalid", "data": "game_over", "canonical": ANSWER})
+    );
+
+    let state = player.get("/api/v1/genedle-state").await;
+    assert_eq!(state["puzzle"], puzzle);
+    assert_eq!(state["solved"], true);
+    assert_eq!(state["guesses"].as_array().unwrap().len(), 3);
+    assert_eq!(state["guesses_remaining"], 0);
+    assert_eq!(state["hints"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        player.get("/api/v1/genedle-hint").await,
+        json!({"type": "invalid", "data": "game_over"})
+    );
+
+    // only the guess that ended the game was rated, and the replay after it wasn't counted
+    let report: Value = player
+        .client
+        .get(format!("{}/api/admin/genedle-report/{day}", player.base))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        report["survey"],
+        json!({"too_easy": 1, "just_right": 0, "too_hard": 0})
+    );
+
+    assert_eq!(player.get("/api/v1/genedle-answer").await, json!(ANSWER));
+    let themes = player.get("/api/v1/genedle-themes").await;
+    assert_eq!(
+        themes["themes"][0],
+        json!({"kind": "disease", "name": "Breast cancer"})
+    );
+    assert_eq!(themes["collected"], 1);
+    assert_eq!(
+        themes["collection"][1],
+        json!({"kind": "pathway", "name": "Homologous recombination", "answers": 1})
+    );
+    // numbered from the default epoch, the same way the public endpoint counts
+    let epoch = NaiveDate::from_ymd_opt(2025, 1, 1)
+        .unwrap()
+        .num_days_from_ce() as u64;
+    let number = day - epoch + 1;
+    let numbering = player.get("/api/v1/puzzle-number/genedle").await;
+    assert_eq!(numbering["number"], number);
+    assert_eq!(numbering["puzzle"], day);
+    assert_eq!(
+        player.get("/api/v1/puzzle-number/not-a-game").await,
+        Value::Null
+    );
+    let share = player.get("/api/v1/genedle-share").await;
+    assert_eq!(
+        share,
+        json!(format!(
+            "Genedle #{number} 3/6 💡1\n\n🟩🟩🟩🟩⬛\n⬛🟨🟩🟩🟩\n🟩🟩🟩🟩🟩"
+        ))
+    );
+
+    let stats = player.get("/games/stats").await;
+    assert_eq!(stats["genedle"]["played"], 1);
+    assert_eq!(stats["genedle"]["won"], 1);
+    assert_eq!(stats["genedle"]["distribution"], json!({"3": 1}));
+    assert_eq!(stats["genedle"]["streak"]["current"], 1);
+
+    // the streak board only knows what the server recorded for this daily
+    let streaks = player.get("/api/v1/streak-leaderboard").await;
+    assert_eq!(streaks["rules"]["timezone"], "UTC");
+    assert!(
+        streaks["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|entry| entry["streak"] == 1 && entry["rank"].as_u64().is_some())
+    );
+
+    let progress = player.get("/api/v1/daily-progress").await;
+    assert_eq!(progress["day"], day);
+    assert_eq!(
+        progress["genedle"],
+        json!({"started": true, "finished": true})
+    );
+    assert_eq!(
+        progress["genections"],
+        json!({"started": false, "finished": false})
+    );
+
+    assert_eq!(sessions.metrics().await.active_sessions, 1);
+    assert_eq!(in_progress(&sessions, "genedle").await, 1);
+}
+
+#[tokio::test]
+async fn test_genedle_rollover() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let mut player = Player::join(&sessions).await;
+    let puzzle = player.get("/games/genedle").await;
+
+    // a session still on yesterday's board, as after midnight before the sweep has run
+    let cookie = player.cookie.clone().unwrap();
+    let id = Id::from_str(cookie.strip_prefix("id=").unwrap()).unwrap();
+    let mut record = sessions.load(&id).await.unwrap().unwrap();
+    record
+        .data
+        .insert("genedle.word".to_string(), json!(schedule::today() - 1));
+    sessions.save(&record).await.unwrap();
+
+    assert_eq!(player.get("/games/genedle").await, puzzle);
+}
+
+#[tokio::test]
+async fn test_genedle_hard_mode_aliases() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let mut player = Player::join(&sessions).await;
+    let puzzle = player.get("/games/genedle").await;
+
+    // ERBB1 is EGFR's alias and its previous symbol, and BRCC1 the answer's own alias; typed
+    // guesses are scored in capitals
+    for (word, expected) in [
+        (
+            "ZZZZZ",
+            json!({"type": "invalid", "data": "not_in_corpus", "canonical": "ZZZZZ"}),
+        ),
+        ("erbb1", json!("EGFR")),
+        ("BRCC1", json!("BRCA1")),
+        (ANSWER, Value::Null),
+    ] {
+        let result = player
+            .post(
+                "/api/v1/genedle-guess",
+                json!({"word": word.chars().collect::<Vec<_>>(), "session": puzzle, "mode": "hard"}),
+            )
+            .await;
+        if result["type"] == "valid" {
+            assert_eq!(result["canonical"], word.to_ascii_uppercase());
+            assert_eq!(result["alias_of"], expected);
+        } else {
+            assert_eq!(result, expected);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_genedle_tutorial() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let mut player = Player::join(&sessions).await;
+
+    let tutorial = player.get("/games/genedle-tutorial").await;
+    assert_eq!(tutorial["length"], 4);
+    assert_eq!(tutorial["step"], "welcome");
+
+    let result = player
+        .post(
+            "/games/genedle-tutorial/guess",
+            json!({"word": ["Z", "Z", "Z", "Z"]}),
+        )
+        .await;
+    assert_eq!(result["data"], "not_in_corpus");
+    assert_eq!(result["tutorial"]["step"], "welcome");
+
+    for (word, step) in [("egfr", "absent"), ("TP53", "solved")] {
+        let result = player
+            .post(
+                "/games/genedle-tutorial/guess",
+                json!({"word": word.chars().collect::<Vec<_>>()}),
+            )
+            .await;
+        assert_eq!(result["type"], "valid");
+        assert_eq!(result["tutorial"]["step"], step);
+        assert!(result["tutorial"]["message"].as_str().is_some());
+    }
+
+    // the tutorial never counts as a game played
+    let stats = player.get("/games/stats").await;
+    assert_eq!(stats["genedle"]["played"], 0);
+    let progress = player.get("/api/v1/daily-progress").await;
+    assert_eq!(
+        progress["genedle"],
+        json!({"started": false, "finished": false})
+    );
+
+    let request = player
+        .client
+        .delete(format!("{}/games/genedle-tutorial", player.base));
+    let restarted = player.send(request).await;
+    assert_eq!(restarted["step"], "welcome");
+    assert_eq!(restarted["guesses"], json!([]));
+}
+
+#[tokio::test]
+async fn test_crossover_bonus() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let mut player = Player::join(&sessions).await;
+
+    let puzzle = player.get("/games/genedle").await;
+    player
+        .post(
+            "/api/v1/genedle-guess",
+            json!({"word": ANSWER.chars().collect::<Vec<_>>(), "session": puzzle, "mode": "normal"}),
+        )
+        .await;
+
+    // one daily down isn't enough
+    let crossover = player.get("/games/crossover").await;
+    assert_eq!(crossover["unlocked"], false);
+    assert!(crossover.get("category").is_none());
+    let request = player
+        .client
+        .post(format!("{}/games/crossover/guess", player.base))
+        .json(&json!({"word": symbol(0, 0).chars().collect::<Vec<_>>()}));
+    let request = request.header(COOKIE, player.cookie.clone().unwrap());
+    assert_eq!(
+        request.send().await.unwrap().status(),
+        reqwest::StatusCode::FORBIDDEN
+    );
+
+    player.get("/games/genections").await;
+    for group in 0..4 {
+        let symbols: Vec<String> = (0..4).map(|member| symbol(group, member)).collect();
+        player
+            .post("/games/genections/guess", json!({"symbols": symbols}))
+            .await;
+    }
+
+    let crossover = player.get("/games/crossover").await;
+    assert_eq!(crossover["unlocked"], true);
+    assert_eq!(crossover["length"], 6);
+    assert_eq!(crossover["guesses_remaining"], 3);
+    let category = crossover["category"].as_str().unwrap();
+    let group: u64 = category.trim_start_matches("Family ").parse().unwrap();
+
+    // the fixture corpus only knows the answer itself, so misses are turned away uncounted
+    let mut result = Value::Null;
+    for member in 0..4 {
+        result = player
+            .post(
+                "/games/crossover/guess",
+                json!({"word": symbol(group, member).chars().collect::<Vec<_>>()}),
+            )
+            .await;
+        if result["type"] == "valid" {
+            break;
+        }
+        assert_eq!(result["data"], "not_in_corpus");
+    }
+    assert_eq!(result["crossover"]["solved"], true);
+    assert_eq!(result["crossover"]["xp"], 30);
+    assert!(result["crossover"]["answer"].as_str().is_some());
+
+    let stats = player.get("/games/stats").await;
+    assert_eq!(
+        stats["crossover"],
+        json!({"played": 1, "solved": 1, "xp": 30, "last_day": schedule::today()})
+    );
+    let progress = player.get("/api/v1/daily-progress").await;
+    assert_eq!(
+        progress["crossover"],
+        json!({"started": true, "finished": true})
+    );
+}
+
+#[tokio::test]
+async fn test_spelling_gene_day() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let mut player = Player::join(&sessions).await;
+
+    let game = player.get("/games/spelling-gene").await;
+    assert_eq!(game["center_letter"], "R");
+    assert_eq!(game["found"], json!([]));
+
+    let rejections = [
+        ("ZZZZ", json!("not_a_gene")),
+        ("XIST", json!("missing_center_letter")),
+        ("KRT10", json!({"disallowed_letter": {"letter": "K"}})),
+    ];
+    for (guess, reason) in rejections {
+        let result = player
+            .post("/games/spelling-gene/guess", json!({"guess": guess}))
+            .await;
+        assert_eq!(result, json!({"type": "invalid", "data": reason}));
+    }
+
+    for guess in ["brca1", " BRCA2 ", "EGFR"] {
+        let result = player
+            .post("/games/spelling-gene/guess", json!({"guess": guess}))
+            .await;
+        assert_eq!(result["type"], "valid", "{guess}: {result}");
+    }
+
+    let progress = player.get("/games/spelling-gene/progress").await;
+    assert_eq!(progress["found"], 3);
+    assert_eq!(progress["total"], 3);
+    assert_eq!(progress["queen_gene"], true);
+    assert_eq!(progress["queen_genes"], 1);
+    let today = schedule::date(schedule::today()).unwrap();
+    let yesterday = today.pred_opt().unwrap();
+    let heatmap = player
+        .get(&format!(
+            "/api/public/spelling-gene/heatmap?from={yesterday}&to={today}"
+        ))
+        .await;
+    assert_eq!(heatmap.as_array().unwrap().len(), 2);
+    assert_eq!(heatmap[1]["date"], today.to_string());
+    assert!(heatmap[1]["attempted"].as_u64().unwrap() >= 1);
+    assert!(heatmap[1]["completed"].as_u64().unwrap() >= 1);
+    assert_eq!(
+        player
+            .status(&format!(
+                "/api/public/spelling-gene/heatmap?from={today}&to={yesterday}"
+            ))
+            .await,
+        reqwest::StatusCode::BAD_REQUEST
+    );
+
+    let hints = player.get("/games/spelling-gene/hints").await;
+    assert_eq!(
+        hints["two_letter_list"],
+        json!([
+            {"prefix": "BR", "found": 2, "total": 2},
+            {"prefix": "EG", "found": 1, "total": 1},
+        ])
+    );
+    let progress = player.get("/api/v1/daily-progress").await;
+    assert_eq!(
+        progress["spelling_gene"],
+        json!({"started": true, "finished": true})
+    );
+
+    let game = player.get("/games/spelling-gene").await;
+    assert_eq!(game["found"], json!(["BRCA1", "BRCA2", "EGFR"]));
+    assert_eq!(in_progress(&sessions, "spelling_gene").await, 1);
+}
+
+#[tokio::test]
+async fn test_genections_loss() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let mut player = Player::join(&sessions).await;
+
+    let board = player.get("/games/genections").await;
+    assert_eq!(board["symbols"].as_array().unwrap().len(), 16);
+    assert_eq!(board["mistakes_remaining"], 4);
+
+    let wrong = [
+        [symbol(0, 0), symbol(0, 1), symbol(0, 2), symbol(1, 0)],
+        [symbol(0, 0), symbol(1, 1), symbol(2, 2), symbol(3, 3)],
+        [symbol(0, 1), symbol(1, 2), symbol(2, 3), symbol(3, 0)],
+        [symbol(0, 2), symbol(1, 3), symbol(2, 0), symbol(3, 1)],
+    ];
+    for (mistakes, symbols) in wrong.iter().enumerate() {
+        let result = player
+            .post("/games/genections/guess", json!({"symbols": symbols}))
+            .await;
+        assert_eq!(result["type"], "valid", "{result}");
+        assert_eq!(result["data"]["category"], Value::Null);
+        assert_eq!(result["data"]["one_away"], mistakes == 0);
+        assert_eq!(result["data"]["mistakes_remaining"], 3 - mistakes);
+    }
+
+    let late = player
+        .post(
+            "/games/genections/guess",
+            json!({"symbols": (0..4).map(|member| symbol(0, member)).collect::<Vec<_>>()}),
+        )
+        .await;
+    assert_eq!(late, json!({"type": "invalid", "data": "game_over"}));
+
+    let board = player.get("/games/genections").await;
+    assert_eq!(board["finished"], true);
+    assert_eq!(board["solved"], json!([]));
+
+    let share = player.get("/games/genections/share").await;
+    let share = share.as_str().unwrap();
+    assert!(share.contains("🟨🟨🟨🟩"));
+    let link = share.lines().last().unwrap();
+    let path = &link[link.find("/games/genections/snapshot/").unwrap()..];
+    assert_eq!(player.get("/games/genections/share").await, json!(share));
+
+    let image = reqwest::get(format!("{}{path}", player.base))
+        .await
+        .unwrap();
+    assert_eq!(image.headers()["content-type"], "image/png");
+    assert!(image.bytes().await.unwrap().starts_with(b"\x89PNG"));
+
+    let stats = player.get("/games/stats").await;
+    assert_eq!(stats["genections"]["played"], 1);
+    assert_eq!(stats["genections"]["won"], 0);
+    assert_eq!(stats["genections"]["streak"]["current"], 0);
+    assert_eq!(in_progress(&sessions, "genections").await, 1);
+
+    // support can read the finished game back without touching the player's session
+    let id = player.cookie.as_deref().unwrap().trim_start_matches("id=");
+    let admin = reqwest::Client::new();
+    let inspection: Value = admin
+        .get(format!("{}/api/admin/sessions/{id}", player.base))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(inspection["data"]["genections.game"]["mistakes"], 4);
+    assert_eq!(inspection["stats"]["genections"]["played"], 1);
+
+    let missing = admin
+        .get(format!("{}/api/admin/sessions/not-a-session", player.base))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_config_reload() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let player = Player::join(&sessions).await;
+    let admin = reqwest::Client::new();
+    let config_path = scratch_dir().join("genedle.conf");
+
+    let reload = |settings: &str| {
+        std::fs::write(&config_path, settings).unwrap();
+        admin
+            .post(format!("{}/api/admin/config/reload", player.base))
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+    };
+    let allowed = |origin: &'static str| {
+        let request = player
+            .client
+            .get(format!("{}/api/v1/daily-progress", player.base))
+            .header(reqwest::header::ORIGIN, origin)
+            .send();
+        async move {
+            request
+                .await
+                .unwrap()
+                .headers()
+                .contains_key(reqwest::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        }
+    };
+
+    assert!(allowed("https://elsewhere.example.org").await);
+
+    let response = reload(
+        "GENEDLE_CORS_ORIGINS=https://wiki.example.org\nGENEDLE_BASE_PATH=/moved\n\
+         GENEDLE_TIMEZONE=Asia/Tokyo\nGENEDLE_SPELLING_GENE_ALIASES=true\n\
+         GENEDLE_GENECTIONS_RED_HERRINGS=2\n",
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    // moving midnight or changing how boards are drawn would change the puzzles players are
+    // already on
+    assert_eq!(
+        response.json::<Value>().await.unwrap(),
+        json!({"restart_required": [
+            "GENEDLE_BASE_PATH",
+            "GENEDLE_TIMEZONE",
+            "GENEDLE_SPELLING_GENE_ALIASES",
+            "GENEDLE_GENECTIONS_RED_HERRINGS"
+        ]})
+    );
+    assert_eq!(genedle::config::config().timezone, chrono_tz::Tz::UTC);
+    assert!(!genedle::config::config().spelling_gene_aliases);
+    assert_eq!(genedle::config::config().genections_red_herrings, 1);
+    assert!(allowed("https://wiki.example.org").await);
+    assert!(!allowed("https://elsewhere.example.org").await);
+
+    // one bad setting keeps the whole file from applying
+    let response = reload("GENEDLE_CORS_ORIGINS=\nGENEDLE_MAX_GUESSES=0\n")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        response.json::<Value>().await.unwrap(),
+        json!(["GENEDLE_MAX_GUESSES: must be at least 1"])
+    );
+    assert!(!allowed("https://elsewhere.example.org").await);
+
+    let response = reload("").await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert!(allowed("https://elsewhere.example.org").await);
+}
+
+#[tokio::test]
+async fn test_curated_board_guards() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let player = Player::join(&sessions).await;
+    let admin = reqwest::Client::new();
+    let board = |day: u64| format!("{}/api/admin/genections-board/{day}", player.base);
+    let today = schedule::today();
+
+    let categories: Vec<Value> = (0..4)
+        .map(|group| {
+            json!({
+                "name": format!("Family {group}"),
+                "symbols": (0..4).map(|member| symbol(group, member)).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    // today's board is already out, curated or not
+    let response = admin
+        .put(board(today))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"author": "flows", "categories": categories}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::CONFLICT);
+
+    let response = admin
+        .put(board(today + 1))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"author": "flows", "categories": categories[..3]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+
+    // the fixture corpus doesn't know the placeholder symbols
+    let response = admin
+        .put(board(today + 1))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"author": "flows", "categories": categories}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = admin
+        .post(format!("{}/rollback/1", board(today + 1)))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&json!({"author": "flows"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+    let history: Value = admin
+        .get(board(today + 1))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(history, json!({"versions": []}));
+}
+
+#[tokio::test]
+async fn test_special_puzzle_guards() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let player = Player::join(&sessions).await;
+    let admin = reqwest::Client::new();
+    let special = |day: u64| format!("{}/api/admin/spelling-gene-specials/{day}", player.base);
+    let today = schedule::today();
+    let schedule = |day: u64, outer_letters: [&str; 6]| {
+        admin
+            .put(special(day))
+            .bearer_auth(ADMIN_TOKEN)
+            .json(&json!({
+                "title": "DNA Day",
+                "center_letter": "R",
+                "outer_letters": outer_letters,
+                "multiplier": 2,
+            }))
+            .send()
+    };
+
+    let response = schedule(today, ["A", "B", "C", "E", "F", "G"])
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::CONFLICT);
+
+    let response = schedule(today + 1, ["A", "B", "C", "E", "F", "R"])
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.text().await.unwrap(), "R appears more than once");
+
+    // only a handful of the fixture's symbols can be spelled, short of a full puzzle
+    let response = schedule(today + 1, ["A", "B", "C", "E", "F", "G"])
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(response.text().await.unwrap().starts_with("Only "));
+
+    let specials: Value = admin
+        .get(format!("{}/api/admin/spelling-gene-specials", player.base))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(specials, json!({}));
+}
+
+#[tokio::test]
+async fn test_answer_pool_export() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let player = Player::join(&sessions).await;
+    let admin = reqwest::Client::new();
+    let export = |format: &str| {
+        admin
+            .get(format!(
+                "{}/api/admin/genedle-answer-pool?format={format}",
+                player.base
+            ))
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+    };
+
+    let pool: Value = export("json").await.unwrap().json().await.unwrap();
+    let symbols: Vec<&str> = pool
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["symbol"].as_str().unwrap())
+        .collect();
+    // MYC is under the default minimum length
+    assert!(!symbols.contains(&"MYC"));
+    let answer = pool
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["symbol"] == ANSWER)
+        .unwrap();
+    assert_eq!(
+        answer["last_used"],
+        schedule::date(schedule::today()).unwrap().to_string()
+    );
+    assert_eq!(answer["recent"], true);
+
+    let csv = export("csv").await.unwrap().text().await.unwrap();
+    assert!(csv.starts_with("symbol,length,"));
+    assert_eq!(csv.lines().count(), symbols.len() + 1);
+
+    // previewing a coming day shows its answer without drawing it for good
+    let report = |day: u64| {
+        admin
+            .get(format!("{}/api/admin/genedle-report/{day}", player.base))
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+    };
+    let ahead = schedule::today() + 3;
+    let preview: Value = report(ahead).await.unwrap().json().await.unwrap();
+    assert!(preview["answer"].is_string());
+    let pool: Value = export("json").await.unwrap().json().await.unwrap();
+    let ahead_date = schedule::date(ahead).unwrap().to_string();
+    assert!(
+        pool.as_array()
+            .unwrap()
+            .iter()
+            .all(|entry| entry["last_used"] != ahead_date.as_str())
+    );
+    let far: Value = report(schedule::today() + 1000)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(far, Value::Null);
+
+    // a hand-picked answer has to be one the draw could have made
+    let tomorrow = schedule::today() + 1;
+    let override_answer = |symbol: &str, token: &str| {
+        admin
+            .put(format!(
+                "{}/api/admin/genedle-answer/{tomorrow}",
+                player.base
+            ))
+            .bearer_auth(token)
+            .json(&json!({"symbol": symbol}))
+            .send()
+    };
+    for symbol in ["MYC", "BRCA9"] {
+        let response = override_answer(symbol, ADMIN_TOKEN).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+    let response = override_answer(ANSWER, "flows-admi").await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_notification_retries() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let mut player = Player::join(&sessions).await;
+    let admin = reqwest::Client::new();
+
+    let preferences = json!({
+        "channels": {"email": "ada@example.org"},
+        "quiet_hours": null,
+        "muted": ["genedle.daily"],
+    });
+    assert_eq!(
+        player
+            .put("/games/notifications", preferences.clone())
+            .await,
+        preferences
+    );
+    assert_eq!(player.get("/games/notifications").await, preferences);
+    let code = player.get("/games/preferences").await["player"].clone();
+
+    let notify = |kind: &str| {
+        admin
+            .post(format!("{}/api/admin/notifications", player.base))
+            .bearer_auth(ADMIN_TOKEN)
+            .json(&json!({"players": [code], "kind": kind, "title": "Hi", "body": "Hello"}))
+            .send()
+    };
+    let muted: Value = notify("genedle.daily").await.unwrap().json().await.unwrap();
+    assert_eq!(muted, json!([]));
+    let queued: Value = notify("announcement").await.unwrap().json().await.unwrap();
+    assert_eq!(queued[0]["channel"], "email");
+    assert_eq!(queued[0]["status"], "pending");
+
+    // no email relay is configured, so the attempt fails and is retried later
+    let now = chrono::Utc::now();
+    notifications::deliver_due(&reqwest::Client::new(), now).await;
+    let deliveries: Value = admin
+        .get(format!(
+            "{}/api/admin/notifications/{}",
+            player.base,
+            code.as_str().unwrap()
+        ))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(deliveries[0]["id"], queued[0]["id"]);
+    assert_eq!(deliveries[0]["status"], "pending");
+    assert_eq!(deliveries[0]["attempts"], 1);
+    assert_eq!(deliveries[0]["last_error"], "No relay configured for Email");
+    assert_eq!(deliveries[0]["next_attempt_at"], now.timestamp() + 60);
+}
+
+#[tokio::test]
+async fn test_slo_reports() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let player = Player::join(&sessions).await;
+    let admin = reqwest::Client::new();
+
+    let response = player
+        .client
+        .get(format!("{}/games/genections", player.base))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert_eq!(response.headers()["x-request-id"].len(), 32);
+
+    // a proxy's request id is kept rather than replaced
+    let response = admin
+        .get(format!("{}/api/changelog", player.base))
+        .header("x-request-id", "edge-7f3a")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["x-request-id"], "edge-7f3a");
+
+    let reports: Value = admin
+        .get(format!("{}/api/admin/slos", player.base))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let genections = reports
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|report| report["route"] == "/games/genections")
+        .unwrap();
+    assert!(genections["requests"].as_u64().unwrap() >= 1);
+    assert_eq!(genections["errors"], 0);
+    assert_eq!(genections["latency_target"], 99.0);
+
+    let metrics = admin
+        .get(format!("{}/api/admin/metrics", player.base))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(metrics.contains("# TYPE genedle_slo_burn_rate gauge"));
+    assert!(metrics.contains(
+        "genedle_slo_burn_rate{route=\"/games/genections\",objective=\"availability\"} 0"
+    ));
+
+    let response = reqwest::get(format!("{}/api/admin/metrics", player.base))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_deprecated_routes() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let mut player = Player::join(&sessions).await;
+    let puzzle = player.get("/games/genedle").await;
+    let puzzle = puzzle.as_str().unwrap();
+
+    let response = player
+        .client
+        .get(format!("{}/api/v1/genedle-letters/{puzzle}", player.base))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let headers = response.headers();
+    assert!(headers["deprecation"].to_str().unwrap().starts_with('@'));
+    assert!(headers.contains_key("sunset"));
+    assert!(headers["link"].to_str().unwrap().contains(&format!(
+        "</api/v1/genedle-metadata/{puzzle}>; rel=\"successor-version\""
+    )));
+
+    let response = player
+        .client
+        .get(format!("{}/api/v1/genedle-metadata/{puzzle}", player.base))
+        .send()
+        .await
+        .unwrap();
+    assert!(!response.headers().contains_key("deprecation"));
+
+    let changelog = player.get("/api/changelog").await;
+    assert!(
+        changelog
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|change| { change["deprecations"][0]["route"] == "/api/v1/genedle-letters/{id}" })
+    );
+}
+
+#[tokio::test]
+async fn test_spelling_gene_v2() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let mut player = Player::join(&sessions).await;
+    let puzzle = player.get("/games/genedle").await;
+
+    let response = player
+        .client
+        .post(format!("{}/api/v2/spelling-gene", player.base))
+        .json(&json!({"puzzle": puzzle, "num_letters": 1, "min_words": 100000}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "bad_request");
+    let message = body["message"].as_str().unwrap();
+    assert!(message.contains("min_words") && message.contains("num_letters"));
+    assert!(!message.contains("min_length"));
+
+    // a misspelt parameter is refused rather than quietly playing the default
+    let response = player
+        .client
+        .post(format!("{}/api/v2/spelling-gene", player.base))
+        .json(&json!({"puzzle": puzzle, "minWords": 5}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "invalid_data");
+
+    let response = player
+        .client
+        .post(format!("{}/api/v2/spelling-gene/guess", player.base))
+        .json(&json!({"puzzle": "not-a-puzzle", "guess": "HLA-A"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    // the defaults are the daily's, so today's pinned game answers without spelling them out
+    let daily = PuzzleId(schedule::today() - UNIX_EPOCH_DAYS_FROM_CE);
+    let result = player
+        .post(
+            "/api/v2/spelling-gene/guess",
+            json!({"puzzle": daily, "guess": "egfr"}),
+        )
+        .await;
+    assert_eq!(result, json!({"type": "valid", "data": {"symbol": "EGFR"}}));
+
+    // v1 answers the same way through v2, and points there
+    let puzzle = puzzle.as_str().unwrap();
+    let response = player
+        .client
+        .get(format!(
+            "{}/api/v1/spelling-gene/{puzzle}/0/10/7",
+            player.base
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    assert!(
+        response.headers()["link"]
+            .to_str()
+            .unwrap()
+            .ends_with("</api/v2/spelling-gene>; rel=\"successor-version\"")
+    );
+}
+
+#[tokio::test]
+async fn test_race_rooms() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let mut player = Player::join(&sessions).await;
+    let room = player.post("/games/genedle-race", json!({})).await;
+    // only the code goes out; the puzzle stays on the server
+    assert_eq!(room.as_object().unwrap().len(), 1);
+    let code = room["code"].as_str().unwrap();
+
+    let status = |path: String| {
+        let request = player
+            .client
+            .get(format!("{}{path}", player.base))
+            .header(reqwest::header::CONNECTION, "upgrade")
+            .header(reqwest::header::UPGRADE, "websocket")
+            .header(reqwest::header::SEC_WEBSOCKET_VERSION, "13")
+            .header(
+                reqwest::header::SEC_WEBSOCKET_KEY,
+                "dGhlIHNhbXBsZSBub25jZQ==",
+            )
+            .send();
+        async move { request.await.unwrap().status() }
+    };
+    assert_eq!(
+        status(format!("/games/genedle-race/{code}/spectate")).await,
+        reqwest::StatusCode::SWITCHING_PROTOCOLS
+    );
+    // a wrong code is turned away as missing, not as a full room
+    for path in [
+        "/games/genedle-race/NOPE42",
+        "/games/genedle-race/NOPE42/spectate",
+    ] {
+        assert_eq!(
+            status(path.to_string()).await,
+            reqwest::StatusCode::NOT_FOUND
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_gene_lookup() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let player = Player::join(&sessions).await;
+
+    // anything the corpus doesn't know is turned away before HGNC is asked
+    assert_eq!(
+        player.status("/api/v1/gene/NOTAGENE").await,
+        reqwest::StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        player.status("/api/v1/gene/..%2Fsearch%3Fq=BRCA1").await,
+        reqwest::StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn test_health_probes() {
+    let sessions = TrackedStore::open(&SessionBackend::Memory).unwrap();
+    let player = Player::join(&sessions).await;
+    assert_eq!(player.status("/healthz").await, reqwest::StatusCode::OK);
+
+    genedle::corpus::corpus().await.unwrap();
+    let response = player
+        .client
+        .get(format!("{}/readyz", player.base))
+        .send()
+        .await
+        .unwrap();
+    let status = response.status();
+    let readiness: Value = response.json().await.unwrap();
+    assert_eq!(readiness["corpus_loaded"], true);
+    assert_eq!(readiness["sessions_reachable"], true);
+    // other flows finish games, whose gene lookups can open the circuit when HGNC is unreachable
+    assert_eq!(
+        status == reqwest::StatusCode::OK,
+        readiness["ready"] == true,
+        "{readiness}"
+    );
+    assert_eq!(
+        readiness["ready"] == true,
+        readiness["upstream"] != "open",
+        "{readiness}"
+    );
+}
@@ -12,8 +12,12 @@ interface ValidGuess {
   result: GuessState[];
 }
 
+type InvalidHint =
+  | { hint_not_used: { letter: string; position: number } }
+  | { missing_letter: { letter: string } };
+
 type GuessResult =
-  | { type: 'invalid'; data: string }
+  | { type: 'invalid'; data: string | InvalidHint }
   | { type: 'valid'; data: ValidGuess };
 
 const getLetterClass = (state: GuessState) => {
@@ -109,7 +113,13 @@ const App: React.FC = () => {
     const result: GuessResult = await response.json();
 
     if (result.type == 'invalid') {
-      if (result.data === 'not_enough_letters') {
+      if (typeof result.data === 'object') {
+        if ('hint_not_used' in result.data) {
+          const { letter, position } = result.data.hint_not_used;
+          throw new Error(`Letter ${position + 1} must be ${letter}`);
+        }
+        throw new Error(`Guess must contain ${result.data.missing_letter.letter}`);
+      } else if (result.data === 'not_enough_letters') {
         throw new Error('Not enough letters');
       } else if (result.data === 'too_many_letters') {
         throw new Error('Too many letters');
@@ -117,6 +127,8 @@ const App: React.FC = () => {
         throw new Error('Invalid letter in guess');
       } else if (result.data === 'not_in_corpus') {
         throw new Error(`Invalid gene symbol`);
+      } else if (result.data === 'game_over') {
+        throw new Error('No guesses left');
       } else if (result.data === 'internal_error') {
         throw new Error(`Internal error`);
       } else {
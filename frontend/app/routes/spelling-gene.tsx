@@ -69,8 +69,8 @@ const App: React.FC = () => {
                     return false;
                 }
 
-                const result: boolean = await response.json();
-                return result
+                const result: { type: "valid" | "invalid" } = await response.json();
+                return result.type === "valid";
             } catch (error) {
                 return false;
             }